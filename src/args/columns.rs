@@ -0,0 +1,45 @@
+use super::Args;
+use crate::{output::out_message::OutMessage, service::v1::FIELDS};
+use clap::{Arg, ArgAction, ArgMatches};
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind, Result},
+};
+
+static ARG: &str = "columns";
+
+pub(crate) struct ArgColumns;
+
+impl Args for ArgColumns {
+    type ArgType = BTreeMap<String, String>;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .value_name("FIELD=COLUMN")
+            .value_delimiter(',')
+            .action(ArgAction::Append)
+            .help("Map fields (user, team, project, group and level) to columns of the file, e.g. user=Email,level=Access")
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<BTreeMap<String, String>> {
+        let mut columns: BTreeMap<String, String> = BTreeMap::new();
+        for value in sub_matches.get_many::<String>(ARG).unwrap_or_default() {
+            match value.split_once('=') {
+                Some((field, column)) if FIELDS.contains(&field.trim()) => {
+                    columns.insert(field.trim().to_string(), column.trim().to_string());
+                }
+                _ => {
+                    let err_msg = format!(
+                        "Column mapping {} is not valid, use FIELD=COLUMN, where FIELD is one of: {}",
+                        value,
+                        FIELDS.join(", ")
+                    );
+                    OutMessage::message_error(err_msg.as_str());
+                    return Err(Error::new(ErrorKind::InvalidInput, err_msg));
+                }
+            }
+        }
+        Ok(columns)
+    }
+}
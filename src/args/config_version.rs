@@ -0,0 +1,36 @@
+use super::Args;
+use crate::{output::out_message::OutMessage, types::common::Versions};
+use clap::{Arg, ArgMatches};
+use std::{
+    io::{Error, Result},
+    str::FromStr,
+};
+
+static ARG: &str = "to";
+
+pub(crate) struct ArgConfigVersion;
+
+impl Args for ArgConfigVersion {
+    type ArgType = Versions;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .short('t')
+            .value_name("VERSION")
+            .help("Provide a version of the config file to migrate to")
+            .default_value("v2")
+            .value_parser(["v1", "v2"])
+    }
+
+    fn parse<'a>(sub_matches: &'_ ArgMatches) -> Result<Versions> {
+        sub_matches
+            .get_one::<String>(ARG)
+            .ok_or_else(|| {
+                let err_msg = "Config version is not provided";
+                OutMessage::message_error(err_msg);
+                Error::new(std::io::ErrorKind::InvalidInput, err_msg)
+            })
+            .and_then(|value| Versions::from_str(value))
+    }
+}
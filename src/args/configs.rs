@@ -0,0 +1,35 @@
+use super::Args;
+use crate::output::out_message::OutMessage;
+use clap::{Arg, ArgMatches};
+use std::io::{Error, Result};
+
+static ARG: &str = "configs";
+
+pub(crate) struct ArgConfigs;
+
+impl Args for ArgConfigs {
+    type ArgType = (String, String);
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .value_names(["OLD", "NEW"])
+            .help("Provide two config files to compare")
+            .num_args(2)
+            .required(true)
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<(String, String)> {
+        let configs: Vec<String> = sub_matches
+            .get_many::<String>(ARG)
+            .map(|configs| configs.cloned().collect())
+            .unwrap_or_default();
+        match &configs[..] {
+            [old, new] => Ok((old.clone(), new.clone())),
+            _ => {
+                let err_msg = "Two config files must be provided";
+                OutMessage::message_error(err_msg);
+                Err(Error::new(std::io::ErrorKind::InvalidInput, err_msg))
+            }
+        }
+    }
+}
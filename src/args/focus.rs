@@ -0,0 +1,22 @@
+use super::Args;
+use clap::{Arg, ArgMatches};
+use std::io::Result;
+
+static ARG: &str = "focus";
+
+pub(crate) struct ArgFocus;
+
+impl Args for ArgFocus {
+    type ArgType = Option<String>;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .value_name("NODE")
+            .help("Show only what is connected to this node, e.g. user:42, team:backend or project:10")
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<Option<String>> {
+        Ok(sub_matches.get_one::<String>(ARG).cloned())
+    }
+}
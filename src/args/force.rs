@@ -0,0 +1,22 @@
+use super::Args;
+use clap::{Arg, ArgAction, ArgMatches};
+use std::io::Result;
+
+static ARG: &str = "force";
+
+pub(crate) struct ArgForce;
+
+impl Args for ArgForce {
+    type ArgType = bool;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .action(ArgAction::SetTrue)
+            .help("Use it if you know what you're doing")
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<bool> {
+        Ok(sub_matches.get_flag(ARG))
+    }
+}
@@ -0,0 +1,31 @@
+use super::Args;
+use crate::output::out_message::OutMessage;
+use clap::{Arg, ArgMatches};
+use std::io::{Error, Result};
+
+static ARG: &str = "format";
+
+pub(crate) struct ArgFormat;
+
+impl Args for ArgFormat {
+    type ArgType = String;
+
+    // Possible values and the default one are set by commands
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .value_name("FORMAT")
+            .help("Provide the output format")
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<String> {
+        sub_matches
+            .get_one::<String>(ARG)
+            .ok_or_else(|| {
+                let err_msg = "Output format is not provided";
+                OutMessage::message_error(err_msg);
+                Error::new(std::io::ErrorKind::InvalidInput, err_msg)
+            })
+            .map(|value| value.to_string())
+    }
+}
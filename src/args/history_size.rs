@@ -0,0 +1,29 @@
+use super::Args;
+use crate::output::out_message::OutMessage;
+use clap::{value_parser, Arg, ArgMatches};
+use std::io::{Error, Result};
+
+static ARG: &str = "history-size";
+
+pub(crate) struct ArgHistorySize;
+
+impl Args for ArgHistorySize {
+    type ArgType = usize;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .value_name("SIZE")
+            .help("Provide how many previous states should be kept, at least one")
+            .default_value("10")
+            .value_parser(value_parser!(u64).range(1..))
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<usize> {
+        sub_matches.get_one::<u64>(ARG).map(|size| *size as usize).ok_or_else(|| {
+            let err_msg = "History size is not specified";
+            OutMessage::message_error(err_msg);
+            Error::new(std::io::ErrorKind::InvalidInput, err_msg)
+        })
+    }
+}
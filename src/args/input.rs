@@ -0,0 +1,25 @@
+use super::Args;
+use clap::{Arg, ArgMatches};
+use std::io::{Error, ErrorKind, Result};
+
+static ARG: &str = "input";
+
+pub(crate) struct ArgInput;
+
+impl Args for ArgInput {
+    type ArgType = String;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .value_name("FILE")
+            .help("Provide a file to import")
+            .required(true)
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<String> {
+        sub_matches
+            .get_one::<String>(ARG)
+            .cloned()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "A file to import is not provided"))
+    }
+}
@@ -1,18 +1,39 @@
 mod access_level;
+mod check;
+mod columns;
+mod config_version;
+mod configs;
 mod dry_run;
 mod file_name;
+mod focus;
+mod force;
+mod format;
 mod gitlab_token;
 mod gitlab_url;
 mod group_id;
 mod group_list;
+mod history_size;
+mod input;
 mod large_out;
 mod namespace_id;
 mod no_confirm;
+mod object_group;
+mod object_project;
+mod online;
+mod output;
 mod project_id;
+mod revision;
+mod revisions;
 mod shell;
+mod sources;
 mod state_destination;
 mod state_source;
+mod state_store;
+mod subject;
+mod subject_type;
+mod target;
 mod team_name;
+mod team_role;
 mod user_id;
 mod write_state;
 
@@ -20,20 +41,41 @@ mod write_state;
 // Each Argument should be exported like this
 
 pub(crate) use self::access_level::ArgAccess;
+pub(crate) use self::check::ArgCheck;
+pub(crate) use self::columns::ArgColumns;
+pub(crate) use self::config_version::ArgConfigVersion;
+pub(crate) use self::configs::ArgConfigs;
 pub(crate) use self::dry_run::ArgDryRun;
 pub(crate) use self::file_name::ArgFileName;
+pub(crate) use self::focus::ArgFocus;
+pub(crate) use self::force::ArgForce;
+pub(crate) use self::format::ArgFormat;
 pub(crate) use self::gitlab_token::ArgGitlabToken;
 pub(crate) use self::gitlab_url::ArgGitlabUrl;
 pub(crate) use self::group_id::ArgGroupId;
 pub(crate) use self::group_list::ArgGroupList;
+pub(crate) use self::history_size::ArgHistorySize;
+pub(crate) use self::input::ArgInput;
 pub(crate) use self::large_out::ArgLargeOut;
 pub(crate) use self::namespace_id::ArgNamespaceId;
 pub(crate) use self::no_confirm::ArgNoConfirm;
+pub(crate) use self::object_group::ArgObjectGroup;
+pub(crate) use self::object_project::ArgObjectProject;
+pub(crate) use self::online::ArgOnline;
+pub(crate) use self::output::ArgOutput;
 pub(crate) use self::project_id::ArgProjectId;
+pub(crate) use self::revision::ArgRevision;
+pub(crate) use self::revisions::ArgRevisions;
 pub(crate) use self::shell::ArgShell;
+pub(crate) use self::sources::ArgSources;
 pub(crate) use self::state_destination::ArgStateDestination;
 pub(crate) use self::state_source::ArgStateSource;
+pub(crate) use self::state_store::ArgStateStore;
+pub(crate) use self::subject::ArgSubject;
+pub(crate) use self::subject_type::ArgSubjectType;
+pub(crate) use self::target::ArgTarget;
 pub(crate) use self::team_name::ArgTeamName;
+pub(crate) use self::team_role::ArgTeamRole;
 pub(crate) use self::user_id::ArgUserId;
 pub(crate) use self::write_state::ArgWriteState;
 
@@ -61,7 +103,6 @@ use std::io::Result;
 ///     })
 /// }
 /// --------------------------------------------------------------------------
-
 pub(crate) trait Args {
     type ArgType;
     fn add() -> Arg;
@@ -0,0 +1,24 @@
+use super::Args;
+use clap::{value_parser, Arg, ArgMatches};
+use std::io::Result;
+
+static ARG: &str = "group";
+
+pub(crate) struct ArgObjectGroup;
+
+impl Args for ArgObjectGroup {
+    type ArgType = Option<u64>;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .value_name("GROUP_ID")
+            .help("Provide the id of a GitLab group the subject has access to")
+            .conflicts_with("project")
+            .value_parser(value_parser!(u64))
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<Option<u64>> {
+        Ok(sub_matches.get_one::<u64>(ARG).copied())
+    }
+}
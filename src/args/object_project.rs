@@ -0,0 +1,24 @@
+use super::Args;
+use clap::{value_parser, Arg, ArgMatches};
+use std::io::Result;
+
+static ARG: &str = "project";
+
+pub(crate) struct ArgObjectProject;
+
+impl Args for ArgObjectProject {
+    type ArgType = Option<u64>;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .value_name("PROJECT_ID")
+            .help("Provide the id of a GitLab project the subject has access to")
+            .conflicts_with("group")
+            .value_parser(value_parser!(u64))
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<Option<u64>> {
+        Ok(sub_matches.get_one::<u64>(ARG).copied())
+    }
+}
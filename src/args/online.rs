@@ -0,0 +1,23 @@
+use clap::{Arg, ArgAction, ArgMatches};
+use std::io::Result;
+
+use super::Args;
+
+static ARG: &str = "online";
+pub(crate) struct ArgOnline;
+
+impl Args for ArgOnline {
+    type ArgType = bool;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .action(ArgAction::SetTrue)
+            .num_args(0)
+            .help("Use if you wanna check things that need GitLab too, e.g. the users policy")
+    }
+
+    fn parse(sub_matches: &ArgMatches) -> Result<Self::ArgType> {
+        Ok(sub_matches.get_flag(ARG))
+    }
+}
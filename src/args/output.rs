@@ -0,0 +1,23 @@
+use super::Args;
+use clap::{Arg, ArgMatches};
+use std::io::Result;
+
+static ARG: &str = "output";
+
+pub(crate) struct ArgOutput;
+
+impl Args for ArgOutput {
+    type ArgType = Option<String>;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .short('o')
+            .value_name("PATH")
+            .help("Write the output to this path instead of stdout")
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<Option<String>> {
+        Ok(sub_matches.get_one::<String>(ARG).cloned())
+    }
+}
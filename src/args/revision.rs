@@ -0,0 +1,22 @@
+use super::Args;
+use clap::{value_parser, Arg, ArgMatches};
+use std::io::Result;
+
+static ARG: &str = "revision";
+
+pub(crate) struct ArgRevision;
+
+impl Args for ArgRevision {
+    type ArgType = Option<u64>;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .value_name("REV")
+            .help("Provide a revision of the state, check them out with `gum state list`")
+            .value_parser(value_parser!(u64))
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<Option<u64>> {
+        Ok(sub_matches.get_one::<u64>(ARG).copied())
+    }
+}
@@ -0,0 +1,36 @@
+use super::Args;
+use crate::output::out_message::OutMessage;
+use clap::{value_parser, Arg, ArgMatches};
+use std::io::{Error, Result};
+
+static ARG: &str = "revisions";
+
+pub(crate) struct ArgRevisions;
+
+impl Args for ArgRevisions {
+    type ArgType = (u64, u64);
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .value_names(["A", "B"])
+            .help("Provide two revisions of the state to compare")
+            .num_args(2)
+            .required(true)
+            .value_parser(value_parser!(u64))
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<(u64, u64)> {
+        let revs: Vec<u64> = sub_matches
+            .get_many::<u64>(ARG)
+            .map(|revs| revs.copied().collect())
+            .unwrap_or_default();
+        match revs[..] {
+            [a, b] => Ok((a, b)),
+            _ => {
+                let err_msg = "Two revisions must be provided";
+                OutMessage::message_error(err_msg);
+                Err(Error::new(std::io::ErrorKind::InvalidInput, err_msg))
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use clap::{Arg, ArgAction, ArgMatches};
+use std::io::Result;
+
+use super::Args;
+
+static ARG: &str = "sources";
+pub(crate) struct ArgSources;
+
+impl Args for ArgSources {
+    type ArgType = bool;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .action(ArgAction::SetTrue)
+            .num_args(0)
+            .help("Use if you wanna see where the access comes from")
+    }
+
+    fn parse(sub_matches: &ArgMatches) -> Result<Self::ArgType> {
+        Ok(sub_matches.get_flag(ARG))
+    }
+}
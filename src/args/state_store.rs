@@ -0,0 +1,32 @@
+use super::Args;
+use crate::output::out_message::OutMessage;
+use clap::{Arg, ArgMatches};
+use std::io::{Error, Result};
+
+static ARG: &str = "to";
+
+pub(crate) struct ArgStateStore;
+
+impl Args for ArgStateStore {
+    type ArgType = String;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .short('t')
+            .value_name("STORE")
+            .help("Provide a store for the state: `config`, `file:<path>` or an http(s) address")
+            .required(true)
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<String> {
+        sub_matches
+            .get_one::<String>(ARG)
+            .ok_or_else(|| {
+                let err_msg = "State store is not provided";
+                OutMessage::message_error(err_msg);
+                Error::new(std::io::ErrorKind::InvalidInput, err_msg)
+            })
+            .map(|value| value.to_string())
+    }
+}
@@ -0,0 +1,24 @@
+use super::Args;
+use clap::{value_parser, Arg, ArgMatches};
+use std::io::Result;
+
+static ARG: &str = "subject";
+
+pub(crate) struct ArgSubject;
+
+impl Args for ArgSubject {
+    type ArgType = Option<u64>;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .short('s')
+            .value_name("SUBJECT_ID")
+            .help("Provide the id of a user or a group that is kept in the state")
+            .value_parser(value_parser!(u64))
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<Option<u64>> {
+        Ok(sub_matches.get_one::<u64>(ARG).copied())
+    }
+}
@@ -0,0 +1,35 @@
+use super::Args;
+use crate::{output::out_message::OutMessage, types::v1::EntityType};
+use clap::{Arg, ArgMatches};
+use std::{
+    io::{Error, Result},
+    str::FromStr,
+};
+
+static ARG: &str = "subject-type";
+
+pub(crate) struct ArgSubjectType;
+
+impl Args for ArgSubjectType {
+    type ArgType = EntityType;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .value_name("TYPE")
+            .help("Provide the type of the subject, it's used only when the subject is new")
+            .default_value("user")
+            .value_parser(["user", "group"])
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<EntityType> {
+        sub_matches
+            .get_one::<String>(ARG)
+            .ok_or_else(|| {
+                let err_msg = "Subject type is not provided";
+                OutMessage::message_error(err_msg);
+                Error::new(std::io::ErrorKind::InvalidInput, err_msg)
+            })
+            .and_then(|value| EntityType::from_str(value))
+    }
+}
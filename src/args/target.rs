@@ -0,0 +1,27 @@
+use super::Args;
+use crate::output::out_message::OutMessage;
+use clap::{Arg, ArgMatches};
+use std::io::{Error, Result};
+
+static ARG: &str = "target";
+
+pub(crate) struct ArgTarget;
+
+impl Args for ArgTarget {
+    type ArgType = String;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .value_name("NAME")
+            .help("Provide the entity to describe")
+            .required(true)
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<String> {
+        sub_matches.get_one::<String>(ARG).cloned().ok_or_else(|| {
+            let err_msg = "Nothing to describe";
+            OutMessage::message_error(err_msg);
+            Error::new(std::io::ErrorKind::InvalidInput, err_msg)
+        })
+    }
+}
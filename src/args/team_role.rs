@@ -0,0 +1,36 @@
+use super::Args;
+use crate::{output::out_message::OutMessage, types::v1::TeamRole};
+use clap::{Arg, ArgMatches};
+use std::{
+    io::{Error, Result},
+    str::FromStr,
+};
+
+static ARG: &str = "role";
+
+pub(crate) struct ArgTeamRole;
+
+impl Args for ArgTeamRole {
+    type ArgType = TeamRole;
+
+    fn add() -> Arg {
+        Arg::new(ARG)
+            .long(ARG)
+            .short('r')
+            .value_name("ROLE")
+            .help("Provide a role of the member in the team, a lead gets Maintainer instead of Developer")
+            .default_value("member")
+            .value_parser(["member", "lead"])
+    }
+
+    fn parse(sub_matches: &'_ ArgMatches) -> Result<TeamRole> {
+        sub_matches
+            .get_one::<String>(ARG)
+            .ok_or_else(|| {
+                let err_msg = "Team role is not provided";
+                OutMessage::message_error(err_msg);
+                Error::new(std::io::ErrorKind::InvalidInput, err_msg)
+            })
+            .and_then(|value| TeamRole::from_str(value))
+    }
+}
@@ -1,35 +1,35 @@
-use crate::output::out_message::OutMessage;
+use crate::{output::out_message::OutMessage, types::v1::UserRef};
 
 use super::Args;
 use clap::{Arg, ArgMatches};
-use std::io::{Error, ErrorKind, Result};
+use std::{
+    io::{Error, Result},
+    str::FromStr,
+};
 
 static ARG: &str = "user-id";
 
 pub(crate) struct ArgUserId;
 
 impl Args for ArgUserId {
-    type ArgType = u64;
+    type ArgType = UserRef;
 
     fn add() -> Arg {
         Arg::new(ARG)
             .short('u')
             .long(ARG)
-            .value_name("USER_ID")
-            .help("Provide the id of the GitLab user")
-            .default_value("-1")
+            .value_name("USER")
+            .help("Provide the id, the username or the email of the GitLab user")
             .global(true)
     }
 
-    fn parse<'a>(sub_matches: &'_ ArgMatches) -> Result<u64> {
+    fn parse<'a>(sub_matches: &'_ ArgMatches) -> Result<UserRef> {
         sub_matches.get_one::<String>(ARG)
         .ok_or_else(|| {
-            let err_msg = "User ID is incorrect";
+            let err_msg = "User is not specified";
             OutMessage::message_error(err_msg);
             Error::new(std::io::ErrorKind::InvalidInput, err_msg)
         })
-        .and_then(|value| {
-            return Ok(value.parse::<u64>().unwrap());
-        })
+        .and_then(|value| UserRef::from_str(value))
     }
 }
@@ -1,7 +1,7 @@
 use clap::{Command, ArgMatches};
 use crate::cmd::{
-    GenerateCmd, groups::{self, add_groups_cmd}, InitCmd, search::{self, add_search_cmd},
-    SyncCmd, teams::{self,add_teams_cmd}, upgrade::{self, add_upgrade_cmd}, users::{self, add_users_cmd}, Cmd, CmdOld,
+    describe::{self, add_describe_cmd}, DiffCmd, export::{self, add_export_cmd}, GenerateCmd, GraphCmd, groups::{self, add_groups_cmd}, import::{self, add_import_cmd}, InitCmd, MigrateCmd, policy::{self, add_policy_cmd}, RefreshCmd, report::{self, add_report_cmd}, search::{self, add_search_cmd}, state::{self, add_state_cmd},
+    SyncCmd, teams::{self,add_teams_cmd}, upgrade::{self, add_upgrade_cmd}, users::{self, add_users_cmd}, ValidateCmd, Cmd, CmdOld,
 };
 
 use std::io::{Result, Error, ErrorKind};
@@ -21,6 +21,17 @@ pub(crate) fn build() -> Command {
         .subcommand(SyncCmd::add())
         .subcommand(add_upgrade_cmd())
         .subcommand(add_groups_cmd())
+        .subcommand(add_state_cmd())
+        .subcommand(MigrateCmd::add())
+        .subcommand(ValidateCmd::add())
+        .subcommand(RefreshCmd::add())
+        .subcommand(add_describe_cmd())
+        .subcommand(add_policy_cmd())
+        .subcommand(add_export_cmd())
+        .subcommand(add_report_cmd())
+        .subcommand(GraphCmd::add())
+        .subcommand(DiffCmd::add())
+        .subcommand(add_import_cmd())
 }
 
 pub(crate) fn exec(matches: ArgMatches) -> Result<()> {
@@ -43,6 +54,36 @@ pub(crate) fn exec(matches: ArgMatches) -> Result<()> {
                 Err(err) => Err(err),
             }
         }
+        Some(("migrate", sub_matches)) => {
+            match MigrateCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            }
+        }
+        Some(("validate", sub_matches)) => {
+            match ValidateCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            }
+        }
+        Some(("refresh", sub_matches)) => {
+            match RefreshCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            }
+        }
+        Some(("graph", sub_matches)) => {
+            match GraphCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            }
+        }
+        Some(("diff", sub_matches)) => {
+            match DiffCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            }
+        }
         Some(("users", sub_matches)) => {
             return match users::prepare(sub_matches) {
                 Ok(cmd) => cmd.exec(),
@@ -62,6 +103,42 @@ pub(crate) fn exec(matches: ArgMatches) -> Result<()> {
                 Err(err) => Err(err),
             };
         }
+        Some(("state", sub_matches)) => {
+            return match state::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            };
+        }
+        Some(("describe", sub_matches)) => {
+            return match describe::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            };
+        }
+        Some(("policy", sub_matches)) => {
+            return match policy::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            };
+        }
+        Some(("export", sub_matches)) => {
+            return match export::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            };
+        }
+        Some(("report", sub_matches)) => {
+            return match report::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            };
+        }
+        Some(("import", sub_matches)) => {
+            return match import::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            };
+        }
         Some(("search", sub_matches)) => {
             return match search::prepare(sub_matches) {
                 Ok(cmd) => cmd.exec(),
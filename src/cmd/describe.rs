@@ -0,0 +1,68 @@
+mod group_cmd;
+mod project_cmd;
+mod team_cmd;
+mod user_cmd;
+
+use std::io::{Error, ErrorKind, Result};
+
+use clap::{ArgMatches, Command};
+
+use crate::output::out_message::OutMessage;
+
+use self::{group_cmd::GroupCmd, project_cmd::ProjectCmd, team_cmd::TeamCmd, user_cmd::UserCmd};
+
+use super::{Cmd, CmdOld};
+
+pub(crate) fn add_describe_cmd() -> Command {
+    // Register command
+    Command::new("describe")
+        .about("Show everything the config knows about a user, a team, a group or a project")
+        .arg_required_else_help(true)
+        .subcommand(UserCmd::add())
+        .subcommand(TeamCmd::add())
+        .subcommand(GroupCmd::add())
+        .subcommand(ProjectCmd::add())
+}
+
+pub(crate) struct DescribeCmd<'a> {
+    describe_sub: Option<(&'a str, &'a ArgMatches)>,
+}
+
+pub(crate) fn prepare<'a>(sub_matches: &'a ArgMatches) -> Result<impl CmdOld<'a>> {
+    Ok(DescribeCmd {
+        describe_sub: sub_matches.subcommand(),
+    })
+}
+
+impl<'a> CmdOld<'a> for DescribeCmd<'a> {
+    fn exec(&self) -> Result<()> {
+        match self.describe_sub {
+            Some(("user", sub_matches)) => match UserCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("team", sub_matches)) => match TeamCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("group", sub_matches)) => match GroupCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("project", sub_matches)) => match ProjectCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+// Groups and projects are described by their ids
+fn parse_id(target: &str) -> Result<u64> {
+    target.parse::<u64>().map_err(|_| {
+        let err_msg = format!("{} is not a valid id", target);
+        OutMessage::message_error(err_msg.as_str());
+        Error::new(ErrorKind::InvalidInput, err_msg)
+    })
+}
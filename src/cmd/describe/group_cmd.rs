@@ -0,0 +1,45 @@
+use crate::args::{ArgFileName, ArgTarget, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+use super::parse_id;
+
+pub(crate) struct GroupCmd {
+    file_name: String,
+    target: u64,
+}
+
+impl Cmd for GroupCmd {
+    type CmdType = GroupCmd;
+
+    fn add() -> Command {
+        Command::new("group")
+            .about("Show what the group is shared with and who has access to it")
+            .after_help("$ gum describe group 42 -f gum-config-example.yaml")
+            .arg(ArgTarget::add().value_name("GROUP_ID"))
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            target: parse_id(&ArgTarget::parse(sub_matches)?)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl GroupCmd {
+    fn exec_v1(&self) -> Result<()> {
+        v1::DescribeService::new(self.file_name.clone())?.group(self.target)
+    }
+}
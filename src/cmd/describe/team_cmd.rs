@@ -0,0 +1,43 @@
+use crate::args::{ArgFileName, ArgTarget, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct TeamCmd {
+    file_name: String,
+    target: String,
+}
+
+impl Cmd for TeamCmd {
+    type CmdType = TeamCmd;
+
+    fn add() -> Command {
+        Command::new("team")
+            .about("Show members and grants of the team")
+            .after_help("$ gum describe team backend -f gum-config-example.yaml")
+            .arg(ArgTarget::add().value_name("TEAM"))
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            target: ArgTarget::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl TeamCmd {
+    fn exec_v1(&self) -> Result<()> {
+        v1::DescribeService::new(self.file_name.clone())?.team(&self.target)
+    }
+}
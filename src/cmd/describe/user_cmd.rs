@@ -0,0 +1,43 @@
+use crate::args::{ArgFileName, ArgTarget, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::{ConfigFile, UserRef};
+use clap::{ArgMatches, Command};
+use std::{io::Result, str::FromStr};
+
+pub(crate) struct UserCmd {
+    file_name: String,
+    target: UserRef,
+}
+
+impl Cmd for UserCmd {
+    type CmdType = UserCmd;
+
+    fn add() -> Command {
+        Command::new("user")
+            .about("Show grants, teams and the effective access of the user")
+            .after_help("$ gum describe user allanger -f gum-config-example.yaml")
+            .arg(ArgTarget::add().value_name("ID|USERNAME"))
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            target: UserRef::from_str(&ArgTarget::parse(sub_matches)?)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl UserCmd {
+    fn exec_v1(&self) -> Result<()> {
+        v1::DescribeService::new(self.file_name.clone())?.user(&self.target)
+    }
+}
@@ -0,0 +1,53 @@
+use std::io::Result;
+
+use clap::{ArgMatches, Command};
+
+use crate::{
+    args::{ArgConfigs, Args},
+    service::v1,
+    types::{
+        common::{Version, Versions},
+        v1::ConfigFile,
+    },
+};
+
+use super::Cmd;
+
+pub(crate) struct DiffCmd {
+    configs: (String, String),
+}
+
+impl Cmd for DiffCmd {
+    type CmdType = DiffCmd;
+
+    fn add() -> Command {
+        Command::new("diff")
+            .about("Show how the access changes between two config files")
+            .after_help("$ gum diff gum-config.yaml new/gum-config.yaml")
+            .before_help("Both configs are expanded the same way as by `sync`, but GitLab is not used. Nothing is printed if they give the same access")
+            .arg(ArgConfigs::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> Result<DiffCmd> {
+        Ok(DiffCmd {
+            configs: ArgConfigs::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> Result<()> {
+        let (old, new) = &self.configs;
+        match (
+            ConfigFile::read(old.clone())?.get_version()?,
+            ConfigFile::read(new.clone())?.get_version()?,
+        ) {
+            (Versions::V1 | Versions::V2, Versions::V1 | Versions::V2) => self.exec_v1(),
+        }
+    }
+}
+
+impl DiffCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let (old, new) = &self.configs;
+        v1::DiffService::new(old.clone(), new.clone())?.diff()
+    }
+}
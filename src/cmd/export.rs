@@ -0,0 +1,39 @@
+mod matrix_cmd;
+
+use std::io::Result;
+
+use clap::{ArgMatches, Command};
+
+use self::matrix_cmd::MatrixCmd;
+
+use super::{Cmd, CmdOld};
+
+pub(crate) fn add_export_cmd() -> Command {
+    // Register command
+    Command::new("export")
+        .about("Export the access that is defined by the config")
+        .arg_required_else_help(true)
+        .subcommand(MatrixCmd::add())
+}
+
+pub(crate) struct ExportCmd<'a> {
+    export_sub: Option<(&'a str, &'a ArgMatches)>,
+}
+
+pub(crate) fn prepare<'a>(sub_matches: &'a ArgMatches) -> Result<impl CmdOld<'a>> {
+    Ok(ExportCmd {
+        export_sub: sub_matches.subcommand(),
+    })
+}
+
+impl<'a> CmdOld<'a> for ExportCmd<'a> {
+    fn exec(&self) -> Result<()> {
+        match self.export_sub {
+            Some(("matrix", sub_matches)) => match MatrixCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            _ => Ok(()),
+        }
+    }
+}
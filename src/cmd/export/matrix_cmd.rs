@@ -0,0 +1,77 @@
+use crate::args::{
+    ArgFileName, ArgFormat, ArgGitlabToken, ArgGitlabUrl, ArgOutput, ArgSources, Args,
+};
+use crate::cmd::Cmd;
+use crate::gitlab::GitlabApi;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct MatrixCmd {
+    file_name: String,
+    gitlab_token: String,
+    gitlab_url: String,
+    format: String,
+    sources: bool,
+    output: Option<String>,
+}
+
+impl Cmd for MatrixCmd {
+    type CmdType = MatrixCmd;
+
+    fn add() -> Command {
+        Command::new("matrix")
+            .about("Export a table of users and groups against projects and groups they have access to")
+            .after_help("$ gum export matrix -f gum-config-example.yaml --format markdown --sources -o access.md")
+            .before_help("Access is expanded the same way as by `sync`, but the state is not used")
+            .arg(ArgFileName::add())
+            .arg(ArgGitlabToken::add())
+            .arg(ArgGitlabUrl::add())
+            .arg(
+                ArgFormat::add()
+                    .value_parser(["csv", "markdown", "json"])
+                    .default_value("csv"),
+            )
+            .arg(ArgSources::add())
+            .arg(ArgOutput::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            gitlab_token: ArgGitlabToken::parse(sub_matches)?,
+            gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
+            format: ArgFormat::parse(sub_matches)?,
+            sources: ArgSources::parse(sub_matches)?,
+            output: ArgOutput::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl MatrixCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let mut svc = v1::SyncService::new(
+            self.file_name.clone(),
+            GitlabApi::lazy(&self.gitlab_url, &self.gitlab_token),
+            String::new(),
+            String::new(),
+            false,
+            0,
+        );
+        svc.read_config()?
+            .resolve_paths()?
+            .resolve_users()?
+            .plan_state()?;
+        let (config, state) = svc.planned();
+        let text = v1::Matrix::new(config, state, self.sources).render(&self.format)?;
+        v1::write_output(&self.output, &text)
+    }
+}
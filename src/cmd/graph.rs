@@ -0,0 +1,67 @@
+use std::io::Result;
+
+use clap::{ArgMatches, Command};
+
+use crate::{
+    args::{ArgFileName, ArgFocus, ArgFormat, ArgOutput, Args},
+    service::v1,
+    types::{
+        common::{Version, Versions},
+        v1::{ConfigFile, LockFile},
+    },
+};
+
+use super::Cmd;
+
+pub(crate) struct GraphCmd {
+    file_name: String,
+    format: String,
+    focus: Option<String>,
+    output: Option<String>,
+}
+
+impl Cmd for GraphCmd {
+    type CmdType = GraphCmd;
+
+    fn add() -> Command {
+        Command::new("graph")
+            .about("Draw users, teams, projects and groups and the access between them")
+            .after_help("$ gum graph -f gum-config-example.yaml --format mermaid --focus user:42")
+            .before_help("The graph is built from the config and its lock file, GitLab is not queried")
+            .arg(ArgFileName::add())
+            .arg(
+                ArgFormat::add()
+                    .value_parser(["dot", "mermaid"])
+                    .default_value("dot"),
+            )
+            .arg(ArgFocus::add())
+            .arg(ArgOutput::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> Result<GraphCmd> {
+        Ok(GraphCmd {
+            file_name: ArgFileName::parse(sub_matches)?,
+            format: ArgFormat::parse(sub_matches)?,
+            focus: ArgFocus::parse(sub_matches)?,
+            output: ArgOutput::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl GraphCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let config_file = ConfigFile::read(self.file_name.clone())?;
+        let lock_file = LockFile::read(LockFile::file_name_for(&self.file_name))?;
+        let mut graph = v1::Graph::new(config_file.config(), &lock_file)?;
+        if let Some(focus) = &self.focus {
+            graph = graph.focus(focus)?;
+        }
+        v1::write_output(&self.output, &graph.render(&self.format)?)
+    }
+}
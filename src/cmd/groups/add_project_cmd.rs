@@ -48,7 +48,7 @@ impl Cmd for AddProjectCmd {
 
     fn exec(&self) -> std::io::Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 | Versions::V2 => self.exec_v1(),
         }
     }
 }
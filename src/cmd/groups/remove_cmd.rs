@@ -31,7 +31,7 @@ impl Cmd for RemoveCmd {
 
     fn exec(&self) -> std::io::Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 | Versions::V2 => self.exec_v1(),
         }
     }
 }
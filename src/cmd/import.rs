@@ -0,0 +1,39 @@
+mod csv_cmd;
+
+use std::io::Result;
+
+use clap::{ArgMatches, Command};
+
+use self::csv_cmd::CsvCmd;
+
+use super::{Cmd, CmdOld};
+
+pub(crate) fn add_import_cmd() -> Command {
+    // Register command
+    Command::new("import")
+        .about("Import users and their access to the config")
+        .arg_required_else_help(true)
+        .subcommand(CsvCmd::add())
+}
+
+pub(crate) struct ImportCmd<'a> {
+    import_sub: Option<(&'a str, &'a ArgMatches)>,
+}
+
+pub(crate) fn prepare<'a>(sub_matches: &'a ArgMatches) -> Result<impl CmdOld<'a>> {
+    Ok(ImportCmd {
+        import_sub: sub_matches.subcommand(),
+    })
+}
+
+impl<'a> CmdOld<'a> for ImportCmd<'a> {
+    fn exec(&self) -> Result<()> {
+        match self.import_sub {
+            Some(("csv", sub_matches)) => match CsvCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            _ => Ok(()),
+        }
+    }
+}
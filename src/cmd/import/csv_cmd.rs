@@ -0,0 +1,63 @@
+use crate::args::{
+    ArgColumns, ArgDryRun, ArgFileName, ArgGitlabToken, ArgGitlabUrl, ArgInput, Args,
+};
+use crate::cmd::Cmd;
+use crate::gitlab::GitlabApi;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::collections::BTreeMap;
+use std::io::Result;
+
+pub(crate) struct CsvCmd {
+    file_name: String,
+    input: String,
+    columns: BTreeMap<String, String>,
+    dry_run: bool,
+    gitlab_token: String,
+    gitlab_url: String,
+}
+
+impl Cmd for CsvCmd {
+    type CmdType = CsvCmd;
+
+    fn add() -> Command {
+        Command::new("csv")
+            .about("Add users from a csv file to the config and give them teams, projects and groups")
+            .after_help("$ gum import csv onboarding.csv -f gum-config-example.yaml --columns user=Email,level=Access --dry-run")
+            .before_help("A row has a user (an id, a username or an email) and optionally a team, a project and a group with the level of the access. Columns are named as fields unless they're mapped")
+            .arg(ArgInput::add())
+            .arg(ArgColumns::add())
+            .arg(ArgDryRun::add())
+            .arg(ArgFileName::add())
+            .arg(ArgGitlabToken::add())
+            .arg(ArgGitlabUrl::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            input: ArgInput::parse(sub_matches)?,
+            columns: ArgColumns::parse(sub_matches)?,
+            dry_run: ArgDryRun::parse(sub_matches)?,
+            gitlab_token: ArgGitlabToken::parse(sub_matches)?,
+            gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl CsvCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let rows = v1::ImportService::read_csv(&self.input, &self.columns)?;
+        v1::ImportService::new(self.file_name.clone())?
+            .import(&GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?, &rows)?
+            .write_state(self.dry_run)
+    }
+}
@@ -0,0 +1,47 @@
+use std::io::Result;
+
+use clap::{ArgMatches, Command};
+
+use crate::{
+    args::{ArgCheck, ArgConfigVersion, ArgFileName, Args},
+    service::MigrateService,
+    types::common::Versions,
+};
+
+use super::Cmd;
+
+pub(crate) struct MigrateCmd {
+    file_name: String,
+    to: Versions,
+    check: bool,
+}
+
+impl Cmd for MigrateCmd {
+    type CmdType = MigrateCmd;
+
+    fn add() -> Command {
+        Command::new("migrate")
+            .about("Migrate the config file to another version")
+            .after_help("$ gum migrate --to v2 -f gum-config-example.yaml")
+            .before_help("Use this command if you want to upgrade your config file to a newer version without losing data")
+            .arg(ArgFileName::add())
+            .arg(ArgConfigVersion::add())
+            .arg(ArgCheck::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> Result<MigrateCmd> {
+        Ok(MigrateCmd {
+            file_name: ArgFileName::parse(sub_matches)?,
+            to: ArgConfigVersion::parse(sub_matches)?,
+            check: ArgCheck::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> Result<()> {
+        let mut svc = MigrateService::new(self.file_name.clone())?;
+        if self.check {
+            return svc.check(self.to);
+        }
+        svc.migrate(self.to)?.write_config()
+    }
+}
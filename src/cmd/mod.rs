@@ -1,15 +1,31 @@
+pub(crate) mod describe;
+pub(crate) mod diff;
+pub(crate) mod export;
 pub mod generate;
+pub(crate) mod graph;
 pub(crate) mod groups;
+pub(crate) mod import;
 pub(crate) mod init;
+pub(crate) mod migrate;
+pub(crate) mod policy;
+pub(crate) mod refresh;
+pub(crate) mod report;
 pub(crate) mod search;
+pub(crate) mod state;
 pub(crate) mod sync;
 pub(crate) mod teams;
 pub(crate) mod upgrade;
 pub(crate) mod users;
+pub(crate) mod validate;
 
+pub(crate) use self::diff::DiffCmd;
 pub(crate) use self::generate::GenerateCmd;
+pub(crate) use self::graph::GraphCmd;
 pub(crate) use self::init::InitCmd;
+pub(crate) use self::migrate::MigrateCmd;
+pub(crate) use self::refresh::RefreshCmd;
 pub(crate) use self::sync::SyncCmd;
+pub(crate) use self::validate::ValidateCmd;
 
 use std::io::Result;
 
@@ -0,0 +1,39 @@
+mod check_cmd;
+
+use std::io::Result;
+
+use clap::{ArgMatches, Command};
+
+use self::check_cmd::CheckCmd;
+
+use super::{Cmd, CmdOld};
+
+pub(crate) fn add_policy_cmd() -> Command {
+    // Register command
+    Command::new("policy")
+        .about("Work with the policy of the config")
+        .arg_required_else_help(true)
+        .subcommand(CheckCmd::add())
+}
+
+pub(crate) struct PolicyCmd<'a> {
+    policy_sub: Option<(&'a str, &'a ArgMatches)>,
+}
+
+pub(crate) fn prepare<'a>(sub_matches: &'a ArgMatches) -> Result<impl CmdOld<'a>> {
+    Ok(PolicyCmd {
+        policy_sub: sub_matches.subcommand(),
+    })
+}
+
+impl<'a> CmdOld<'a> for PolicyCmd<'a> {
+    fn exec(&self) -> Result<()> {
+        match self.policy_sub {
+            Some(("check", sub_matches)) => match CheckCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            _ => Ok(()),
+        }
+    }
+}
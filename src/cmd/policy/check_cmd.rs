@@ -0,0 +1,60 @@
+use crate::args::{ArgFileName, ArgGitlabToken, ArgGitlabUrl, Args};
+use crate::cmd::Cmd;
+use crate::gitlab::GitlabApi;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct CheckCmd {
+    file_name: String,
+    gitlab_token: String,
+    gitlab_url: String,
+}
+
+impl Cmd for CheckCmd {
+    type CmdType = CheckCmd;
+
+    fn add() -> Command {
+        Command::new("check")
+            .about("Check the state that is planned by the config against the policy")
+            .after_help("$ gum policy check -f gum-config-example.yaml")
+            .before_help("It's done by `sync` too, but this command doesn't need the state and reports every violation at once")
+            .arg(ArgFileName::add())
+            .arg(ArgGitlabToken::add())
+            .arg(ArgGitlabUrl::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            gitlab_token: ArgGitlabToken::parse(sub_matches)?,
+            gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl CheckCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let mut svc = v1::SyncService::new(
+            self.file_name.clone(),
+            GitlabApi::lazy(&self.gitlab_url, &self.gitlab_token),
+            String::new(),
+            String::new(),
+            false,
+            0,
+        );
+        svc.read_config()?
+            .resolve_paths()?
+            .resolve_users()?
+            .plan_state()?
+            .evaluate_policy()
+    }
+}
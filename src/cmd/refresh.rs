@@ -0,0 +1,63 @@
+use std::io::Result;
+
+use clap::{ArgMatches, Command};
+
+use crate::{
+    args::{ArgDryRun, ArgFileName, ArgGitlabToken, ArgGitlabUrl, Args},
+    gitlab::GitlabApi,
+    service::v1,
+    types::{
+        common::{Version, Versions},
+        v1::ConfigFile,
+    },
+};
+
+use super::Cmd;
+
+pub(crate) struct RefreshCmd {
+    file_name: String,
+    dry_run: bool,
+    gitlab_token: String,
+    gitlab_url: String,
+}
+
+impl Cmd for RefreshCmd {
+    type CmdType = RefreshCmd;
+
+    fn add() -> Command {
+        Command::new("refresh")
+            .about("Update names of users, projects and groups in the config")
+            .after_help("$ gum refresh -f gum-config-example.yaml --dry-run")
+            .before_help("Use this command when something has been renamed in GitLab")
+            .arg(ArgDryRun::add())
+            .arg(ArgGitlabToken::add())
+            .arg(ArgGitlabUrl::add())
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> Result<RefreshCmd> {
+        Ok(RefreshCmd {
+            file_name: ArgFileName::parse(sub_matches)?,
+            dry_run: ArgDryRun::parse(sub_matches)?,
+            gitlab_token: ArgGitlabToken::parse(sub_matches)?,
+            gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl RefreshCmd {
+    fn exec_v1(&self) -> Result<()> {
+        v1::RefreshService::new(
+            self.file_name.clone(),
+            GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
+        )?
+        .refresh()?
+        .write_state(self.dry_run)
+    }
+}
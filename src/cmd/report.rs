@@ -0,0 +1,39 @@
+mod html_cmd;
+
+use std::io::Result;
+
+use clap::{ArgMatches, Command};
+
+use self::html_cmd::HtmlCmd;
+
+use super::{Cmd, CmdOld};
+
+pub(crate) fn add_report_cmd() -> Command {
+    // Register command
+    Command::new("report")
+        .about("Render reports about the access")
+        .arg_required_else_help(true)
+        .subcommand(HtmlCmd::add())
+}
+
+pub(crate) struct ReportCmd<'a> {
+    report_sub: Option<(&'a str, &'a ArgMatches)>,
+}
+
+pub(crate) fn prepare<'a>(sub_matches: &'a ArgMatches) -> Result<impl CmdOld<'a>> {
+    Ok(ReportCmd {
+        report_sub: sub_matches.subcommand(),
+    })
+}
+
+impl<'a> CmdOld<'a> for ReportCmd<'a> {
+    fn exec(&self) -> Result<()> {
+        match self.report_sub {
+            Some(("html", sub_matches)) => match HtmlCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            _ => Ok(()),
+        }
+    }
+}
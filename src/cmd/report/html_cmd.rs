@@ -0,0 +1,44 @@
+use crate::args::{ArgFileName, ArgOutput, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct HtmlCmd {
+    file_name: String,
+    output: String,
+}
+
+impl Cmd for HtmlCmd {
+    type CmdType = HtmlCmd;
+
+    fn add() -> Command {
+        Command::new("html")
+            .about("Render the state as a static site, it can be published with GitLab Pages")
+            .after_help("$ gum report html -f gum-config-example.yaml -o public/")
+            .before_help("Pages don't fetch anything when they're opened, so they can be published as they are")
+            .arg(ArgFileName::add())
+            .arg(ArgOutput::add().help("Provide a directory for the site").required(true))
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            output: ArgOutput::parse(sub_matches)?.unwrap_or_default(),
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl HtmlCmd {
+    fn exec_v1(&self) -> Result<()> {
+        v1::ReportService::new(self.file_name.clone())?.html(&self.output)
+    }
+}
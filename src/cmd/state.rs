@@ -0,0 +1,90 @@
+mod add_cmd;
+mod diff_cmd;
+mod list_cmd;
+mod migrate_cmd;
+mod refresh_cmd;
+mod revert_cmd;
+mod rm_cmd;
+mod show_cmd;
+mod unlock_cmd;
+
+use std::io::Error;
+
+use clap::{ArgMatches, Command};
+
+use self::{
+    add_cmd::AddCmd, diff_cmd::DiffCmd, list_cmd::ListCmd, migrate_cmd::MigrateCmd,
+    refresh_cmd::RefreshCmd, revert_cmd::RevertCmd, rm_cmd::RmCmd, show_cmd::ShowCmd, unlock_cmd::UnlockCmd,
+};
+
+use super::{Cmd, CmdOld};
+
+pub(crate) fn add_state_cmd() -> Command {
+    // Register command
+    Command::new("state")
+        .about("Manage the GUM state")
+        .arg_required_else_help(true)
+        .subcommand(MigrateCmd::add())
+        .subcommand(UnlockCmd::add())
+        .subcommand(ListCmd::add())
+        .subcommand(ShowCmd::add())
+        .subcommand(DiffCmd::add())
+        .subcommand(RevertCmd::add())
+        .subcommand(AddCmd::add())
+        .subcommand(RmCmd::add())
+        .subcommand(RefreshCmd::add())
+}
+
+pub(crate) struct StateCmd<'a> {
+    state_sub: Option<(&'a str, &'a ArgMatches)>,
+}
+
+pub(crate) fn prepare<'a>(sub_matches: &'a ArgMatches) -> Result<impl CmdOld<'a>, Error> {
+    Ok(StateCmd {
+        state_sub: sub_matches.subcommand(),
+    })
+}
+
+impl<'a> CmdOld<'a> for StateCmd<'a> {
+    fn exec(&self) -> Result<(), Error> {
+        match self.state_sub {
+            Some(("migrate", sub_matches)) => match MigrateCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("unlock", sub_matches)) => match UnlockCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("list", sub_matches)) => match ListCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("show", sub_matches)) => match ShowCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("diff", sub_matches)) => match DiffCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("revert", sub_matches)) => match RevertCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("add", sub_matches)) => match AddCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("rm", sub_matches)) => match RmCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            Some(("refresh", sub_matches)) => match RefreshCmd::prepare(sub_matches) {
+                Ok(cmd) => cmd.exec(),
+                Err(err) => Err(err),
+            },
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::args::{
+    ArgAccess, ArgFileName, ArgObjectGroup, ArgObjectProject, ArgSubject, ArgSubjectType, Args,
+};
+use crate::cmd::Cmd;
+use crate::output::out_message::OutMessage;
+use crate::service::v1::{self, StateObject};
+use crate::types::common::{Version, Versions};
+use crate::types::v1::{AccessLevel, ConfigFile, EntityType};
+use clap::{ArgGroup, ArgMatches, Command};
+use std::io::{Error, ErrorKind, Result};
+
+pub(crate) struct AddCmd {
+    file_name: String,
+    subject: Option<u64>,
+    subject_type: EntityType,
+    object: Option<StateObject>,
+    access: AccessLevel,
+}
+
+impl Cmd for AddCmd {
+    type CmdType = AddCmd;
+
+    fn add() -> Command {
+        Command::new("add")
+            .about("Adopt an existing access into the state without touching GitLab")
+            .after_help("$ gum state add --subject 42 --project 1337 --access developer -f gum-config-example.yaml")
+            .before_help("Use it when gum should start managing an access that already exists in GitLab")
+            .arg(ArgSubject::add().required(true))
+            .arg(ArgSubjectType::add())
+            .arg(ArgObjectProject::add())
+            .arg(ArgObjectGroup::add())
+            .group(ArgGroup::new("object").args(["project", "group"]).required(true))
+            .arg(ArgAccess::add())
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            subject: ArgSubject::parse(sub_matches)?,
+            subject_type: ArgSubjectType::parse(sub_matches)?,
+            object: StateObject::from_ids(
+                ArgObjectProject::parse(sub_matches)?,
+                ArgObjectGroup::parse(sub_matches)?,
+            ),
+            access: ArgAccess::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl AddCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let subject = self.subject.ok_or_else(|| {
+            let err_msg = "Subject is not specified";
+            OutMessage::message_error(err_msg);
+            Error::new(ErrorKind::InvalidInput, err_msg)
+        })?;
+        v1::StateService::new(self.file_name.clone())?.add(
+            subject,
+            self.subject_type.clone(),
+            self.object,
+            self.access,
+        )
+    }
+}
@@ -0,0 +1,44 @@
+use crate::args::{ArgFileName, ArgRevisions, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct DiffCmd {
+    file_name: String,
+    revs: (u64, u64),
+}
+
+impl Cmd for DiffCmd {
+    type CmdType = DiffCmd;
+
+    fn add() -> Command {
+        Command::new("diff")
+            .about("Show what has been changed between two revisions of the state")
+            .after_help("$ gum state diff 2 3 -f gum-config-example.yaml")
+            .arg(ArgRevisions::add())
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            revs: ArgRevisions::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl DiffCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let (a, b) = self.revs;
+        v1::StateService::new(self.file_name.clone())?.diff(a, b)
+    }
+}
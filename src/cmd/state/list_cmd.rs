@@ -0,0 +1,40 @@
+use crate::args::{ArgFileName, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct ListCmd {
+    file_name: String,
+}
+
+impl Cmd for ListCmd {
+    type CmdType = ListCmd;
+
+    fn add() -> Command {
+        Command::new("list")
+            .about("List previous states that are kept in the history")
+            .after_help("$ gum state list -f gum-config-example.yaml")
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl ListCmd {
+    fn exec_v1(&self) -> Result<()> {
+        v1::StateService::new(self.file_name.clone())?.list()
+    }
+}
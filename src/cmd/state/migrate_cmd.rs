@@ -0,0 +1,44 @@
+use crate::args::{ArgFileName, ArgStateStore, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct MigrateCmd {
+    file_name: String,
+    to: String,
+}
+
+impl Cmd for MigrateCmd {
+    type CmdType = MigrateCmd;
+
+    fn add() -> Command {
+        Command::new("migrate")
+            .about("Move the state to another store")
+            .after_help("$ gum state migrate --to file:./state.json -f gum-config-example.yaml")
+            .arg(ArgStateStore::add())
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            to: ArgStateStore::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl MigrateCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let mut svc = v1::StateService::new(self.file_name.clone())?;
+        svc.migrate(self.to.clone())
+    }
+}
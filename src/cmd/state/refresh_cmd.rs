@@ -0,0 +1,72 @@
+use crate::args::{ArgDryRun, ArgFileName, ArgGitlabToken, ArgGitlabUrl, ArgHistorySize, Args};
+use crate::cmd::Cmd;
+use crate::gitlab::{GitlabApi, GitlabApiInterface};
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct RefreshCmd {
+    file_name: String,
+    dry_run: bool,
+    gitlab_token: String,
+    gitlab_url: String,
+    history_size: usize,
+}
+
+impl Cmd for RefreshCmd {
+    type CmdType = RefreshCmd;
+
+    fn add() -> Command {
+        Command::new("refresh")
+            .about("Rebuild the state from the access that users and groups of the config have in GitLab")
+            .after_help("$ gum state refresh -f gum-config-example.yaml --dry-run")
+            .before_help("Use it when the state is lost or broken, GitLab itself is not changed")
+            .arg(ArgDryRun::add())
+            .arg(ArgGitlabToken::add())
+            .arg(ArgGitlabUrl::add())
+            .arg(ArgFileName::add())
+            .arg(ArgHistorySize::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            dry_run: ArgDryRun::parse(sub_matches)?,
+            gitlab_token: ArgGitlabToken::parse(sub_matches)?,
+            gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
+            history_size: ArgHistorySize::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl RefreshCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let mut svc = v1::SyncService::new(
+            self.file_name.clone(),
+            GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
+            String::new(),
+            String::new(),
+            false,
+            self.history_size,
+        );
+        svc.read_config()?.lock_state()?;
+        // The state must be unlocked even if the refresh has failed
+        let result = self.exec_locked(&mut svc);
+        svc.unlock_state();
+        result
+    }
+
+    fn exec_locked<T: GitlabApiInterface>(&self, svc: &mut v1::SyncService<T>) -> Result<()> {
+        svc.resolve_paths()?
+            .resolve_users()?
+            .refresh_state()?.write_state(self.dry_run)
+    }
+}
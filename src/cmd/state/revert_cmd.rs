@@ -0,0 +1,82 @@
+use crate::args::{ArgDryRun, ArgFileName, ArgGitlabToken, ArgGitlabUrl, ArgHistorySize, ArgRevision, Args};
+use crate::cmd::Cmd;
+use crate::gitlab::{GitlabApi, GitlabApiInterface};
+use crate::output::out_message::OutMessage;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::{Error, ErrorKind, Result};
+
+pub(crate) struct RevertCmd {
+    file_name: String,
+    rev: Option<u64>,
+    dry_run: bool,
+    gitlab_token: String,
+    gitlab_url: String,
+    history_size: usize,
+}
+
+impl Cmd for RevertCmd {
+    type CmdType = RevertCmd;
+
+    fn add() -> Command {
+        Command::new("revert")
+            .about("Bring GitLab back to one of the previous revisions of the state")
+            .after_help("$ gum state revert 3 -f gum-config-example.yaml --dry-run")
+            .before_help("The config is not changed, so the next sync will apply it again")
+            .arg(ArgRevision::add().required(true))
+            .arg(ArgDryRun::add())
+            .arg(ArgGitlabToken::add())
+            .arg(ArgGitlabUrl::add())
+            .arg(ArgFileName::add())
+            .arg(ArgHistorySize::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            rev: ArgRevision::parse(sub_matches)?,
+            dry_run: ArgDryRun::parse(sub_matches)?,
+            gitlab_token: ArgGitlabToken::parse(sub_matches)?,
+            gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
+            history_size: ArgHistorySize::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl RevertCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let rev = self.rev.ok_or_else(|| {
+            let err_msg = "Revision is not specified";
+            OutMessage::message_error(err_msg);
+            Error::new(ErrorKind::InvalidInput, err_msg)
+        })?;
+        let mut svc = v1::SyncService::new(
+            self.file_name.clone(),
+            GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
+            String::new(),
+            String::new(),
+            false,
+            self.history_size,
+        );
+        svc.read_config()?.lock_state()?;
+        // The state must be unlocked even if the revert has failed
+        let result = self.exec_locked(&mut svc, rev);
+        svc.unlock_state();
+        result
+    }
+
+    fn exec_locked<T: GitlabApiInterface>(&self, svc: &mut v1::SyncService<T>, rev: u64) -> Result<()> {
+        svc.revert(rev)?
+            .compare()?
+            .apply(self.dry_run)?
+            .write_state(self.dry_run)
+    }
+}
@@ -0,0 +1,57 @@
+use crate::args::{ArgFileName, ArgObjectGroup, ArgObjectProject, ArgSubject, Args};
+use crate::cmd::Cmd;
+use crate::output::out_message::OutMessage;
+use crate::service::v1::{self, StateObject};
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::{Error, ErrorKind, Result};
+
+pub(crate) struct RmCmd {
+    file_name: String,
+    subject: Option<u64>,
+    object: Option<StateObject>,
+}
+
+impl Cmd for RmCmd {
+    type CmdType = RmCmd;
+
+    fn add() -> Command {
+        Command::new("rm")
+            .about("Forget a subject or its access in the state without touching GitLab")
+            .after_help("$ gum state rm --subject 42 --project 1337 -f gum-config-example.yaml")
+            .before_help("Use it when something has been removed from GitLab by hand")
+            .arg(ArgSubject::add().required(true))
+            .arg(ArgObjectProject::add())
+            .arg(ArgObjectGroup::add())
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            subject: ArgSubject::parse(sub_matches)?,
+            object: StateObject::from_ids(
+                ArgObjectProject::parse(sub_matches)?,
+                ArgObjectGroup::parse(sub_matches)?,
+            ),
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl RmCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let subject = self.subject.ok_or_else(|| {
+            let err_msg = "Subject is not specified";
+            OutMessage::message_error(err_msg);
+            Error::new(ErrorKind::InvalidInput, err_msg)
+        })?;
+        v1::StateService::new(self.file_name.clone())?.remove(subject, self.object)
+    }
+}
@@ -0,0 +1,46 @@
+use crate::args::{ArgFileName, ArgRevision, ArgSubject, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct ShowCmd {
+    file_name: String,
+    rev: Option<u64>,
+    subject: Option<u64>,
+}
+
+impl Cmd for ShowCmd {
+    type CmdType = ShowCmd;
+
+    fn add() -> Command {
+        Command::new("show")
+            .about("Show the current state or one of its previous revisions")
+            .after_help("$ gum state show 3 --subject 42 -f gum-config-example.yaml")
+            .arg(ArgRevision::add())
+            .arg(ArgSubject::add())
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            rev: ArgRevision::parse(sub_matches)?,
+            subject: ArgSubject::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl ShowCmd {
+    fn exec_v1(&self) -> Result<()> {
+        v1::StateService::new(self.file_name.clone())?.show(self.rev, self.subject)
+    }
+}
@@ -0,0 +1,50 @@
+use crate::args::{ArgFileName, ArgForce, Args};
+use crate::cmd::Cmd;
+use crate::output::out_message::OutMessage;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::ConfigFile;
+use clap::{ArgMatches, Command};
+use std::io::{Error, ErrorKind, Result};
+
+pub(crate) struct UnlockCmd {
+    file_name: String,
+    force: bool,
+}
+
+impl Cmd for UnlockCmd {
+    type CmdType = UnlockCmd;
+
+    fn add() -> Command {
+        Command::new("unlock")
+            .about("Remove a stale lock of the state")
+            .after_help("$ gum state unlock --force -f gum-config-example.yaml")
+            .before_help("Use this command only if you're sure that nobody is syncing the config right now")
+            .arg(ArgForce::add())
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            force: ArgForce::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        if !self.force {
+            let err_msg = "The lock can be held by a sync that is still running, use --force if it's not";
+            OutMessage::message_error(err_msg);
+            return Err(Error::new(ErrorKind::InvalidInput, err_msg));
+        }
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl UnlockCmd {
+    fn exec_v1(&self) -> Result<()> {
+        v1::StateService::new(self.file_name.clone())?.force_unlock()
+    }
+}
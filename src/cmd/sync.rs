@@ -1,6 +1,6 @@
 use crate::{
     cmd::Cmd,
-    gitlab::GitlabApi,
+    gitlab::{GitlabApi, GitlabApiInterface},
     output::out_message::OutMessage,
     service::v1,
     types::{
@@ -13,8 +13,8 @@ use std::io::Result;
 use clap::{ArgMatches, Command};
 
 use crate::args::{
-    ArgDryRun, ArgFileName, ArgGitlabToken, ArgGitlabUrl, ArgStateDestination, ArgStateSource,
-    ArgWriteState, Args,
+    ArgDryRun, ArgFileName, ArgGitlabToken, ArgGitlabUrl, ArgHistorySize, ArgStateDestination,
+    ArgStateSource, ArgWriteState, Args,
 };
 
 pub(crate) struct SyncCmd {
@@ -23,6 +23,7 @@ pub(crate) struct SyncCmd {
     gitlab_url: String,
     file_name: String,
     write_state: bool,
+    history_size: usize,
     state_destination: String,
     state_source: String,
 }
@@ -41,6 +42,7 @@ impl<'a> Cmd for SyncCmd {
             .arg(ArgStateDestination::add())
             .arg(ArgStateSource::add())
             .arg(ArgWriteState::add())
+            .arg(ArgHistorySize::add())
     }
 
     fn prepare(sub_matches: &ArgMatches) -> Result<Self> {
@@ -52,31 +54,49 @@ impl<'a> Cmd for SyncCmd {
             state_destination: ArgStateDestination::parse(sub_matches)?,
             state_source: ArgStateSource::parse(sub_matches)?,
             write_state: ArgWriteState::parse(sub_matches)?,
+            history_size: ArgHistorySize::parse(sub_matches)?,
         })
     }
 
     fn exec(&self) -> Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 => {
+                OutMessage::message_info_with_alias(
+                    "You may be using an outdated config version, check out `gum migrate`",
+                );
+                self.exec_v1()
+            }
+            Versions::V2 => self.exec_v1(),
         }
     }
 }
 
 impl SyncCmd {
     fn exec_v1(&self) -> Result<()> {
-        OutMessage::message_info_with_alias("You may be using the an outdated config version");
         let mut svc = v1::SyncService::new(
             self.file_name.clone(),
             GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
             self.state_source.clone(),
             self.state_destination.clone(),
             self.write_state,
+            self.history_size,
         );
-        svc.read_config()?
+        // The state is locked before anything is read from it, so two syncs can't plan with the same state
+        svc.read_config()?.lock_state()?;
+        // The state must be unlocked even if the sync has failed
+        let result = self.exec_locked(&mut svc);
+        svc.unlock_state();
+        result
+    }
+
+    fn exec_locked<T: GitlabApiInterface>(&self, svc: &mut v1::SyncService<T>) -> Result<()> {
+        svc.resolve_paths()?
+            .resolve_users()?
+            .check_policy()?
             .create_states()?
             .compare()?
+            .check_rules()?
             .apply(self.dry_run)?
-            .update_state()?
             .write_state(self.dry_run)
     }
 }
@@ -1,7 +1,9 @@
+mod add_member_cmd;
 mod add_project_cmd;
 mod create_cmd;
 mod list_cmd;
 mod remove_cmd;
+mod remove_member_cmd;
 mod remove_project_cmd;
 
 use std::io::Error;
@@ -9,8 +11,9 @@ use std::io::Error;
 use clap::{ArgMatches, Command};
 
 use self::{
-    add_project_cmd::AddProjectCmd, create_cmd::CreateCmd, list_cmd::ListCmd,
-    remove_cmd::RemoveCmd, remove_project_cmd::RemoveProjectCmd,
+    add_member_cmd::AddMemberCmd, add_project_cmd::AddProjectCmd, create_cmd::CreateCmd,
+    list_cmd::ListCmd, remove_cmd::RemoveCmd, remove_member_cmd::RemoveMemberCmd,
+    remove_project_cmd::RemoveProjectCmd,
 };
 
 use super::{Cmd, CmdOld};
@@ -25,7 +28,9 @@ pub(crate) fn add_teams_cmd() -> Command {
         .subcommand(ListCmd::add())
         .subcommand(RemoveCmd::add())
         .subcommand(AddProjectCmd::add())
-        .subcommand(RemoveProjectCmd::add());
+        .subcommand(RemoveProjectCmd::add())
+        .subcommand(AddMemberCmd::add())
+        .subcommand(RemoveMemberCmd::add());
 }
 
 pub(crate) struct TeamsCmd<'a> {
@@ -72,6 +77,18 @@ impl<'a> CmdOld<'a> for TeamsCmd<'a> {
                     Err(err) => Err(err),
                 }
             }
+            Some(("add-member", sub_matches)) => {
+                result = match AddMemberCmd::prepare(sub_matches) {
+                    Ok(cmd) => cmd.exec(),
+                    Err(err) => Err(err),
+                }
+            }
+            Some(("remove-member", sub_matches)) => {
+                result = match RemoveMemberCmd::prepare(sub_matches) {
+                    Ok(cmd) => cmd.exec(),
+                    Err(err) => Err(err),
+                }
+            }
 
             _ => return Ok(()),
         }
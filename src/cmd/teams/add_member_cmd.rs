@@ -0,0 +1,51 @@
+use crate::args::{ArgFileName, ArgTeamName, ArgTeamRole, ArgUserId, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::{ConfigFile, TeamRole, UserRef};
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct AddMemberCmd {
+    file_name: String,
+    team_name: String,
+    gitlab_user: UserRef,
+    role: TeamRole,
+}
+
+impl Cmd for AddMemberCmd {
+    type CmdType = AddMemberCmd;
+
+    fn add() -> Command {
+        Command::new("add-member")
+            .alias("am")
+            .about("Add a user to the team, or change the role of a member")
+            .arg(ArgTeamName::add())
+            .arg(ArgUserId::add())
+            .arg(ArgTeamRole::add())
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            team_name: ArgTeamName::parse(sub_matches)?,
+            gitlab_user: ArgUserId::parse(sub_matches)?,
+            role: ArgTeamRole::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl AddMemberCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let mut svc = v1::TeamsService::new(self.file_name.clone())?;
+        svc.add_member(self.team_name.clone(), &self.gitlab_user, self.role)?
+            .write_state()
+    }
+}
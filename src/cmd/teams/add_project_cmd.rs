@@ -46,14 +46,14 @@ impl Cmd for AddProjectCmd {
 
     fn exec(&self) -> std::io::Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 | Versions::V2 => self.exec_v1(),
         }
     }
 }
 
 impl AddProjectCmd {
     fn exec_v1(&self) -> Result<()> {
-        let mut svc = v1::TeamsService::new(self.file_name.clone());
+        let mut svc = v1::TeamsService::new(self.file_name.clone())?;
         svc.add_to_project(
             GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
             self.team_name.clone(),
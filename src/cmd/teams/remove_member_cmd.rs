@@ -0,0 +1,48 @@
+use crate::args::{ArgFileName, ArgTeamName, ArgUserId, Args};
+use crate::cmd::Cmd;
+use crate::service::v1;
+use crate::types::common::{Version, Versions};
+use crate::types::v1::{ConfigFile, UserRef};
+use clap::{ArgMatches, Command};
+use std::io::Result;
+
+pub(crate) struct RemoveMemberCmd {
+    file_name: String,
+    team_name: String,
+    gitlab_user: UserRef,
+}
+
+impl Cmd for RemoveMemberCmd {
+    type CmdType = RemoveMemberCmd;
+
+    fn add() -> Command {
+        Command::new("remove-member")
+            .alias("rmm")
+            .about("Remove a user from the team")
+            .arg(ArgTeamName::add())
+            .arg(ArgUserId::add())
+            .arg(ArgFileName::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
+        Ok(Self {
+            file_name: ArgFileName::parse(sub_matches)?,
+            team_name: ArgTeamName::parse(sub_matches)?,
+            gitlab_user: ArgUserId::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> std::io::Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl RemoveMemberCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let mut svc = v1::TeamsService::new(self.file_name.clone())?;
+        svc.remove_member(self.team_name.clone(), &self.gitlab_user)?
+            .write_state()
+    }
+}
@@ -34,14 +34,14 @@ impl Cmd for RemoveProjectCmd {
 
     fn exec(&self) -> std::io::Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 | Versions::V2 => self.exec_v1(),
         }
     }
 }
 
 impl RemoveProjectCmd {
     fn exec_v1(&self) -> Result<()> {
-        let mut svc = v1::TeamsService::new(self.file_name.clone());
+        let mut svc = v1::TeamsService::new(self.file_name.clone())?;
         svc.remove_from_project(self.team_name.clone(), self.gitlab_project_id)?
             .write_state()
     }
@@ -5,13 +5,13 @@ use crate::cmd::Cmd;
 use crate::gitlab::GitlabApi;
 use crate::service::v1;
 use crate::types::common::{Version, Versions};
-use crate::types::v1::{AccessLevel, ConfigFile};
+use crate::types::v1::{AccessLevel, ConfigFile, UserRef};
 use clap::{ArgMatches, Command};
 use std::io::Result;
 
 pub(crate) struct AddNamespaceCmd {
     file_name: String,
-    gitlab_user_id: u64,
+    gitlab_user: UserRef,
     gitlab_group_id: u64,
     gitlab_url: String,
     gitlab_token: String,
@@ -36,7 +36,7 @@ impl Cmd for AddNamespaceCmd {
     fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
         Ok(Self {
             file_name: ArgFileName::parse(sub_matches)?,
-            gitlab_user_id: ArgUserId::parse(sub_matches)?,
+            gitlab_user: ArgUserId::parse(sub_matches)?,
             gitlab_group_id: ArgNamespaceId::parse(sub_matches)?,
             gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
             gitlab_token: ArgGitlabToken::parse(sub_matches)?,
@@ -46,18 +46,18 @@ impl Cmd for AddNamespaceCmd {
 
     fn exec(&self) -> std::io::Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 | Versions::V2 => self.exec_v1(),
         }
     }
 }
 
 impl AddNamespaceCmd {
     fn exec_v1(&self) -> Result<()> {
-        let mut svc = v1::users::UsersService::new(self.file_name.clone());
+        let mut svc = v1::users::UsersService::new(self.file_name.clone())?;
         svc.add_to_namespace(
-            GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
+            &GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
             self.gitlab_group_id,
-            self.gitlab_user_id,
+            &self.gitlab_user,
             self.access_level,
         )?
         .write_state()
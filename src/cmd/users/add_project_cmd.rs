@@ -5,7 +5,7 @@ use crate::{
     service::v1,
     types::{
         common::{Version, Versions},
-        v1::{AccessLevel, ConfigFile},
+        v1::{AccessLevel, ConfigFile, UserRef},
     },
 };
 use clap::{ArgMatches, Command};
@@ -13,7 +13,7 @@ use std::io::Result;
 
 pub(crate) struct AddProjectCmd {
     file_name: String,
-    gitlab_user_id: u64,
+    gitlab_user: UserRef,
     access_level: AccessLevel,
     gitlab_project_id: u64,
     gitlab_url: String,
@@ -38,7 +38,7 @@ impl Cmd for AddProjectCmd {
     fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
         Ok(Self {
             file_name: ArgFileName::parse(sub_matches)?,
-            gitlab_user_id: ArgUserId::parse(sub_matches)?,
+            gitlab_user: ArgUserId::parse(sub_matches)?,
             gitlab_project_id: ArgProjectId::parse(sub_matches)?,
             gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
             gitlab_token: ArgGitlabToken::parse(sub_matches)?,
@@ -48,18 +48,18 @@ impl Cmd for AddProjectCmd {
 
     fn exec(&self) -> std::io::Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 | Versions::V2 => self.exec_v1(),
         }
     }
 }
 
 impl AddProjectCmd {
     fn exec_v1(&self) -> Result<()> {
-        let mut svc = v1::users::UsersService::new(self.file_name.clone());
+        let mut svc = v1::users::UsersService::new(self.file_name.clone())?;
         svc.add_to_project(
-            GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
+            &GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
             self.gitlab_project_id,
-            self.gitlab_user_id,
+            &self.gitlab_user,
             self.access_level,
         )?
         .write_state()
@@ -3,12 +3,12 @@ use crate::cmd::Cmd;
 use crate::gitlab::GitlabApi;
 use crate::service::v1;
 use crate::types::common::{Version, Versions};
-use crate::types::v1::ConfigFile;
+use crate::types::v1::{ConfigFile, UserRef};
 use clap::{ArgMatches, Command};
 use std::io::Result;
 
 pub(crate) struct CreateCmd {
-    gitlab_user_id: u64,
+    gitlab_user: UserRef,
     gitlab_url: String,
     gitlab_token: String,
     file_name: String,
@@ -29,7 +29,7 @@ impl Cmd for CreateCmd {
 
     fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
         Ok(Self {
-            gitlab_user_id: ArgUserId::parse(sub_matches)?,
+            gitlab_user: ArgUserId::parse(sub_matches)?,
             gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
             gitlab_token: ArgGitlabToken::parse(sub_matches)?,
             file_name: ArgFileName::parse(sub_matches)?,
@@ -38,17 +38,17 @@ impl Cmd for CreateCmd {
 
     fn exec(&self) -> std::io::Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 | Versions::V2 => self.exec_v1(),
         }
     }
 }
 
 impl CreateCmd {
     fn exec_v1(&self) -> Result<()> {
-        let mut svc = v1::users::UsersService::new(self.file_name.clone());
+        let mut svc = v1::users::UsersService::new(self.file_name.clone())?;
         svc.create(
-            GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
-            self.gitlab_user_id,
+            &GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?,
+            &self.gitlab_user,
         )?
         .write_state()
     }
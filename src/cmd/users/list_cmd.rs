@@ -35,14 +35,14 @@ impl Cmd for ListCmd {
 
     fn exec(&self) -> std::io::Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 | Versions::V2 => self.exec_v1(),
         }
     }
 }
 
 impl ListCmd {
     fn exec_v1(&self) -> Result<()> {
-        let mut svc = v1::users::UsersService::new(self.file_name.clone());
+        let mut svc = v1::users::UsersService::new(self.file_name.clone())?;
         svc.list(self.large_out)
     }
 }
@@ -2,12 +2,12 @@ use crate::args::{ArgFileName, ArgTeamName, ArgUserId, Args};
 use crate::cmd::Cmd;
 use crate::service::v1;
 use crate::types::common::{Version, Versions};
-use crate::types::v1::ConfigFile;
+use crate::types::v1::{ConfigFile, UserRef};
 use clap::{ArgMatches, Command};
 use std::io::Result;
 
 pub(crate) struct RemoveTeamCmd {
-    gitlab_user_id: u64,
+    gitlab_user: UserRef,
     team_name: String,
     file_name: String,
 }
@@ -26,7 +26,7 @@ impl Cmd for RemoveTeamCmd {
 
     fn prepare(sub_matches: &'_ ArgMatches) -> std::io::Result<Self::CmdType> {
         Ok(Self {
-            gitlab_user_id: ArgUserId::parse(sub_matches)?,
+            gitlab_user: ArgUserId::parse(sub_matches)?,
             team_name: ArgTeamName::parse(sub_matches)?,
             file_name: ArgFileName::parse(sub_matches)?,
         })
@@ -34,15 +34,15 @@ impl Cmd for RemoveTeamCmd {
 
     fn exec(&self) -> std::io::Result<()> {
         match ConfigFile::read(self.file_name.clone())?.get_version()? {
-            Versions::V1 => self.exec_v1(),
+            Versions::V1 | Versions::V2 => self.exec_v1(),
         }
     }
 }
 
 impl RemoveTeamCmd {
     fn exec_v1(&self) -> Result<()> {
-        let mut svc = v1::users::UsersService::new(self.file_name.clone());
-        svc.remove_from_team(self.gitlab_user_id, self.team_name.clone())?
+        let mut svc = v1::users::UsersService::new(self.file_name.clone())?;
+        svc.remove_from_team(&self.gitlab_user, self.team_name.clone())?
             .write_state()
     }
 }
@@ -0,0 +1,62 @@
+use std::io::Result;
+
+use clap::{ArgMatches, Command};
+
+use crate::{
+    args::{ArgFileName, ArgGitlabToken, ArgGitlabUrl, ArgOnline, Args},
+    gitlab::GitlabApi,
+    service::v1,
+    types::{
+        common::{Version, Versions},
+        v1::ConfigFile,
+    },
+};
+
+use super::Cmd;
+
+pub(crate) struct ValidateCmd {
+    file_name: String,
+    online: bool,
+    gitlab_token: String,
+    gitlab_url: String,
+}
+
+impl Cmd for ValidateCmd {
+    type CmdType = ValidateCmd;
+
+    fn add() -> Command {
+        Command::new("validate")
+            .about("Check the config file for problems")
+            .after_help("$ gum validate -f gum-config-example.yaml --online")
+            .before_help("Use this command if you want to find problems in your config file before syncing it")
+            .arg(ArgFileName::add())
+            .arg(ArgOnline::add())
+            .arg(ArgGitlabToken::add())
+            .arg(ArgGitlabUrl::add())
+    }
+
+    fn prepare(sub_matches: &'_ ArgMatches) -> Result<ValidateCmd> {
+        Ok(ValidateCmd {
+            file_name: ArgFileName::parse(sub_matches)?,
+            online: ArgOnline::parse(sub_matches)?,
+            gitlab_token: ArgGitlabToken::parse(sub_matches)?,
+            gitlab_url: ArgGitlabUrl::parse(sub_matches)?,
+        })
+    }
+
+    fn exec(&self) -> Result<()> {
+        match ConfigFile::read(self.file_name.clone())?.get_version()? {
+            Versions::V1 | Versions::V2 => self.exec_v1(),
+        }
+    }
+}
+
+impl ValidateCmd {
+    fn exec_v1(&self) -> Result<()> {
+        let svc = v1::ValidateService::new(self.file_name.clone())?;
+        if !self.online {
+            return svc.validate();
+        }
+        svc.validate_online(GitlabApi::new(&self.gitlab_url, &self.gitlab_token)?)
+    }
+}
@@ -1,35 +1,31 @@
 use crate::{
     gitlab::{
         types::group::{Group, GroupsWithShared, SharedWithGroups},
-        CustomMember, Project,
+        query_all, query_object, CustomMember, LazyGitlab, Project,
     },
     output::{out_message::OutMessage, out_spinner::OutSpinner},
     types::v1::AccessLevel,
 };
-use core::time;
 use gitlab::{
     api::{self, groups, ApiError, Query},
-    Gitlab,
 };
 use mockall::predicate::*;
 use mockall::*;
-use std::{
-    io::{Error, ErrorKind, Result},
-    thread,
-};
+use std::io::{Error, ErrorKind, Result};
 #[automock]
 pub(crate) trait GitlabGroupsApi {
     fn get_data_by_id(&self, id: u64) -> Result<Group>;
-    fn get_subgroups(&self, group_name: String, id: u64, recursive: bool) -> Vec<Group>;
-    fn get_members(&self, name: String, id: u64) -> Vec<CustomMember>;
+    fn get_data_by_path(&self, path: &str) -> Result<Group>;
+    fn get_subgroups(&self, group_name: String, id: u64, recursive: bool) -> Result<Vec<Group>>;
+    fn get_members(&self, name: String, id: u64) -> Result<Vec<CustomMember>>;
     fn get_shared_projects(&self, group_id: u64) -> Vec<Project>;
-    fn get_projects(&self, group_name: String, id: u64) -> Vec<Project>;
+    fn get_projects(&self, group_name: String, id: u64) -> Result<Vec<Project>>;
     fn remove_from_namespace(&self, gid: u64, nid: u64) -> Result<String>;
     fn add_to_namespace(&self, gid: u64, nid: u64, access_level: AccessLevel) -> Result<String>;
     fn git_groups_shared_with(&self, group_id: u64) -> Result<Vec<SharedWithGroups>>;
 }
 pub(crate) struct GroupGitlab {
-    pub(crate) gitlab_client: Gitlab,
+    pub(crate) gitlab_client: LazyGitlab,
 }
 
 impl GitlabGroupsApi for GroupGitlab {
@@ -37,72 +33,67 @@ impl GitlabGroupsApi for GroupGitlab {
     fn get_data_by_id(&self, id: u64) -> Result<Group> {
         let group = match groups::Group::builder().group(id).build() {
             Ok(group) => group,
-            Err(err) => {
-                return Err(Error::new(std::io::ErrorKind::Other, err.to_string()));
-            }
+            Err(err) => return Err(Error::other(err.to_string())),
         };
+        query_object(&self.gitlab_client, &group, &format!("group {}", id))
+    }
 
-        let output: Group = match group.query(&self.gitlab_client) {
-            Err(err) => {
-                match err {
-                    ApiError::GitlabObject { ref obj } => {
-                        if format!("{}", obj) == "{\"error\":\"This endpoint has been requested too many times. Try again later.\"}" {
-                            OutMessage::message_info_with_alias("Gitlab is screwed by amount of your requests. You need to wait");
-                            let spinner = OutSpinner::spinner_start("Waiting 30s".to_string());
-                            let await_time = time::Duration::from_secs(30);
-                            thread::sleep(await_time);
-                            spinner.spinner_success("Let's try again".to_string());
-                            return self.get_data_by_id(id);
-                            };
-                    }
-                    _ => return Err(Error::new(ErrorKind::AddrNotAvailable, err)),
-                };
-                return Err(Error::new(ErrorKind::AddrNotAvailable, err));
-            }
-            Ok(res) => res,
+    /// Get the group data from Gitlab by the full path of the group
+    fn get_data_by_path(&self, path: &str) -> Result<Group> {
+        let group = match groups::Group::builder().group(path).build() {
+            Ok(group) => group,
+            Err(err) => return Err(Error::other(err.to_string())),
         };
-        Ok(output)
+        query_object(&self.gitlab_client, &group, &format!("group {}", path))
     }
 
-    /// Get groups which are subgroups to current one
-    fn get_subgroups(&self, group_name: String, id: u64, recursive: bool) -> Vec<Group> {
-        let spinner = OutSpinner::spinner_start("Getting subgroups".to_string());
-
-        let mut groups: Vec<Group> = Vec::new();
+    /// Get groups which are subgroups to current one, with their subgroups if it's recursive
+    fn get_subgroups(&self, group_name: String, id: u64, recursive: bool) -> Result<Vec<Group>> {
+        let spinner = OutSpinner::spinner_start(format!("Getting subgroups of {}", group_name));
         let query = match groups::subgroups::GroupSubgroups::builder()
             .group(id)
             .all_available(true)
             .build()
         {
             Ok(q) => q,
-            Err(_) => todo!(),
+            Err(err) => return Err(Error::other(err.to_string())),
+        };
+        let head: Vec<Group> = match query_all(&self.gitlab_client, query) {
+            Ok(head) => head,
+            Err(err) => {
+                OutSpinner::spinner_failure(spinner, err.to_string());
+                return Err(err);
+            }
         };
-        let head: Vec<Group> = query.query(&self.gitlab_client).unwrap();
-        if recursive && !head.is_empty() {
-            OutSpinner::spinner_success(spinner, group_name);
+        OutSpinner::spinner_success(spinner, format!("Got {}", head.len()));
+
+        let mut groups: Vec<Group> = Vec::new();
+        if recursive {
             for g in head.iter() {
-                let sub: Vec<Group> = self.get_subgroups(g.name.clone(), g.id, true);
-                if !sub.is_empty() {
-                    groups.extend(sub);
-                }
+                groups.extend(self.get_subgroups(g.name.clone(), g.id, true)?);
             }
-        } else {
-            OutSpinner::spinner_success(spinner, group_name);
         }
         groups.extend(head);
-        groups
+        Ok(groups)
     }
 
     /// Get users that have access to this group
-    fn get_members(&self, name: String, id: u64) -> Vec<CustomMember> {
+    fn get_members(&self, name: String, id: u64) -> Result<Vec<CustomMember>> {
         let spinner = OutSpinner::spinner_start(format!("Getting users from {}", name));
         let query = match groups::members::GroupMembers::builder().group(id).build() {
             Ok(q) => q,
-            Err(_) => todo!(),
+            Err(err) => return Err(Error::other(err.to_string())),
         };
-        let users: Vec<CustomMember> = query.query(&self.gitlab_client).unwrap();
-        OutSpinner::spinner_success(spinner, "Done".to_string());
-        users
+        match query_all(&self.gitlab_client, query) {
+            Ok(users) => {
+                OutSpinner::spinner_success(spinner, "Done".to_string());
+                Ok(users)
+            }
+            Err(err) => {
+                OutSpinner::spinner_failure(spinner, err.to_string());
+                Err(err)
+            }
+        }
     }
 
     fn get_shared_projects(&self, group_id: u64) -> Vec<Project> {
@@ -115,7 +106,8 @@ impl GitlabGroupsApi for GroupGitlab {
         users
     }
 
-    fn get_projects(&self, group_name: String, id: u64) -> Vec<Project> {
+    /// Get projects of the group, projects of its subgroups are not included
+    fn get_projects(&self, group_name: String, id: u64) -> Result<Vec<Project>> {
         let spinner = OutSpinner::spinner_start(format!("Getting projects from {}", group_name));
         let query = match groups::projects::GroupProjects::builder()
             .group(id)
@@ -123,12 +115,19 @@ impl GitlabGroupsApi for GroupGitlab {
             .build()
         {
             Ok(q) => q,
-            Err(_) => todo!(),
+            Err(err) => return Err(Error::other(err.to_string())),
         };
-        let projects: Vec<Project> = query.query(&self.gitlab_client).unwrap();
-        OutSpinner::spinner_success(spinner, format!("Got {}", projects.len()));
-
-        projects
+        match query_all(&self.gitlab_client, query) {
+            Ok(projects) => {
+                let projects: Vec<Project> = projects;
+                OutSpinner::spinner_success(spinner, format!("Got {}", projects.len()));
+                Ok(projects)
+            }
+            Err(err) => {
+                OutSpinner::spinner_failure(spinner, err.to_string());
+                Err(err)
+            }
+        }
     }
 
     fn remove_from_namespace(&self, gid: u64, nid: u64) -> Result<String> {
@@ -202,53 +201,56 @@ impl GitlabGroupsApi for GroupGitlab {
     }
 }
 
-pub(crate) struct GroupGitlabMock;
+#[cfg(test)]
+mod tests {
+    use super::{GitlabGroupsApi, GroupGitlab};
+    use crate::gitlab::{test_client, test_page};
 
-impl GitlabGroupsApi for GroupGitlabMock {
-    fn get_data_by_id(&self, id: u64) -> Result<Group> {
-        let group = Group {
-            id,
-            name: "group_name".to_string(),
-            web_url: "http://localhost/group".to_string(),
-        };
-        Ok(group)
-    }
-
-    fn get_subgroups(&self, _: String, id: u64, _: bool) -> Vec<Group> {
-        let group_1 = Group {
-            id: id + 1,
-            name: "group_1".to_string(),
-            web_url: "http://localhost/group".to_string(),
-        };
-        let group_2 = Group {
-            id: id + 2,
-            name: "group_2".to_string(),
-            web_url: "http://localhost/group".to_string(),
-        };
-        return vec![group_1, group_2];
+    fn items(ids: impl Iterator<Item = u64>) -> Vec<String> {
+        ids.map(|id| format!(r#"{{"id":{},"name":"item{}","web_url":""}}"#, id, id))
+            .collect()
     }
 
-    fn get_members(&self, name: String, id: u64) -> Vec<CustomMember> {
-        todo!()
-    }
-
-    fn get_shared_projects(&self, group_id: u64) -> Vec<Project> {
-        todo!()
-    }
+    #[test]
+    fn subgroups_of_every_page_and_level() {
+        let head = items(1..=25);
+        let nested = items(100..=101);
+        let gitlab_client = test_client(move |path| {
+            if path.starts_with("/api/v4/groups/1000/subgroups") {
+                test_page(path, &head)
+            } else if path.starts_with("/api/v4/groups/25/subgroups") {
+                test_page(path, &nested)
+            } else {
+                "[]".to_string()
+            }
+        });
+        let groups_api = GroupGitlab { gitlab_client };
 
-    fn get_projects(&self, group_name: String, id: u64) -> Vec<Project> {
-        todo!()
-    }
+        let groups = groups_api.get_subgroups("head".to_string(), 1000, false).unwrap();
+        assert_eq!(groups.len(), 25);
 
-    fn remove_from_namespace(&self, gid: u64, nid: u64) -> Result<String> {
-        todo!()
+        let mut ids: Vec<u64> = groups_api
+            .get_subgroups("head".to_string(), 1000, true)
+            .unwrap()
+            .iter()
+            .map(|g| g.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, (1..=25).chain(100..=101).collect::<Vec<u64>>());
     }
 
-    fn add_to_namespace(&self, gid: u64, nid: u64, access_level: AccessLevel) -> Result<String> {
-        todo!()
-    }
+    #[test]
+    fn projects_of_every_page() {
+        let projects = items(1..=45);
+        let gitlab_client = test_client(move |path| match path.starts_with("/api/v4/groups/7/projects") {
+            true => test_page(path, &projects),
+            false => "[]".to_string(),
+        });
 
-    fn git_groups_shared_with(&self, group_id: u64) -> Result<Vec<SharedWithGroups>> {
-        todo!()
+        let projects = GroupGitlab { gitlab_client }
+            .get_projects("backend".to_string(), 7)
+            .unwrap();
+        assert_eq!(projects.len(), 45);
+        assert_eq!(projects.last().unwrap().name, "item45");
     }
 }
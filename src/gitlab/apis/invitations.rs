@@ -0,0 +1,256 @@
+use std::{
+    borrow::Cow,
+    io::{Error, ErrorKind, Result},
+};
+
+use gitlab::{
+    api::{self, ApiError, BodyError, Endpoint, FormParams, Pageable, Query, QueryParams},
+};
+use http::Method;
+use serde::Deserialize;
+
+use crate::{
+    gitlab::{query_all, LazyGitlab},
+    types::v1::AccessLevel,
+};
+use mockall::automock;
+
+// Invitations are sent by email to people who don't have a GitLab account yet,
+// they become members when the invitation is accepted
+#[automock]
+pub(crate) trait GitlabInvitationsApi {
+    fn invite_to_project(&self, email: &str, pid: u64, access_level: AccessLevel) -> Result<String>;
+    fn invite_to_group(&self, email: &str, gid: u64, access_level: AccessLevel) -> Result<String>;
+    fn is_invited_to_project(&self, email: &str, pid: u64) -> Result<bool>;
+    fn is_invited_to_group(&self, email: &str, gid: u64) -> Result<bool>;
+    fn edit_invite_in_project(&self, email: &str, pid: u64, access_level: AccessLevel) -> Result<String>;
+    fn edit_invite_in_group(&self, email: &str, gid: u64, access_level: AccessLevel) -> Result<String>;
+    fn remove_invite_from_project(&self, email: &str, pid: u64) -> Result<String>;
+    fn remove_invite_from_group(&self, email: &str, gid: u64) -> Result<String>;
+}
+
+pub(crate) struct InvitationGitlab {
+    pub(crate) gitlab_client: LazyGitlab,
+}
+
+impl InvitationGitlab {
+    pub(crate) fn new(gitlab_client: LazyGitlab) -> Self {
+        Self { gitlab_client }
+    }
+
+    fn invite(&self, source: Source, email: &str, access_level: AccessLevel) -> Result<String> {
+        let q = Invitation {
+            source,
+            method: Method::POST,
+            email,
+            access_level: Some(access_level),
+        };
+        // GitLab answers with 201 even if the invitation is not sent, the reason is in the body
+        let response: InvitationResponse = match q.query(&self.gitlab_client) {
+            Ok(r) => r,
+            Err(err) => return Err(Error::new(ErrorKind::AddrNotAvailable, err)),
+        };
+        match response.status.as_str() {
+            "success" => Ok("Invited".to_string()),
+            _ => {
+                let msg = response.message.to_string();
+                if msg.contains("already") {
+                    return Ok("Already invited".to_string());
+                }
+                Err(Error::new(ErrorKind::AddrNotAvailable, msg))
+            }
+        }
+    }
+
+    fn is_invited(&self, source: Source, email: &str) -> Result<bool> {
+        let q = PendingInvitations { source, email };
+        let invitations: Vec<PendingInvitation> = query_all(&self.gitlab_client, q)?;
+        Ok(invitations
+            .iter()
+            .any(|i| i.invite_email.eq_ignore_ascii_case(email)))
+    }
+
+    fn edit(&self, source: Source, email: &str, access_level: AccessLevel) -> Result<String> {
+        let q = Invitation {
+            source,
+            method: Method::PUT,
+            email,
+            access_level: Some(access_level),
+        };
+        match api::ignore(q).query(&self.gitlab_client) {
+            Ok(_) => Ok("Updated".to_string()),
+            Err(err) => Err(Error::new(ErrorKind::AddrNotAvailable, err)),
+        }
+    }
+
+    fn remove(&self, source: Source, email: &str) -> Result<String> {
+        let q = Invitation {
+            source,
+            method: Method::DELETE,
+            email,
+            access_level: None,
+        };
+        match api::ignore(q).query(&self.gitlab_client) {
+            Ok(_) => Ok("Removed".to_string()),
+            Err(ApiError::Gitlab { msg }) if msg.starts_with("404") => Ok("Not found".to_string()),
+            Err(err) => Err(Error::new(ErrorKind::AddrNotAvailable, err)),
+        }
+    }
+}
+
+impl GitlabInvitationsApi for InvitationGitlab {
+    fn invite_to_project(&self, email: &str, pid: u64, access_level: AccessLevel) -> Result<String> {
+        self.invite(Source::Project(pid), email, access_level)
+    }
+
+    fn invite_to_group(&self, email: &str, gid: u64, access_level: AccessLevel) -> Result<String> {
+        self.invite(Source::Group(gid), email, access_level)
+    }
+
+    fn is_invited_to_project(&self, email: &str, pid: u64) -> Result<bool> {
+        self.is_invited(Source::Project(pid), email)
+    }
+
+    fn is_invited_to_group(&self, email: &str, gid: u64) -> Result<bool> {
+        self.is_invited(Source::Group(gid), email)
+    }
+
+    fn edit_invite_in_project(&self, email: &str, pid: u64, access_level: AccessLevel) -> Result<String> {
+        self.edit(Source::Project(pid), email, access_level)
+    }
+
+    fn edit_invite_in_group(&self, email: &str, gid: u64, access_level: AccessLevel) -> Result<String> {
+        self.edit(Source::Group(gid), email, access_level)
+    }
+
+    fn remove_invite_from_project(&self, email: &str, pid: u64) -> Result<String> {
+        self.remove(Source::Project(pid), email)
+    }
+
+    fn remove_invite_from_group(&self, email: &str, gid: u64) -> Result<String> {
+        self.remove(Source::Group(gid), email)
+    }
+}
+
+/*
+The gitlab crate doesn't have endpoints for invitations, so they are defined here
+*/
+
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    Project(u64),
+    Group(u64),
+}
+
+impl Source {
+    fn endpoint(&self) -> String {
+        match self {
+            Source::Project(id) => format!("projects/{}/invitations", id),
+            Source::Group(id) => format!("groups/{}/invitations", id),
+        }
+    }
+}
+
+// An email is a part of the path when an invitation is edited or removed, it's encoded by UTF-8 bytes
+fn escape(email: &str) -> String {
+    email
+        .bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'.' | b'-' | b'_' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+struct Invitation<'a> {
+    source: Source,
+    method: Method,
+    email: &'a str,
+    access_level: Option<AccessLevel>,
+}
+
+impl<'a> Endpoint for Invitation<'a> {
+    fn method(&self) -> Method {
+        self.method.clone()
+    }
+
+    fn endpoint(&self) -> Cow<'static, str> {
+        match self.method {
+            Method::POST => self.source.endpoint().into(),
+            _ => format!("{}/{}", self.source.endpoint(), escape(self.email)).into(),
+        }
+    }
+
+    fn body(&self) -> std::result::Result<Option<(&'static str, Vec<u8>)>, BodyError> {
+        let mut params = FormParams::default();
+        if self.method == Method::POST {
+            params.push("email", self.email);
+        }
+        if let Some(access_level) = self.access_level {
+            params.push("access_level", access_level.to_gitlab_access_level().as_u64());
+        }
+        params.into_body()
+    }
+}
+
+struct PendingInvitations<'a> {
+    source: Source,
+    email: &'a str,
+}
+
+impl<'a> Endpoint for PendingInvitations<'a> {
+    fn method(&self) -> Method {
+        Method::GET
+    }
+
+    fn endpoint(&self) -> Cow<'static, str> {
+        self.source.endpoint().into()
+    }
+
+    fn parameters(&self) -> QueryParams<'_> {
+        let mut params = QueryParams::default();
+        params.push("query", self.email);
+        params
+    }
+}
+
+impl<'a> Pageable for PendingInvitations<'a> {}
+
+#[derive(Debug, Deserialize)]
+struct InvitationResponse {
+    status: String,
+    #[serde(default)]
+    message: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct PendingInvitation {
+    invite_email: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, GitlabInvitationsApi, InvitationGitlab};
+    use crate::gitlab::{test_client, test_page};
+
+    #[test]
+    fn escape_email() {
+        assert_eq!(escape("jane.doe+gum@example.com"), "jane.doe%2Bgum%40example.com");
+        assert_eq!(escape("jürgen@example.com"), "j%C3%BCrgen%40example.com");
+    }
+
+    #[test]
+    fn pending_invitations_on_every_page() {
+        let invitations: Vec<String> = (1..=25)
+            .map(|id| format!(r#"{{"invite_email":"jane{}@example.com"}}"#, id))
+            .collect();
+        let gitlab_client = test_client(move |path| match path.starts_with("/api/v4/projects/10/invitations") {
+            true => test_page(path, &invitations),
+            false => "[]".to_string(),
+        });
+
+        let invitations_api = InvitationGitlab::new(gitlab_client);
+        assert!(invitations_api.is_invited_to_project("Jane25@example.com", 10).unwrap());
+        assert!(!invitations_api.is_invited_to_project("jane26@example.com", 10).unwrap());
+    }
+}
@@ -1,10 +1,12 @@
 use gitlab::{
-    api::{self, groups, projects, ApiError, Query},
-    Gitlab,
+    api::{self, groups, projects, ApiError, Endpoint, Query},
 };
 use std::io::{Error, ErrorKind, Result};
 
-use crate::types::v1::AccessLevel;
+use crate::{gitlab::LazyGitlab, types::v1::AccessLevel};
+use mockall::automock;
+
+#[automock]
 pub(crate) trait GitlabMembersApi {
     fn add_user_to_project(&self, uid: u64, pid: u64, access_level: AccessLevel) -> Result<String>;
     fn add_user_to_group(&self, uid: u64, gid: u64, access_level: AccessLevel) -> Result<String>;
@@ -23,16 +25,27 @@ pub(crate) trait GitlabMembersApi {
     ) -> Result<String>;
     fn remove_group_from_namespace(&self, gid: u64, nid: u64) -> Result<String>;
     fn edit_user_in_group(&self, uid: u64, gid: u64, access_level: AccessLevel) -> Result<String>;
+    fn is_member_of_project(&self, uid: u64, pid: u64) -> Result<bool>;
+    fn is_member_of_group(&self, uid: u64, gid: u64) -> Result<bool>;
 }
 
 pub(crate) struct MemberGitlab {
-    pub(crate) gitlab_client: Gitlab,
+    pub(crate) gitlab_client: LazyGitlab,
 }
 
 impl MemberGitlab {
-    pub(crate) fn new(gitlab_client: Gitlab) -> Self {
+    pub(crate) fn new(gitlab_client: LazyGitlab) -> Self {
         Self { gitlab_client }
     }
+
+    // Only direct members are found, inherited ones are not
+    fn is_member<E: Endpoint>(&self, q: E) -> Result<bool> {
+        match api::ignore(q).query(&self.gitlab_client) {
+            Ok(_) => Ok(true),
+            Err(ApiError::Gitlab { msg }) if msg.starts_with("404") => Ok(false),
+            Err(err) => Err(Error::new(ErrorKind::AddrNotAvailable, err)),
+        }
+    }
 }
 
 impl GitlabMembersApi for MemberGitlab {
@@ -300,4 +313,32 @@ impl GitlabMembersApi for MemberGitlab {
             Err(_) => return Err(Error::new(ErrorKind::AddrNotAvailable, "asd")),
         };
     }
+
+    fn is_member_of_project(&self, uid: u64, pid: u64) -> Result<bool> {
+        let q = match projects::members::ProjectMember::builder()
+            .user(uid)
+            .project(pid)
+            .build()
+        {
+            Ok(q) => q,
+            Err(err) => {
+                return Err(Error::other(err.to_string()));
+            }
+        };
+        self.is_member(q)
+    }
+
+    fn is_member_of_group(&self, uid: u64, gid: u64) -> Result<bool> {
+        let q = match groups::members::GroupMember::builder()
+            .user(uid)
+            .group(gid)
+            .build()
+        {
+            Ok(q) => q,
+            Err(err) => {
+                return Err(Error::other(err.to_string()));
+            }
+        };
+        self.is_member(q)
+    }
 }
@@ -1,4 +1,5 @@
 pub(crate) mod groups;
+pub(crate) mod invitations;
 pub(crate) mod members;
 pub(crate) mod projects;
 pub(crate) mod users;
@@ -1,34 +1,32 @@
 use crate::{
     gitlab::{
         types::project::{ProjectsWithShared, SharedWithGroups, Project},
-        CustomMember,
+        query_all, query_object, CustomMember, LazyGitlab,
     },
     output::{out_message::OutMessage, out_spinner::OutSpinner},
     types::v1::AccessLevel,
 };
-use core::time;
 use gitlab::{
     api::{self, projects, ApiError, Query},
-    Gitlab,
-};
-use std::{
-    io::{Error, ErrorKind, Result},
-    thread,
 };
+use std::io::{Error, ErrorKind, Result};
+use mockall::automock;
 
+#[automock]
 pub(crate) trait GitlabProjectsApi {
     fn add_user(&self, uid: u64, pid: u64, access_level: AccessLevel) -> Result<String>;
     fn edit_user(&self, uid: u64, pid: u64, access_level: AccessLevel) -> Result<String>;
     fn get_data_by_id(&self, id: u64) -> Result<Project>;
+    fn get_data_by_path(&self, path: &str) -> Result<Project>;
     fn get_groups_shared_with(&self, pid: u64) -> Result<Vec<SharedWithGroups>>;
-    fn get_members(&self, name: String, id: u64) -> Vec<CustomMember>;
+    fn get_members(&self, name: String, id: u64) -> Result<Vec<CustomMember>>;
     fn remove_user(&self, uid: u64, pid: u64) -> Result<String>;
     fn share_with_group(&self, gid: u64, pid: u64, access_level: AccessLevel) -> Result<String>;
     fn stop_sharing_with_group(&self, gid: u64, pid: u64) -> Result<String>;
 }
 
 pub(crate) struct ProjectsGitlab {
-    pub(crate) gitlab_client: Gitlab,
+    pub(crate) gitlab_client: LazyGitlab,
 }
 
 impl GitlabProjectsApi for ProjectsGitlab {
@@ -81,31 +79,17 @@ impl GitlabProjectsApi for ProjectsGitlab {
     fn get_data_by_id(&self, id: u64) -> Result<Project> {
         let project = match projects::Project::builder().project(id).build() {
             Ok(project) => project,
-            Err(err) => {
-                return Err(Error::new(std::io::ErrorKind::Other, err.to_string()));
-            }
+            Err(err) => return Err(Error::other(err.to_string())),
         };
+        query_object(&self.gitlab_client, &project, &format!("project {}", id))
+    }
 
-        let output: Project = match project.query(&self.gitlab_client) {
-            Err(err) => {
-                match err {
-                    ApiError::GitlabObject { ref obj } => {
-                        if format!("{}", obj) == "{\"error\":\"This endpoint has been requested too many times. Try again later.\"}" {
-                                OutMessage::message_info_with_alias("Gitlab is screwed by amount of your requests. You need to wait");
-                                let spinner = OutSpinner::spinner_start("Waiting 30s".to_string());
-                                let await_time = time::Duration::from_secs(30);
-                                thread::sleep(await_time);
-                                spinner.spinner_success("Let's try again".to_string());
-                                return self.get_data_by_id(id);
-                            };
-                    }
-                    _ => return Err(Error::new(ErrorKind::AddrNotAvailable, err)),
-                };
-                return Err(Error::new(ErrorKind::AddrNotAvailable, err));
-            }
-            Ok(res) => res,
+    fn get_data_by_path(&self, path: &str) -> Result<Project> {
+        let project = match projects::Project::builder().project(path).build() {
+            Ok(project) => project,
+            Err(err) => return Err(Error::other(err.to_string())),
         };
-        Ok(output)
+        query_object(&self.gitlab_client, &project, &format!("project {}", path))
     }
 
     fn get_groups_shared_with(&self, id: u64) -> Result<Vec<SharedWithGroups>> {
@@ -124,18 +108,26 @@ impl GitlabProjectsApi for ProjectsGitlab {
         Ok(r)
     }
 
-    fn get_members(&self, name: String, id: u64) -> Vec<CustomMember> {
-        let spinner = OutSpinner::spinner_start(format!("Getting projects from {}", name));
+    /// Get users that have access to this project
+    fn get_members(&self, name: String, id: u64) -> Result<Vec<CustomMember>> {
+        let spinner = OutSpinner::spinner_start(format!("Getting users from {}", name));
         let query = match projects::members::ProjectMembers::builder()
             .project(id)
             .build()
         {
             Ok(q) => q,
-            Err(_) => todo!(),
+            Err(err) => return Err(Error::other(err.to_string())),
         };
-        let users: Vec<CustomMember> = query.query(&self.gitlab_client).unwrap();
-        OutSpinner::spinner_success(spinner, "Done".to_string());
-        users
+        match query_all(&self.gitlab_client, query) {
+            Ok(users) => {
+                OutSpinner::spinner_success(spinner, "Done".to_string());
+                Ok(users)
+            }
+            Err(err) => {
+                OutSpinner::spinner_failure(spinner, err.to_string());
+                Err(err)
+            }
+        }
     }
 
     fn remove_user(&self, uid: u64, pid: u64) -> Result<String> {
@@ -220,3 +212,31 @@ impl GitlabProjectsApi for ProjectsGitlab {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::{GitlabProjectsApi, ProjectsGitlab};
+    use crate::gitlab::{test_client, test_page};
+
+    #[test]
+    fn members_of_every_page() {
+        let members: Vec<String> = (1..=130)
+            .map(|id| {
+                format!(
+                    r#"{{"id":{},"access_level":30,"username":"user{}","name":"User {}","web_url":""}}"#,
+                    id, id, id
+                )
+            })
+            .collect();
+        let gitlab_client = test_client(move |path| match path.starts_with("/api/v4/projects/10/members") {
+            true => test_page(path, &members),
+            false => "[]".to_string(),
+        });
+
+        let members = ProjectsGitlab { gitlab_client }
+            .get_members("api".to_string(), 10)
+            .unwrap();
+        assert_eq!(members.len(), 130);
+        assert_eq!(members.last().unwrap().username, "user130");
+    }
+}
@@ -1,18 +1,22 @@
-use gitlab::{Gitlab, api::{users, ApiError, Query}};
-use core::time;
-use std::{io::{Result, Error, ErrorKind}, thread};
+use gitlab::api::{users, Query};
+use std::io::{Result, Error, ErrorKind};
 
-use crate::{gitlab::types::users::User, output::{out_message::OutMessage, out_spinner::OutSpinner}};
+use crate::gitlab::{query_all, query_object, types::users::User, LazyGitlab};
+use mockall::automock;
+
+#[automock]
 pub(crate) trait GitlabUsersApi {
     fn get_data_by_id(&self, id: u64) -> Result<User>;
+    fn find_by_username(&self, username: &str) -> Result<Vec<User>>;
+    fn find_by_email(&self, email: &str) -> Result<Vec<User>>;
 }
 
 pub(crate) struct UserGitlab {
-    pub(crate) gitlab_client: Gitlab,
+    pub(crate) gitlab_client: LazyGitlab,
 }
 
 impl UserGitlab {
-    pub(crate) fn new(gitlab_client: Gitlab) -> Self {
+    pub(crate) fn new(gitlab_client: LazyGitlab) -> Self {
         Self { gitlab_client }
     }
 }
@@ -21,30 +25,64 @@ impl GitlabUsersApi for UserGitlab {
     fn get_data_by_id(&self, id: u64) -> Result<User> {
         let user = match users::User::builder().user(id).build() {
             Ok(user) => user,
+            Err(err) => return Err(Error::other(err.to_string())),
+        };
+        query_object(&self.gitlab_client, &user, &format!("user {}", id))
+    }
+
+    /// Get users with this username, GitLab matches usernames exactly
+    fn find_by_username(&self, username: &str) -> Result<Vec<User>> {
+        let users = match users::Users::builder().username(username).build() {
+            Ok(users) => users,
             Err(err) => {
-                return Err(Error::new(std::io::ErrorKind::Other, err.to_string()));
+                return Err(Error::other(err.to_string()));
             }
         };
-        let output: User = match user.query(&self.gitlab_client) {
+        match users.query(&self.gitlab_client) {
+            Ok(res) => Ok(res),
+            Err(err) => Err(Error::new(ErrorKind::AddrNotAvailable, err)),
+        }
+    }
+
+    /// Get users with this email, private emails can be only found with an admin token
+    fn find_by_email(&self, email: &str) -> Result<Vec<User>> {
+        let users = match users::Users::builder().search(email).build() {
+            Ok(users) => users,
             Err(err) => {
-                match err {
-                    ApiError::GitlabObject { ref obj } => {
-                        if format!("{}", obj) == "{\"error\":\"This endpoint has been requested too many times. Try again later.\"}" {
-                                OutMessage::message_info_with_alias("Gitlab is screwed by amount of your requests. You need to wait");
-                                let spinner = OutSpinner::spinner_start("Waiting 30s".to_string());
-                                let await_time = time::Duration::from_secs(30);
-                                thread::sleep(await_time);
-                                spinner.spinner_success("Let's try again".to_string());
-                                return self.get_data_by_id(id);
-                            };
-                    }
-                    _ => return Err(Error::new(ErrorKind::AddrNotAvailable, err)),
-                };
-                return Err(Error::new(ErrorKind::AddrNotAvailable, err));
+                return Err(Error::other(err.to_string()));
             }
-            Ok(res) => res,
         };
+        let output: Vec<User> = query_all(&self.gitlab_client, users)?;
+        // Search is also matching names and usernames, so only exact emails are left
+        Ok(output.into_iter().filter(|u| u.has_email(email)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GitlabUsersApi, UserGitlab};
+    use crate::gitlab::{test_client, test_page};
+
+    #[test]
+    fn find_by_email_on_every_page() {
+        // The search matches names too, the user with the email is on the second page
+        let users: Vec<String> = (1..=25)
+            .map(|id| {
+                format!(
+                    r#"{{"id":{},"username":"jane{}","name":"Jane","web_url":"","public_email":"jane{}@example.com"}}"#,
+                    id, id, id
+                )
+            })
+            .collect();
+        let gitlab_client = test_client(move |path| match path.starts_with("/api/v4/users") {
+            true => test_page(path, &users),
+            false => "[]".to_string(),
+        });
 
-        Ok(output)
+        let found = UserGitlab::new(gitlab_client)
+            .find_by_email("Jane25@example.com")
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, 25);
     }
 }
@@ -4,13 +4,16 @@ pub(crate) mod types;
 use core::time;
 use std::{
     io::{Error, ErrorKind},
+    sync::{Arc, OnceLock},
     thread,
 };
 
+use bytes::Bytes;
 use gitlab::{
     api::{self, groups, projects, users, ApiError, Query},
-    Gitlab, Member,
+    Gitlab, Member, RestError,
 };
+use url::Url;
 
 use serde::Deserialize;
 use tabled::Tabled;
@@ -22,7 +25,8 @@ use crate::{
 };
 
 use self::apis::{
-    groups::{GitlabGroupsApi, GroupGitlabMock},
+    groups::GitlabGroupsApi,
+    invitations::{GitlabInvitationsApi, InvitationGitlab},
     members::{GitlabMembersApi, MemberGitlab},
     projects::{GitlabProjectsApi, ProjectsGitlab},
     users::{GitlabUsersApi, UserGitlab},
@@ -33,24 +37,130 @@ pub(crate) trait GitlabApiInterface {
     type Projects: GitlabProjectsApi;
     type Users: GitlabUsersApi;
     type Members: GitlabMembersApi;
+    type Invitations: GitlabInvitationsApi;
 
     fn groups(&self) -> Self::Groups;
     fn projects(&self) -> Self::Projects;
     fn users(&self) -> Self::Users;
     fn members(&self) -> Self::Members;
+    fn invitations(&self) -> Self::Invitations;
+}
+
+/// Query every page of the endpoint, GitLab returns only 20 items without pagination
+pub(crate) fn query_all<E, T>(gitlab_client: &LazyGitlab, endpoint: E) -> Result<Vec<T>, Error>
+where
+    E: api::Endpoint + api::Pageable,
+    T: serde::de::DeserializeOwned,
+{
+    api::paged(endpoint, api::Pagination::All)
+        .query(gitlab_client)
+        .map_err(|err| Error::new(ErrorKind::AddrNotAvailable, err))
+}
+
+/// Query a single object, the request is repeated after a pause while GitLab limits the rate of requests.
+/// `name` is used in the error when the object is not found
+pub(crate) fn query_object<E, T>(gitlab_client: &LazyGitlab, endpoint: &E, name: &str) -> Result<T, Error>
+where
+    E: api::Endpoint,
+    T: serde::de::DeserializeOwned,
+{
+    loop {
+        match endpoint.query(gitlab_client) {
+            Ok(res) => return Ok(res),
+            Err(ApiError::GitlabObject { ref obj })
+                if format!("{}", obj) == "{\"error\":\"This endpoint has been requested too many times. Try again later.\"}" =>
+            {
+                OutMessage::message_info_with_alias("Gitlab is screwed by amount of your requests. You need to wait");
+                let spinner = OutSpinner::spinner_start("Waiting 30s".to_string());
+                thread::sleep(time::Duration::from_secs(30));
+                spinner.spinner_success("Let's try again".to_string());
+            }
+            Err(ApiError::Gitlab { ref msg }) if msg.starts_with("404") => {
+                return Err(Error::new(ErrorKind::NotFound, format!("{} can't be found", name)))
+            }
+            Err(err) => return Err(Error::new(ErrorKind::AddrNotAvailable, err)),
+        }
+    }
+}
+
+// LazyGitlab connects to GitLab on the first request, because the client checks the token when it's created.
+// Clones share the connection, so it's made only once
+#[derive(Clone)]
+pub(crate) struct LazyGitlab {
+    gitlab_url: String,
+    gitlab_token: String,
+    gitlab_client: Arc<OnceLock<Gitlab>>,
+}
+
+impl LazyGitlab {
+    pub(crate) fn new(gitlab_url: &str, gitlab_token: &str) -> Self {
+        Self {
+            gitlab_url: gitlab_url.to_string(),
+            gitlab_token: gitlab_token.to_string(),
+            gitlab_client: Arc::new(OnceLock::new()),
+        }
+    }
+
+    fn connect(&self) -> Result<&Gitlab, ApiError<RestError>> {
+        if let Some(gitlab_client) = self.gitlab_client.get() {
+            return Ok(gitlab_client);
+        }
+        let gitlab_client = Gitlab::new(self.gitlab_url.clone(), self.gitlab_token.clone()).map_err(|err| {
+            ApiError::Gitlab {
+                msg: format!("can't connect to {}: {}", self.gitlab_url, err),
+            }
+        })?;
+        Ok(self.gitlab_client.get_or_init(|| gitlab_client))
+    }
+}
+
+impl From<Gitlab> for LazyGitlab {
+    fn from(gitlab_client: Gitlab) -> Self {
+        Self {
+            gitlab_url: String::new(),
+            gitlab_token: String::new(),
+            gitlab_client: Arc::new(OnceLock::from(gitlab_client)),
+        }
+    }
+}
+
+impl api::RestClient for LazyGitlab {
+    type Error = RestError;
+
+    fn rest_endpoint(&self, endpoint: &str) -> Result<Url, ApiError<Self::Error>> {
+        self.connect()?.rest_endpoint(endpoint)
+    }
+}
+
+impl api::Client for LazyGitlab {
+    fn rest(
+        &self,
+        request: http::request::Builder,
+        body: Vec<u8>,
+    ) -> Result<http::Response<Bytes>, ApiError<Self::Error>> {
+        self.connect()?.rest(request, body)
+    }
 }
 
 pub(crate) struct GitlabApi {
-    pub(crate) gitlab_client: Gitlab,
+    pub(crate) gitlab_client: LazyGitlab,
 }
 
 impl GitlabApi {
     pub(crate) fn new(gitlab_url: &String, gitlab_token: &String) -> Result<Self, Error> {
         match Gitlab::new(gitlab_url.clone(), gitlab_token.clone()) {
-            Ok(gitlab_client) => Ok(GitlabApi { gitlab_client }),
+            Ok(gitlab_client) => Ok(GitlabApi { gitlab_client: gitlab_client.into() }),
             Err(err) => return Err(Error::new(ErrorKind::Other, err)),
         }
     }
+
+    /// Create the API without connecting to GitLab, it's done by the first request.
+    /// It's used by commands that may get everything from the config and its lock file
+    pub(crate) fn lazy(gitlab_url: &str, gitlab_token: &str) -> Self {
+        GitlabApi {
+            gitlab_client: LazyGitlab::new(gitlab_url, gitlab_token),
+        }
+    }
 }
 
 impl GitlabApiInterface for GitlabApi {
@@ -80,21 +190,120 @@ impl GitlabApiInterface for GitlabApi {
     fn members(&self) -> Self::Members {
         MemberGitlab::new(self.gitlab_client.clone())
     }
+
+    type Invitations = InvitationGitlab;
+
+    fn invitations(&self) -> Self::Invitations {
+        InvitationGitlab::new(self.gitlab_client.clone())
+    }
+}
+// GitlabApiMock should be used in tests instead of GitLab. Every API is made by its function,
+// the default ones have no expectations, so any call fails the test
+#[cfg(test)]
+pub(crate) struct GitlabApiMock {
+    pub(crate) groups: Box<dyn Fn() -> apis::groups::MockGitlabGroupsApi>,
+    pub(crate) projects: Box<dyn Fn() -> apis::projects::MockGitlabProjectsApi>,
+    pub(crate) users: Box<dyn Fn() -> apis::users::MockGitlabUsersApi>,
+    pub(crate) members: Box<dyn Fn() -> apis::members::MockGitlabMembersApi>,
+    pub(crate) invitations: Box<dyn Fn() -> apis::invitations::MockGitlabInvitationsApi>,
+}
+
+#[cfg(test)]
+impl Default for GitlabApiMock {
+    fn default() -> Self {
+        Self {
+            groups: Box::new(apis::groups::MockGitlabGroupsApi::new),
+            projects: Box::new(apis::projects::MockGitlabProjectsApi::new),
+            users: Box::new(apis::users::MockGitlabUsersApi::new),
+            members: Box::new(apis::members::MockGitlabMembersApi::new),
+            invitations: Box::new(apis::invitations::MockGitlabInvitationsApi::new),
+        }
+    }
+}
+
+#[cfg(test)]
+impl GitlabApiInterface for GitlabApiMock {
+    type Groups = apis::groups::MockGitlabGroupsApi;
+    type Projects = apis::projects::MockGitlabProjectsApi;
+    type Users = apis::users::MockGitlabUsersApi;
+    type Members = apis::members::MockGitlabMembersApi;
+    type Invitations = apis::invitations::MockGitlabInvitationsApi;
+
+    fn groups(&self) -> Self::Groups {
+        (self.groups)()
+    }
+
+    fn projects(&self) -> Self::Projects {
+        (self.projects)()
+    }
+
+    fn users(&self) -> Self::Users {
+        (self.users)()
+    }
+
+    fn members(&self) -> Self::Members {
+        (self.members)()
+    }
+
+    fn invitations(&self) -> Self::Invitations {
+        (self.invitations)()
+    }
+}
+
+// Serve GitLab API requests with the handler in tests, it gets the path with the query and returns the body
+#[cfg(test)]
+pub(crate) fn test_client<F>(handler: F) -> LazyGitlab
+where
+    F: Fn(&str) -> String + Send + 'static,
+{
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+            }
+            let body = handler(request_line.split_whitespace().nth(1).unwrap_or_default());
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+    gitlab::GitlabBuilder::new_unauthenticated(host)
+        .insecure()
+        .build()
+        .unwrap()
+        .into()
+}
+
+// Get the page of items that is requested by the path, GitLab returns 20 items if the page is not set
+#[cfg(test)]
+pub(crate) fn test_page(path: &str, items: &[String]) -> String {
+    let param = |name: &str, default: usize| {
+        path.split(['?', '&'])
+            .find_map(|p| p.strip_prefix(format!("{}=", name).as_str()))
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(default)
+    };
+    let (page, per_page) = (param("page", 1), param("per_page", 20));
+    let page: Vec<String> = items.iter().skip((page - 1) * per_page).take(per_page).cloned().collect();
+    format!("[{}]", page.join(","))
 }
-//pub(crate) struct GitlabApiMock;
-
-//impl GitlabApiInterface for GitlabApiMock {
-//    type Groups = GroupGitlabMock;
-//    fn groups(&self) -> Self::Groups {
-//        GroupGitlabMock {}
-//    }
-//
-//    type Projects = ProjectsGitlabMock;
-//
-//    fn projects(&self) -> Self::Projects {
-//        todo!()
-//    }
-//}
 
 /*
 ======================================================================================================================
@@ -178,6 +387,7 @@ impl Project {
     pub(crate) fn to_gum_project(&self, member: CustomMember) -> Result<v1::Project, Error> {
         let project = v1::Project {
             id: self.id,
+            path: Default::default(),
             name: self.name.clone(),
             access_level: AccessLevel::from_gitlab_access_level(member.access_level),
         };
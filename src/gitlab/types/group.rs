@@ -12,12 +12,15 @@ pub(crate) struct Group {
     pub(crate) id: u64,
     pub(crate) name: String,
     pub(crate) web_url: String,
+    #[serde(default)]
+    pub(crate) full_path: String,
 }
 
 impl Group {
     pub(crate) fn to_gum_group(&self, member: CustomMember) -> Result<Namespace> {
         let group = Namespace {
             id: self.id,
+            path: Default::default(),
             name: self.name.clone(),
             url: self.web_url.clone(),
             access_level: AccessLevel::from_gitlab_access_level(member.access_level),
@@ -9,12 +9,15 @@ pub(crate) struct Project {
     pub(crate) id: u64,
     pub(crate) name: String,
     pub(crate) web_url: String,
+    #[serde(default)]
+    pub(crate) path_with_namespace: String,
 }
 
 impl Project {
     pub(crate) fn to_gum_project(&self, member: CustomMember) -> Result<v1::Project> {
         let project = v1::Project {
             id: self.id,
+            path: Default::default(),
             name: self.name.clone(),
             access_level: AccessLevel::from_gitlab_access_level(member.access_level),
         };
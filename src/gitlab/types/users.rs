@@ -7,4 +7,24 @@ pub(crate) struct User {
     pub(crate) username: String,
     pub(crate) name: String,
     pub(crate) web_url: String,
+    // Private email is only returned for admin tokens
+    #[serde(default)]
+    #[tabled(skip)]
+    pub(crate) email: Option<String>,
+    #[serde(default)]
+    #[tabled(skip)]
+    pub(crate) public_email: Option<String>,
+    // It's only returned for admin tokens too
+    #[serde(default)]
+    #[tabled(skip)]
+    pub(crate) two_factor_enabled: Option<bool>,
+}
+
+impl User {
+    /// Check whether the user has this email, either private or public one
+    pub(crate) fn has_email(&self, email: &str) -> bool {
+        [&self.email, &self.public_email]
+            .iter()
+            .any(|e| matches!(e, Some(e) if e.eq_ignore_ascii_case(email)))
+    }
 }
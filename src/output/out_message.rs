@@ -24,6 +24,12 @@ impl OutMessage {
         println!(" {}", style(msg).blue());
     }
 
+    pub(crate) fn message_warning(msg: &str) {
+        let prefix = format!("{}", style("[WARN]").yellow());
+        let msg = format!("{} {}", prefix, msg,);
+        println!(" {}", style(msg).yellow());
+    }
+
     pub(crate) fn message_error(msg: &str) {
         let prefix = format!("{}", style("[ERROR]").red());
         let msg = format!("{} {}", prefix, msg,);
@@ -0,0 +1,145 @@
+use crate::{
+    output::out_message::OutMessage,
+    store::get_store_type,
+    types::{
+        common::{Version, Versions},
+        v1::{Aliases, ConfigFile},
+    },
+};
+use std::io::{Error, ErrorKind, Result};
+
+// MigrateService should be used to move a config file between versions
+pub(crate) struct MigrateService {
+    config_file: ConfigFile,
+    file_path: String,
+}
+
+impl MigrateService {
+    pub(crate) fn new(file_path: String) -> Result<Self> {
+        Ok(Self {
+            config_file: ConfigFile::read(file_path.clone())?,
+            file_path,
+        })
+    }
+
+    /// Check whether the config file needs to be migrated
+    pub(crate) fn is_needed(&self, to: Versions) -> Result<bool> {
+        Ok(self.config_file.get_version()? != to)
+    }
+
+    /// Tell whether the config should be migrated, a pending migration is an error, so CI can catch it
+    pub(crate) fn check(&self, to: Versions) -> Result<()> {
+        let version = self.config_file.get_version()?;
+        if self.is_needed(to)? {
+            let err_msg = format!("Config version is {}, it should be migrated to {}", version, to);
+            OutMessage::message_error(err_msg.as_str());
+            return Err(Error::new(ErrorKind::InvalidData, err_msg));
+        } else {
+            OutMessage::message_info_with_alias(
+                format!("Config version is already {}, nothing to migrate", version).as_str(),
+            );
+        }
+        Ok(())
+    }
+
+    pub(crate) fn migrate(&mut self, to: Versions) -> Result<&mut Self> {
+        let version = self.config_file.get_version()?;
+        if !self.is_needed(to)? {
+            OutMessage::message_info_with_alias(
+                format!("Config version is already {}, nothing to migrate", version).as_str(),
+            );
+            return Ok(self);
+        }
+
+        // The state is moved as it is, but it must be valid, otherwise it would be lost on the next sync
+        match get_store_type(self.config_file.state().to_string(), self.file_path.clone())?.get() {
+            Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+
+        if to == Versions::V2 {
+            let aliases = Aliases::from_config(
+                self.config_file.config(),
+                self.config_file.meta().aliases(),
+            );
+            OutMessage::message_info_clean(
+                format!(
+                    "Got {} project and {} group aliases from the config",
+                    aliases.projects.len(),
+                    aliases.groups.len()
+                )
+                .as_str(),
+            );
+        }
+
+        self.config_file.meta_mut().set_version(to);
+        OutMessage::message_info_with_alias(
+            format!("Config is migrated from {} to {}", version, to).as_str(),
+        );
+        Ok(self)
+    }
+
+    pub(crate) fn write_config(&self) -> Result<()> {
+        self.config_file.write(self.file_path.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    use super::MigrateService;
+    use crate::types::common::{Version, Versions};
+    use crate::types::v1::ConfigFile;
+
+    #[test]
+    fn migrate_v1_to_v2() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let mut file = File::create(file_path.clone()).unwrap();
+        let data = r#"
+meta:
+  version: V1
+config:
+  teams:
+    - name: default
+      projects:
+        - name: api
+          id: 10
+          access_level: Developer
+  users:
+    - id: 1
+      name: user
+      teams: []
+      projects: []
+      namespaces:
+        - name: backend
+          id: 20
+          access_level: Maintainer
+          url: http://localhost/backend
+state: '{"1":{"entity":"User","projects":{"10":"Developer"},"namespaces":{"20":"Maintainer"}}}'
+"#;
+        writeln!(file, "{}", data).unwrap();
+        let file_name = file_path.to_string_lossy().to_string();
+
+        let mut svc = MigrateService::new(file_name.clone()).unwrap();
+        assert!(svc.is_needed(Versions::V2).unwrap());
+        assert!(svc.check(Versions::V2).is_err());
+        svc.migrate(Versions::V2).unwrap().write_config().unwrap();
+
+        let written = std::fs::read_to_string(&file_path).unwrap();
+        assert!(written.contains("aliases"));
+        assert!(!written.contains("name: api"));
+
+        let config_file = ConfigFile::read(file_name.clone()).unwrap();
+        assert_eq!(config_file.get_version().unwrap(), Versions::V2);
+        assert_eq!(config_file.meta().aliases().projects[&10], "api");
+        assert_eq!(config_file.meta().aliases().groups[&20], "backend");
+        assert_eq!(config_file.config().teams[0].projects[0].name, "api");
+        assert_eq!(config_file.config().users[0].namespaces[0].name, "backend");
+        assert!(config_file.state().contains("\"10\":\"Developer\""));
+        assert!(MigrateService::new(file_name).unwrap().check(Versions::V2).is_ok());
+    }
+}
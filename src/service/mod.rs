@@ -1,4 +1,6 @@
 mod generate;
+mod migrate;
 pub(crate) mod v1;
 
 pub(crate) use self::generate::GenerateService;
+pub(crate) use self::migrate::MigrateService;
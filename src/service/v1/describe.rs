@@ -0,0 +1,422 @@
+use crate::{
+    output::out_message::OutMessage,
+    types::v1::{AccessLevel, Config, ConfigFile, EntityType, Group, LockFile, TeamRole, User, UserRef},
+};
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind, Result},
+};
+
+use super::{diff::resolve_from_lock, StateObject};
+
+// Grant is an access of a subject to an object and the place in the config where it comes from
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Grant {
+    pub(crate) object: StateObject,
+    // The path of the object in the config, it tells apart objects that are not resolved yet
+    pub(crate) path: String,
+    pub(crate) title: String,
+    pub(crate) access: AccessLevel,
+    pub(crate) via: String,
+    // The team that grants the access, it's empty for direct grants
+    pub(crate) team: String,
+}
+
+/// Get every grant of the user from its own entries and teams, `projects_in` rules are not expanded
+pub(crate) fn user_grants(config: &Config, u: &User) -> Vec<Grant> {
+    let mut grants: Vec<Grant> = Vec::new();
+    for p in u.projects.iter() {
+        grants.push(Grant {
+            object: StateObject::Project(p.id),
+            path: p.path.clone(),
+            title: title(p.id, &p.name, &p.path),
+            access: p.access_level,
+            via: "direct".to_string(),
+            team: String::new(),
+        });
+    }
+    for n in u.namespaces.iter() {
+        grants.push(Grant {
+            object: StateObject::Group(n.id),
+            path: n.path.clone(),
+            title: title(n.id, &n.name, &n.path),
+            access: n.access_level,
+            via: "direct".to_string(),
+            team: String::new(),
+        });
+    }
+    for (t, role) in config.user_teams(u) {
+        let via = match role {
+            TeamRole::Member => format!("team {}", t.name),
+            TeamRole::Lead => format!("team {} as a lead", t.name),
+        };
+        for p in t.projects.iter() {
+            grants.push(Grant {
+                object: StateObject::Project(p.id),
+                path: p.path.clone(),
+                title: title(p.id, &p.name, &p.path),
+                access: role.access(p.access_level),
+                via: via.clone(),
+                team: t.name.clone(),
+            });
+        }
+        for n in t.namespaces.iter() {
+            grants.push(Grant {
+                object: StateObject::Group(n.id),
+                path: n.path.clone(),
+                title: title(n.id, &n.name, &n.path),
+                access: role.access(n.access_level),
+                via: via.clone(),
+                team: t.name.clone(),
+            });
+        }
+    }
+    grants
+}
+
+pub(crate) fn group_grants(g: &Group) -> Vec<Grant> {
+    let projects = g.projects.iter().map(|p| Grant {
+        object: StateObject::Project(p.id),
+        path: p.path.clone(),
+        title: title(p.id, &p.name, &p.path),
+        access: p.access_level,
+        via: "direct".to_string(),
+        team: String::new(),
+    });
+    let namespaces = g.namespaces.iter().map(|n| Grant {
+        object: StateObject::Group(n.id),
+        path: n.path.clone(),
+        title: title(n.id, &n.name, &n.path),
+        access: n.access_level,
+        via: "direct".to_string(),
+        team: String::new(),
+    });
+    projects.chain(namespaces).collect()
+}
+
+/// Get the title of a subject of the state and its grants from the config
+pub(crate) fn state_subject(config: &Config, id: u64, entity: &EntityType) -> (String, Vec<Grant>) {
+    let found = match entity {
+        EntityType::User => config
+            .users
+            .iter()
+            .find(|u| u.id == id)
+            .map(|u| (format!("user {}", title(u.id, &u.name, &u.username)), user_grants(config, u))),
+        EntityType::Group => config
+            .groups
+            .iter()
+            .find(|g| g.id == id)
+            .map(|g| (format!("group {}", title(g.id, &g.name, &g.path)), group_grants(g))),
+        EntityType::Invite => config
+            .invites
+            .iter()
+            .find(|i| i.state_id() == id)
+            .map(|i| (format!("invite of {}", i.email), user_grants(config, &i.as_user()))),
+    };
+    found.unwrap_or_else(|| (format!("{:?} {}", entity, id), Vec::new()))
+}
+
+impl Grant {
+    /// Get the key of the object, objects without an id are told apart by their paths
+    pub(crate) fn key(&self) -> (StateObject, String) {
+        match self.object {
+            StateObject::Project(0) | StateObject::Group(0) => (self.object, self.path.clone()),
+            object => (object, String::new()),
+        }
+    }
+}
+
+/// Merge grants of the same object, the higher access wins
+pub(crate) fn effective(grants: &[Grant]) -> BTreeMap<(StateObject, String), (String, AccessLevel)> {
+    let mut access: BTreeMap<(StateObject, String), (String, AccessLevel)> = BTreeMap::new();
+    for g in grants.iter() {
+        access
+            .entry(g.key())
+            .and_modify(|(_, a)| *a = g.access.higher(*a))
+            .or_insert((g.title.clone(), g.access));
+    }
+    access
+}
+
+// Entries that are referenced by path only don't have an id until they're synced
+pub(super) fn title(id: u64, name: &str, path: &str) -> String {
+    let id = if id == 0 { "?".to_string() } else { id.to_string() };
+    match (name.is_empty(), path.is_empty()) {
+        (false, _) => format!("{} ({})", id, name),
+        (true, false) => format!("{} ({})", id, path),
+        _ => id,
+    }
+}
+
+// DescribeService should be used to show everything the config knows about an entity.
+// Paths are resolved with the lock file, entries that are not synced yet are shown without ids
+pub(crate) struct DescribeService {
+    config: Config,
+    lock_file: LockFile,
+}
+
+impl DescribeService {
+    pub(crate) fn new(file_path: String) -> Result<Self> {
+        let config = ConfigFile::read(file_path.clone())?.config().clone();
+        let lock_file = LockFile::read(LockFile::file_name_for(&file_path))?;
+        Ok(Self {
+            config: resolve_from_lock(config, &lock_file, false)?,
+            lock_file,
+        })
+    }
+
+    pub(crate) fn user(&self, user_ref: &UserRef) -> Result<()> {
+        let config = &self.config;
+        match config.users.iter().find(|u| user_ref.matches_locked(u, &self.lock_file)) {
+            Some(u) => DescribeService::print(DescribeService::describe_user(config, u)),
+            None => DescribeService::not_found(format!("User {} is not found in the config", user_ref)),
+        }
+    }
+
+    pub(crate) fn describe_user(config: &Config, u: &User) -> Vec<String> {
+        let mut out: Vec<String> = vec![format!("id: {}", u.id)];
+        if !u.username.is_empty() {
+            out.push(format!("username: {}", u.username));
+        }
+        if !u.email.is_empty() {
+            out.push(format!("email: {}", u.email));
+        }
+        out.push(format!("name: {}", u.name));
+        out.push("teams:".to_string());
+        for (t, role) in config.user_teams(u) {
+            out.push(format!("  - {} ({})", t.name, role));
+        }
+        let grants = user_grants(config, u);
+        out.push("grants:".to_string());
+        for g in grants.iter() {
+            out.push(format!("  - {} {}: {}, {}", kind(&g.object), g.title, g.access, g.via));
+        }
+        out.push("effective access:".to_string());
+        for ((object, _), (title, access)) in effective(&grants) {
+            out.push(format!("  - {} {}: {}", kind(&object), title, access));
+        }
+        let rules = u.projects_in.len()
+            + config
+                .user_teams(u)
+                .iter()
+                .map(|(t, _)| t.projects_in.len())
+                .sum::<usize>();
+        if rules > 0 {
+            out.push(format!(
+                "projects_in rules: {}, they are expanded by `gum sync`",
+                rules
+            ));
+        }
+        out
+    }
+
+    pub(crate) fn team(&self, name: &str) -> Result<()> {
+        let config = &self.config;
+        let team = match config.team(name) {
+            Some(team) => team,
+            None => return DescribeService::not_found(format!("Team {} is not found in the config", name)),
+        };
+        let mut out: Vec<String> = vec![format!("name: {}", team.name)];
+        out.push(format!("includes: [{}]", team.includes.join(", ")));
+        let included_by: Vec<&str> = config
+            .teams
+            .iter()
+            .filter(|t| t.includes.contains(&team.name))
+            .map(|t| t.name.as_str())
+            .collect();
+        out.push(format!("included by: [{}]", included_by.join(", ")));
+        out.push("members:".to_string());
+        for u in config.users.iter() {
+            if let Some((_, role)) = config.user_teams(u).iter().find(|(t, _)| t.name == team.name) {
+                out.push(format!("  - user {}: {}", title(u.id, &u.name, &u.username), role));
+            }
+        }
+        out.push("projects:".to_string());
+        for p in team.projects.iter() {
+            out.push(format!("  - {}: {}", title(p.id, &p.name, &p.path), p.access_level));
+        }
+        out.push("groups:".to_string());
+        for n in team.namespaces.iter() {
+            out.push(format!("  - {}: {}", title(n.id, &n.name, &n.path), n.access_level));
+        }
+        if !team.projects_in.is_empty() {
+            out.push(format!(
+                "projects_in rules: {}, they are expanded by `gum sync`",
+                team.projects_in.len()
+            ));
+        }
+        DescribeService::print(out)
+    }
+
+    /// Describe the group as a subject that is shared with projects and groups, and as an object
+    pub(crate) fn group(&self, id: u64) -> Result<()> {
+        let config = &self.config;
+        let mut out: Vec<String> = Vec::new();
+        if let Some(g) = config.groups.iter().find(|g| g.id == id) {
+            out.push(format!("group: {}", title(g.id, &g.name, &g.path)));
+            out.push("shared with:".to_string());
+            for grant in group_grants(g) {
+                out.push(format!("  - {} {}: {}", kind(&grant.object), grant.title, grant.access));
+            }
+        }
+        let access = self.access_to(StateObject::Group(id));
+        if out.is_empty() && access.is_empty() {
+            return DescribeService::not_found(format!("Group {} is not found in the config", id));
+        }
+        out.push("access to the group:".to_string());
+        out.extend(access);
+        DescribeService::print(out)
+    }
+
+    pub(crate) fn project(&self, id: u64) -> Result<()> {
+        let access = self.access_to(StateObject::Project(id));
+        if access.is_empty() {
+            return DescribeService::not_found(format!("Nobody has access to the project {} in the config", id));
+        }
+        let config = &self.config;
+        let project_title = config
+            .users
+            .iter()
+            .flat_map(|u| user_grants(config, u))
+            .chain(config.groups.iter().flat_map(group_grants))
+            .filter(|g| g.object == StateObject::Project(id))
+            .map(|g| g.title)
+            .max_by_key(|t| t.len())
+            .unwrap_or_else(|| id.to_string());
+        let mut out: Vec<String> = vec![format!("project: {}", project_title)];
+        out.push("access to the project:".to_string());
+        out.extend(access);
+        DescribeService::print(out)
+    }
+
+    // Get everyone who has access to the object and through which team or group
+    fn access_to(&self, object: StateObject) -> Vec<String> {
+        let config = &self.config;
+        let mut out: Vec<String> = Vec::new();
+        for u in config.users.iter() {
+            let grants: Vec<Grant> = user_grants(config, u)
+                .into_iter()
+                .filter(|g| g.object == object)
+                .collect();
+            if let Some((_, access)) = effective(&grants).get(&(object, String::new())) {
+                let via: Vec<String> = grants.iter().map(|g| format!("{} {}", g.access, g.via)).collect();
+                out.push(format!(
+                    "  - user {}: {} ({})",
+                    title(u.id, &u.name, &u.username),
+                    access,
+                    via.join(", ")
+                ));
+            }
+        }
+        for g in config.groups.iter() {
+            if let Some((_, access)) = effective(&group_grants(g)).get(&(object, String::new())) {
+                out.push(format!("  - group {}: {}", title(g.id, &g.name, &g.path), access));
+            }
+        }
+        out
+    }
+
+    fn print(out: Vec<String>) -> Result<()> {
+        for line in out.iter() {
+            OutMessage::message_empty(line);
+        }
+        Ok(())
+    }
+
+    fn not_found(err_msg: String) -> Result<()> {
+        OutMessage::message_error(err_msg.as_str());
+        Err(Error::new(ErrorKind::NotFound, err_msg))
+    }
+}
+
+fn kind(object: &StateObject) -> &'static str {
+    match object {
+        StateObject::Project(_) => "project",
+        StateObject::Group(_) => "group",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use super::{DescribeService, StateObject};
+    use crate::types::v1::ConfigFile;
+
+    #[test]
+    fn describe_user() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: backend
+      projects:
+        - id: 10
+          access_level: Developer
+      members:
+        - user: "1"
+          role: lead
+  users:
+    - id: 1
+      name: alice
+      projects:
+        - id: 10
+          access_level: Reporter
+      namespaces:
+        - id: 5
+          access_level: Guest
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        let config_file = ConfigFile::read(file_path.to_string_lossy().to_string()).unwrap();
+        let config = config_file.config();
+
+        let out = DescribeService::describe_user(config, &config.users[0]);
+        assert!(out.contains(&"  - backend (lead)".to_string()));
+        assert!(out.contains(&"  - project 10: Reporter, direct".to_string()));
+        assert!(out.contains(&"  - project 10: Maintainer, team backend as a lead".to_string()));
+        let effective = out.iter().position(|l| l == "effective access:").unwrap();
+        assert_eq!(
+            out[effective + 1..].to_vec(),
+            vec!["  - project 10: Maintainer", "  - group 5: Guest"]
+        );
+    }
+
+    #[test]
+    fn describe_paths() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = r#"
+meta:
+  version: V2
+config:
+  users:
+    - id: 1
+      name: alice
+      projects:
+        - path: org/api
+          access_level: Developer
+        - path: org/web
+          access_level: Reporter
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        fs::write(dir.path().join("gum-config.lock"), "projects:\n  org/web: 11\n").unwrap();
+        let svc = DescribeService::new(file_path.to_string_lossy().to_string()).unwrap();
+
+        let out = DescribeService::describe_user(&svc.config, &svc.config.users[0]);
+        let effective = out.iter().position(|l| l == "effective access:").unwrap();
+        assert_eq!(
+            out[effective + 1..].to_vec(),
+            vec!["  - project ? (org/api): Developer", "  - project 11 (org/web): Reporter"]
+        );
+        assert_eq!(
+            svc.access_to(StateObject::Project(11)),
+            vec!["  - user 1 (alice): Reporter (Reporter direct)"]
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{
+    output::out_message::OutMessage,
+    types::v1::{AccessLevel, AccessUnit, Config, ConfigFile, LockFile},
+};
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind, Result},
+};
+
+use super::{
+    describe::{effective, state_subject},
+    export::kind,
+    sync::{expand_state, warn_teams},
+    StateObject,
+};
+
+// DiffService should be used to compare the access that two configs give, GitLab is not used.
+// Paths and usernames are resolved with lock files that are written by `sync`
+pub(crate) struct DiffService {
+    old: Config,
+    new: Config,
+}
+
+impl DiffService {
+    pub(crate) fn new(old_path: String, new_path: String) -> Result<Self> {
+        let old = ConfigFile::read(old_path.clone())?.config().clone();
+        let new = ConfigFile::read(new_path.clone())?.config().clone();
+        // The new config is usually a copy of the old one, so it may not have a lock file yet
+        let mut lock_file = LockFile::read(LockFile::file_name_for(&old_path))?;
+        let new_lock_file = LockFile::read(LockFile::file_name_for(&new_path))?;
+        lock_file.projects.extend(new_lock_file.projects);
+        lock_file.groups.extend(new_lock_file.groups);
+        lock_file.users.extend(new_lock_file.users);
+        Ok(Self {
+            old: resolve_from_lock(old, &lock_file, true)?,
+            new: resolve_from_lock(new, &lock_file, true)?,
+        })
+    }
+
+    /// Print changes of the access grouped by subjects, nothing is printed if configs are equivalent
+    pub(crate) fn diff(&self) -> Result<()> {
+        for line in self.changes()? {
+            OutMessage::message_empty(line.as_str());
+        }
+        Ok(())
+    }
+
+    fn changes(&self) -> Result<Vec<String>> {
+        warn_teams(&self.old);
+        warn_teams(&self.new);
+        if [&self.old, &self.new].iter().any(|c| c.has_rules()) {
+            OutMessage::message_warning(
+                "projects_in rules can't be expanded without GitLab, projects they give are not compared",
+            );
+        }
+        let old_state = expand_state(&self.old);
+        let new_state = expand_state(&self.new);
+
+        let empty = AccessUnit::default();
+        let mut subjects: BTreeMap<(String, u64), &AccessUnit> = BTreeMap::new();
+        for (id, unit) in old_state.iter().chain(new_state.iter()) {
+            subjects.insert((format!("{:?}", unit.entity), *id), unit);
+        }
+        let mut out: Vec<String> = Vec::new();
+        for ((_, id), unit) in subjects {
+            let (old_title, old_grants) = state_subject(&self.old, id, &unit.entity);
+            let (new_title, new_grants) = state_subject(&self.new, id, &unit.entity);
+            let mut titles = effective(&old_grants);
+            titles.extend(effective(&new_grants));
+
+            let (old_unit, new_unit) = (
+                old_state.get(&id).unwrap_or(&empty),
+                new_state.get(&id).unwrap_or(&empty),
+            );
+            let mut lines: Vec<String> = Vec::new();
+            for (object, old, new) in DiffService::objects(old_unit, new_unit) {
+                let title = match titles.get(&(object, String::new())) {
+                    Some((title, _)) => format!("{} {}", kind(&object), title),
+                    None => object.to_string(),
+                };
+                lines.push(match (old, new) {
+                    (None, Some(new)) => format!("  + {}: {}", title, new),
+                    (Some(old), None) => format!("  - {}: {}", title, old),
+                    (Some(old), Some(new)) => format!("  ~ {}: {} -> {}", title, old, new),
+                    (None, None) => continue,
+                });
+            }
+            if !lines.is_empty() {
+                let title = if new_state.contains_key(&id) { new_title } else { old_title };
+                out.push(format!("{}:", title));
+                out.extend(lines);
+            }
+        }
+        Ok(out)
+    }
+
+    // Objects which access is different in two units of the same subject
+    fn objects(
+        old: &AccessUnit,
+        new: &AccessUnit,
+    ) -> Vec<(StateObject, Option<AccessLevel>, Option<AccessLevel>)> {
+        let mut objects: BTreeMap<StateObject, (Option<AccessLevel>, Option<AccessLevel>)> = BTreeMap::new();
+        for (access, object, is_new) in [
+            (&old.projects, StateObject::Project as fn(u64) -> StateObject, false),
+            (&new.projects, StateObject::Project, true),
+            (&old.namespaces, StateObject::Group, false),
+            (&new.namespaces, StateObject::Group, true),
+        ] {
+            for (id, level) in access.iter() {
+                let entry = objects.entry(object(*id)).or_default();
+                match is_new {
+                    false => entry.0 = Some(*level),
+                    true => entry.1 = Some(*level),
+                }
+            }
+        }
+        objects
+            .into_iter()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(object, (old, new))| (object, old, new))
+            .collect()
+    }
+}
+
+// Set ids for paths and users the same way as `sync` does, but only with the lock file.
+// If it's not required, entries that are not in the lock file keep their ids
+pub(super) fn resolve_from_lock(mut config: Config, lock_file: &LockFile, required: bool) -> Result<Config> {
+    let project = |path: &str, id: u64| resolve_id("project", &lock_file.projects, path, id, required);
+    let group = |path: &str, id: u64| resolve_id("group", &lock_file.groups, path, id, required);
+    for t in config.teams.iter_mut() {
+        for p in t.projects.iter_mut() {
+            p.id = project(&p.path, p.id)?;
+        }
+        for n in t.namespaces.iter_mut() {
+            n.id = group(&n.path, n.id)?;
+        }
+    }
+    for u in config.users.iter_mut() {
+        if let Some(identity) = u.identity() {
+            u.id = resolve_id("user", &lock_file.users, &identity.to_string(), u.id, required)?;
+        }
+        for p in u.projects.iter_mut() {
+            p.id = project(&p.path, p.id)?;
+        }
+        for n in u.namespaces.iter_mut() {
+            n.id = group(&n.path, n.id)?;
+        }
+    }
+    for i in config.invites.iter_mut() {
+        for p in i.projects.iter_mut() {
+            p.id = project(&p.path, p.id)?;
+        }
+        for n in i.namespaces.iter_mut() {
+            n.id = group(&n.path, n.id)?;
+        }
+    }
+    for g in config.groups.iter_mut() {
+        g.id = group(&g.path, g.id)?;
+        for p in g.projects.iter_mut() {
+            p.id = project(&p.path, p.id)?;
+        }
+        for n in g.namespaces.iter_mut() {
+            n.id = group(&n.path, n.id)?;
+        }
+    }
+    Ok(config)
+}
+
+fn resolve_id(kind: &str, resolved: &BTreeMap<String, u64>, name: &str, id: u64, required: bool) -> Result<u64> {
+    match (name.is_empty(), resolved.get(name)) {
+        (true, _) => Ok(id),
+        (false, Some(resolved)) => Ok(*resolved),
+        (false, None) if id != 0 || !required => Ok(id),
+        (false, None) => {
+            let err_msg = format!(
+                "The {} {} is not in the lock file, set its id in the config or run `gum sync` first",
+                kind, name
+            );
+            OutMessage::message_error(err_msg.as_str());
+            Err(Error::new(ErrorKind::NotFound, err_msg))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use super::DiffService;
+
+    #[test]
+    fn diff_configs() {
+        let dir = tempdir().unwrap();
+        let config = |teams: &str, bob: &str| {
+            format!(
+                r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: core
+{}      projects:
+        - id: 10
+          name: api
+          access_level: Developer
+  users:
+    - id: 1
+      name: alice
+      teams: [core]
+    - id: 2
+      name: bob
+{}
+state: ''
+"#,
+                teams, bob
+            )
+        };
+        let old_path = dir.path().join("old.yaml");
+        let new_path = dir.path().join("new.yaml");
+        let moved_path = dir.path().join("moved.yaml");
+        fs::write(
+            &old_path,
+            config(
+                "",
+                "      projects:\n        - id: 10\n          access_level: Developer\n        - path: org/web\n          access_level: Reporter",
+            ),
+        )
+        .unwrap();
+        fs::write(
+            &new_path,
+            config(
+                "      members:\n        - user: bob\n          role: lead\n",
+                "      username: bob",
+            ),
+        )
+        .unwrap();
+        fs::write(
+            &moved_path,
+            config(
+                "      members:\n        - user: \"2\"\n",
+                "      projects:\n        - path: org/web\n          access_level: Reporter",
+            ),
+        )
+        .unwrap();
+        fs::write(dir.path().join("old.lock"), "projects:\n  org/web: 11\n").unwrap();
+        let path = |p: &std::path::PathBuf| p.to_string_lossy().to_string();
+
+        let changes = DiffService::new(path(&old_path), path(&new_path))
+            .unwrap()
+            .changes()
+            .unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                "user 2 (bob):",
+                "  ~ project 10 (api): Developer -> Maintainer",
+                "  - project 11 (org/web): Reporter",
+            ]
+        );
+        let changes = DiffService::new(path(&old_path), path(&moved_path))
+            .unwrap()
+            .changes()
+            .unwrap();
+        assert!(changes.is_empty());
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{
+    output::out_message::OutMessage,
+    types::v1::{AccessLevel, AccessUnit, Config, EntityType},
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{Error, ErrorKind, Result},
+};
+
+use super::{
+    describe::{state_subject, Grant},
+    StateObject,
+};
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct Cell {
+    access: AccessLevel,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sources: Vec<String>,
+}
+
+impl Cell {
+    fn text(&self) -> String {
+        match self.sources.is_empty() {
+            true => self.access.to_string(),
+            false => format!("{} ({})", self.access, self.sources.join(", ")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Row<'a> {
+    subject: &'a str,
+    access: BTreeMap<&'a str, &'a Cell>,
+}
+
+// Matrix is the effective access of every subject of the planned state to every object
+pub(crate) struct Matrix {
+    columns: Vec<(StateObject, String)>,
+    rows: Vec<(String, HashMap<StateObject, Cell>)>,
+}
+
+impl Matrix {
+    /// Build the matrix from the planned state, grants of the config are only used for titles and sources
+    pub(crate) fn new(config: &Config, state: &HashMap<u64, AccessUnit>, sources: bool) -> Self {
+        let mut subjects: Vec<(&u64, &AccessUnit)> = state.iter().collect();
+        subjects.sort_by_key(|(id, unit)| (rank(&unit.entity), **id));
+
+        let mut titles: BTreeMap<StateObject, String> = BTreeMap::new();
+        let mut rows: Vec<(String, HashMap<StateObject, Cell>)> = Vec::new();
+        for (id, unit) in subjects {
+            let (title, grants) = state_subject(config, *id, &unit.entity);
+            for g in grants.iter() {
+                let t = titles.entry(g.object).or_default();
+                if g.title.len() > t.len() {
+                    *t = g.title.clone();
+                }
+            }
+            let objects = unit
+                .projects
+                .iter()
+                .map(|(id, access)| (StateObject::Project(*id), *access))
+                .chain(
+                    unit.namespaces
+                        .iter()
+                        .map(|(id, access)| (StateObject::Group(*id), *access)),
+                );
+            let mut cells: HashMap<StateObject, Cell> = HashMap::new();
+            for (object, access) in objects {
+                titles.entry(object).or_default();
+                let sources = match sources {
+                    true => access_sources(&grants, object, access),
+                    false => Vec::new(),
+                };
+                cells.insert(object, Cell { access, sources });
+            }
+            rows.push((title, cells));
+        }
+
+        let columns = titles
+            .into_iter()
+            .map(|(object, title)| {
+                let title = match title.is_empty() {
+                    true => object.to_string(),
+                    false => format!("{} {}", kind(&object), title),
+                };
+                (object, title)
+            })
+            .collect();
+        Self { columns, rows }
+    }
+
+    pub(crate) fn render(&self, format: &str) -> Result<String> {
+        match format {
+            "csv" => Ok(self.csv()),
+            "markdown" => Ok(self.markdown()),
+            "json" => self.json(),
+            _ => {
+                let err_msg = format!("Format {} is not supported", format);
+                OutMessage::message_error(err_msg.as_str());
+                Err(Error::new(ErrorKind::InvalidInput, err_msg))
+            }
+        }
+    }
+
+    fn csv(&self) -> String {
+        let mut header = vec!["subject".to_string()];
+        header.extend(self.columns.iter().map(|(_, title)| title.clone()));
+        let mut lines = vec![csv_line(&header)];
+        for (subject, cells) in self.rows.iter() {
+            let mut line = vec![subject.clone()];
+            line.extend(self.cells(cells));
+            lines.push(csv_line(&line));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    fn markdown(&self) -> String {
+        let mut header = vec!["subject".to_string()];
+        header.extend(self.columns.iter().map(|(_, title)| title.clone()));
+        let mut lines = vec![
+            markdown_line(&header),
+            format!("|{}", "---|".repeat(header.len())),
+        ];
+        for (subject, cells) in self.rows.iter() {
+            let mut line = vec![subject.clone()];
+            line.extend(self.cells(cells));
+            lines.push(markdown_line(&line));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    fn json(&self) -> Result<String> {
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .map(|(subject, cells)| Row {
+                subject,
+                access: self
+                    .columns
+                    .iter()
+                    .filter_map(|(object, title)| cells.get(object).map(|c| (title.as_str(), c)))
+                    .collect(),
+            })
+            .collect();
+        Ok(serde_json::to_string_pretty(&rows)? + "\n")
+    }
+
+    fn cells(&self, cells: &HashMap<StateObject, Cell>) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|(object, _)| cells.get(object).map(Cell::text).unwrap_or_default())
+            .collect()
+    }
+}
+
+/// Write the output to the file, or print it if the path is not set
+pub(crate) fn write_output(output: &Option<String>, text: &str) -> Result<()> {
+    match output {
+        Some(path) => {
+            fs::write(path, text)?;
+            OutMessage::message_info_clean(format!("Output is written to {}", path).as_str());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Get grants that give the effective access to the object
+pub(super) fn access_sources(grants: &[Grant], object: StateObject, access: AccessLevel) -> Vec<String> {
+    grants
+        .iter()
+        .filter(|g| g.object == object && g.access == access)
+        .map(|g| g.via.clone())
+        .collect()
+}
+
+// Users go first, then groups and invites
+pub(super) fn rank(entity: &EntityType) -> u8 {
+    match entity {
+        EntityType::User => 0,
+        EntityType::Group => 1,
+        EntityType::Invite => 2,
+    }
+}
+
+pub(super) fn kind(object: &StateObject) -> &'static str {
+    match object {
+        StateObject::Project(_) => "project",
+        StateObject::Group(_) => "group",
+    }
+}
+
+fn csv_line(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|f| match f.contains([',', '"', '\n']) {
+            true => format!("\"{}\"", f.replace('"', "\"\"")),
+            false => f.clone(),
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn markdown_line(fields: &[String]) -> String {
+    let fields: Vec<String> = fields.iter().map(|f| f.replace('|', "\\|")).collect();
+    format!("| {} |", fields.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs};
+    use tempfile::tempdir;
+
+    use super::Matrix;
+    use crate::types::v1::{AccessLevel, AccessUnit, ConfigFile, EntityType};
+
+    #[test]
+    fn render_matrix() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: backend
+      projects:
+        - id: 10
+          name: api
+          access_level: Developer
+  users:
+    - id: 1
+      name: alice
+      teams: [backend]
+      namespaces:
+        - id: 5
+          name: infra
+          access_level: Reporter
+    - id: 2
+      name: bob, jr
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        let config_file = ConfigFile::read(file_path.to_string_lossy().to_string()).unwrap();
+        let state = HashMap::from([
+            (
+                1,
+                AccessUnit {
+                    entity: EntityType::User,
+                    projects: HashMap::from([(10, AccessLevel::Developer)]),
+                    namespaces: HashMap::from([(5, AccessLevel::Reporter)]),
+                    ..Default::default()
+                },
+            ),
+            (2, AccessUnit::new_simple(EntityType::User)),
+        ]);
+
+        let matrix = Matrix::new(config_file.config(), &state, true);
+        assert_eq!(
+            matrix.render("csv").unwrap(),
+            "subject,project 10 (api),group 5 (infra)\n\
+            user 1 (alice),Developer (team backend),Reporter (direct)\n\
+            \"user 2 (bob, jr)\",,\n"
+        );
+        assert_eq!(
+            Matrix::new(config_file.config(), &state, false).render("markdown").unwrap(),
+            "| subject | project 10 (api) | group 5 (infra) |\n\
+            |---|---|---|\n\
+            | user 1 (alice) | Developer | Reporter |\n\
+            | user 2 (bob, jr) |  |  |\n"
+        );
+    }
+}
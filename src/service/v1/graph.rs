@@ -0,0 +1,359 @@
+use crate::{
+    output::out_message::OutMessage,
+    types::v1::{Config, LockFile, Namespace, Project, ProjectsIn, User},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Error, ErrorKind, Result},
+};
+
+use super::{describe::title, diff::resolve_from_lock};
+
+// Graph shows how subjects get access: users -> teams -> projects and groups, and groups shared into groups.
+// Nodes are keyed like `user:1`, `team:backend` or `project:10`, so they can be focused on
+#[derive(Debug, Default)]
+pub(crate) struct Graph {
+    nodes: BTreeMap<String, String>,
+    edges: Vec<(String, String, String)>,
+}
+
+impl Graph {
+    /// Build the graph of the config, paths and users are resolved with the lock file,
+    /// so an object that is referenced by its id and by its path is one node
+    pub(crate) fn new(config: &Config, lock_file: &LockFile) -> Result<Self> {
+        let config = resolve_from_lock(config.clone(), lock_file, false)?;
+        let mut graph = Graph::default();
+        for t in config.teams.iter() {
+            let team = format!("team:{}", t.name);
+            graph.node(&team, format!("team {}", t.name));
+        }
+        for t in config.teams.iter() {
+            let team = format!("team:{}", t.name);
+            for i in t.includes.iter() {
+                graph.edge(&team, &format!("team:{}", i), "includes")?;
+            }
+            graph.objects(&team, &t.projects, &t.namespaces)?;
+            graph.rules(&team, &t.projects_in, lock_file)?;
+        }
+        for u in config.users.iter() {
+            let user = user_key(u);
+            graph.node(&user, format!("user {}", title(u.id, &u.name, &u.username)));
+            for t in u.teams.iter() {
+                graph.edge(&user, &format!("team:{}", t), "member")?;
+            }
+            for t in config.teams.iter() {
+                for m in t.members.iter().filter(|m| m.is(u)) {
+                    graph.edge(&user, &format!("team:{}", t.name), &m.role.to_string())?;
+                }
+            }
+            graph.objects(&user, &u.projects, &u.namespaces)?;
+            graph.rules(&user, &u.projects_in, lock_file)?;
+        }
+        for i in config.invites.iter() {
+            let invite = format!("invite:{}", i.email);
+            graph.node(&invite, format!("invite of {}", i.email));
+            for t in i.teams.iter() {
+                graph.edge(&invite, &format!("team:{}", t), "member")?;
+            }
+            graph.objects(&invite, &i.projects, &i.namespaces)?;
+        }
+        for g in config.groups.iter() {
+            let group = object_key("group", g.id, &g.path);
+            graph.node(&group, format!("group {}", title(g.id, &g.name, &g.path)));
+            graph.objects(&group, &g.projects, &g.namespaces)?;
+            graph.rules(&group, &g.projects_in, lock_file)?;
+        }
+        Ok(graph)
+    }
+
+    /// Get the part of the graph that is connected to the node: everything it grants access to
+    /// and everything that grants access to it
+    pub(crate) fn focus(&self, key: &str) -> Result<Graph> {
+        if !self.nodes.contains_key(key) {
+            let err_msg = format!("{} is not found in the config, use `user:<id>` or `project:<id>`", key);
+            OutMessage::message_error(err_msg.as_str());
+            return Err(Error::new(ErrorKind::NotFound, err_msg));
+        }
+        let mut keep = self.reachable(key, |(from, to, _)| (from, to));
+        keep.extend(self.reachable(key, |(from, to, _)| (to, from)));
+        Ok(Graph {
+            nodes: self
+                .nodes
+                .iter()
+                .filter(|(k, _)| keep.contains(k.as_str()))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter(|(from, to, _)| keep.contains(from.as_str()) && keep.contains(to.as_str()))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    pub(crate) fn render(&self, format: &str) -> Result<String> {
+        let ids: BTreeMap<&str, String> = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(i, k)| (k.as_str(), format!("n{}", i)))
+            .collect();
+        let mut out: Vec<String> = Vec::new();
+        match format {
+            "dot" => {
+                out.push("digraph gum {".to_string());
+                out.push("  rankdir=LR;".to_string());
+                for (key, label) in self.nodes.iter() {
+                    out.push(format!(
+                        "  {} [label=\"{}\", shape={}];",
+                        ids[key.as_str()],
+                        label.replace('"', "\\\""),
+                        shape(key)
+                    ));
+                }
+                for (from, to, label) in self.edges.iter() {
+                    out.push(format!(
+                        "  {} -> {} [label=\"{}\"];",
+                        ids[from.as_str()],
+                        ids[to.as_str()],
+                        label
+                    ));
+                }
+                out.push("}".to_string());
+            }
+            "mermaid" => {
+                out.push("flowchart LR".to_string());
+                for (key, label) in self.nodes.iter() {
+                    out.push(format!(
+                        "  {}[\"{}\"]",
+                        ids[key.as_str()],
+                        label.replace('"', "#quot;")
+                    ));
+                }
+                for (from, to, label) in self.edges.iter() {
+                    out.push(format!(
+                        "  {} -->|{}| {}",
+                        ids[from.as_str()],
+                        label,
+                        ids[to.as_str()]
+                    ));
+                }
+            }
+            _ => {
+                let err_msg = format!("Format {} is not supported", format);
+                OutMessage::message_error(err_msg.as_str());
+                return Err(Error::new(ErrorKind::InvalidInput, err_msg));
+            }
+        }
+        Ok(out.join("\n") + "\n")
+    }
+
+    fn reachable<'a>(
+        &'a self,
+        key: &'a str,
+        direction: fn(&(String, String, String)) -> (&String, &String),
+    ) -> BTreeSet<&'a str> {
+        let mut visited: BTreeSet<&str> = BTreeSet::new();
+        let mut queue: Vec<&str> = vec![key];
+        while let Some(key) = queue.pop() {
+            if !visited.insert(key) {
+                continue;
+            }
+            for edge in self.edges.iter() {
+                let (from, to) = direction(edge);
+                if from == key {
+                    queue.push(to);
+                }
+            }
+        }
+        visited
+    }
+
+    fn objects(&mut self, from: &str, projects: &[Project], namespaces: &[Namespace]) -> Result<()> {
+        for p in projects.iter() {
+            let key = object_key("project", p.id, &p.path);
+            self.node(&key, format!("project {}", title(p.id, &p.name, &p.path)));
+            self.edge(from, &key, &p.access_level.to_string())?;
+        }
+        for n in namespaces.iter() {
+            let key = object_key("group", n.id, &n.path);
+            self.node(&key, format!("group {}", title(n.id, &n.name, &n.path)));
+            self.edge(from, &key, &n.access_level.to_string())?;
+        }
+        Ok(())
+    }
+
+    // Rules can't be expanded without GitLab, so they point to their groups with edges of their own
+    fn rules(&mut self, from: &str, rules: &[ProjectsIn], lock_file: &LockFile) -> Result<()> {
+        for r in rules.iter() {
+            let id = match (r.group, lock_file.groups.get(&r.path)) {
+                (0, Some(id)) => *id,
+                (id, _) => id,
+            };
+            let key = object_key("group", id, &r.path);
+            self.node(&key, format!("group {}", title(id, "", &r.path)));
+            let scope = if r.recursive { "projects in (recursive)" } else { "projects in" };
+            self.edge(from, &key, &format!("{}: {}", scope, r.access))?;
+        }
+        Ok(())
+    }
+
+    // Objects are mentioned many times, the longest label is the most descriptive one
+    fn node(&mut self, key: &str, label: String) {
+        let current = self.nodes.entry(key.to_string()).or_default();
+        if label.len() > current.len() {
+            *current = label;
+        }
+    }
+
+    // Nodes of teams are added before any edges, so a team that isn't a node doesn't exist
+    fn edge(&mut self, from: &str, to: &str, label: &str) -> Result<()> {
+        if let Some(team) = to.strip_prefix("team:").filter(|_| !self.nodes.contains_key(to)) {
+            let err_msg = format!("Team {} is not found in the config, it's referenced by {}", team, from);
+            OutMessage::message_error(err_msg.as_str());
+            return Err(Error::new(ErrorKind::NotFound, err_msg));
+        }
+        self.edges.push((from.to_string(), to.to_string(), label.to_string()));
+        Ok(())
+    }
+}
+
+fn user_key(u: &User) -> String {
+    match (u.id, u.username.is_empty()) {
+        (0, false) => format!("user:{}", u.username),
+        (0, true) => format!("user:{}", u.email),
+        (id, _) => format!("user:{}", id),
+    }
+}
+
+// Entries that are referenced by path only don't have ids until they're synced
+fn object_key(kind: &str, id: u64, path: &str) -> String {
+    match id {
+        0 => format!("{}:{}", kind, path),
+        id => format!("{}:{}", kind, id),
+    }
+}
+
+fn shape(key: &str) -> &'static str {
+    match key.split_once(':').map(|(kind, _)| kind) {
+        Some("team") => "box",
+        Some("project") => "note",
+        Some("group") => "folder",
+        _ => "ellipse",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use super::Graph;
+    use crate::types::v1::{ConfigFile, LockFile};
+
+    #[test]
+    fn focus_and_render() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: backend
+      includes: [core]
+      members:
+        - user: "2"
+          role: lead
+    - name: core
+      projects:
+        - id: 10
+          name: api
+          access_level: Developer
+    - name: frontend
+      projects:
+        - id: 11
+          access_level: Reporter
+  users:
+    - id: 1
+      name: alice
+      teams: [frontend]
+    - id: 2
+      name: bob
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        let config_file = ConfigFile::read(file_path.to_string_lossy().to_string()).unwrap();
+        let graph = Graph::new(config_file.config(), &LockFile::default()).unwrap();
+
+        assert_eq!(
+            graph.focus("project:10").unwrap().render("mermaid").unwrap(),
+            "flowchart LR\n\
+            \x20 n0[\"project 10 (api)\"]\n\
+            \x20 n1[\"team backend\"]\n\
+            \x20 n2[\"team core\"]\n\
+            \x20 n3[\"user 2 (bob)\"]\n\
+            \x20 n1 -->|includes| n2\n\
+            \x20 n2 -->|Developer| n0\n\
+            \x20 n3 -->|lead| n1\n"
+        );
+        let dot = graph.focus("user:1").unwrap().render("dot").unwrap();
+        assert!(dot.contains("n0 [label=\"project 11\", shape=note];"));
+        assert!(!dot.contains("backend"));
+        assert!(graph.focus("user:3").is_err());
+    }
+
+    #[test]
+    fn resolved_with_lock_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: backend
+      projects:
+        - path: group/api
+          access_level: Developer
+      projects_in:
+        - path: group/libs
+          recursive: true
+          access: Reporter
+  users:
+    - username: jdoe
+      name: John
+      teams: [backend]
+      projects:
+        - id: 10
+          access_level: Maintainer
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        let config_file = ConfigFile::read(file_path.to_string_lossy().to_string()).unwrap();
+        let mut lock_file = LockFile::default();
+        lock_file.projects.insert("group/api".to_string(), 10);
+        lock_file.groups.insert("group/libs".to_string(), 7);
+        lock_file.users.insert("jdoe".to_string(), 2);
+        let graph = Graph::new(config_file.config(), &lock_file).unwrap();
+
+        assert_eq!(
+            graph.render("mermaid").unwrap(),
+            "flowchart LR\n\
+            \x20 n0[\"group 7 (group/libs)\"]\n\
+            \x20 n1[\"project 10 (group/api)\"]\n\
+            \x20 n2[\"team backend\"]\n\
+            \x20 n3[\"user 2 (John)\"]\n\
+            \x20 n2 -->|Developer| n1\n\
+            \x20 n2 -->|projects in (recursive): Reporter| n0\n\
+            \x20 n3 -->|member| n2\n\
+            \x20 n3 -->|Maintainer| n1\n"
+        );
+
+        fs::write(&file_path, data.replace("teams: [backend]", "teams: [backend, qa]")).unwrap();
+        let config_file = ConfigFile::read(file_path.to_string_lossy().to_string()).unwrap();
+        let err = Graph::new(config_file.config(), &lock_file).unwrap_err();
+        assert_eq!(err.to_string(), "Team qa is not found in the config, it's referenced by user:2");
+    }
+}
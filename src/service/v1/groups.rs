@@ -107,6 +107,7 @@ impl GroupsService {
                 let p = Project {
                     access_level,
                     id: project.id,
+                    path: Default::default(),
                     name: project.name,
                 };
                 if g.projects.iter().any(|i| i.id == p.id) {
@@ -147,6 +148,7 @@ impl GroupsService {
                     name: namespace.name.to_string(),
                     access_level,
                     id: namespace.id,
+                    path: Default::default(),
                     url: namespace.web_url.to_string(),
                 };
                 if g.namespaces.iter().any(|i| i.id == o.id) {
@@ -0,0 +1,265 @@
+use crate::{
+    gitlab::{apis::users::GitlabUsersApi, types::users::User, GitlabApiInterface},
+    output::out_message::OutMessage,
+    types::v1::{Config, LockFile, UserRef},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Error, ErrorKind, Result},
+};
+
+// UsersResolver should be used to get ids of users that are referenced by username or email
+pub(crate) struct UsersResolver<'a, T: GitlabApiInterface> {
+    gitlab_api: &'a T,
+    lock_file: LockFile,
+    resolved: BTreeMap<String, u64>,
+}
+
+impl<'a, T: GitlabApiInterface> UsersResolver<'a, T> {
+    pub(crate) fn new(gitlab_api: &'a T, lock_file: LockFile) -> Self {
+        Self {
+            gitlab_api,
+            lock_file,
+            resolved: BTreeMap::new(),
+        }
+    }
+
+    /// Find exactly one GitLab user
+    pub(crate) fn find(&self, user: &UserRef) -> Result<User> {
+        let users_api = self.gitlab_api.users();
+        let found = match user {
+            UserRef::Id(id) => return users_api.get_data_by_id(*id),
+            UserRef::Username(username) => users_api.find_by_username(username)?,
+            UserRef::Email(email) => users_api.find_by_email(email)?,
+        };
+
+        match found.len() {
+            0 => {
+                let mut err_msg = format!("User {} can't be found in GitLab", user);
+                if let UserRef::Email(_) = user {
+                    err_msg.push_str(", private emails can be only found with an admin token");
+                }
+                Err(Error::new(ErrorKind::NotFound, err_msg))
+            }
+            1 => Ok(found.into_iter().next().unwrap()),
+            _ => {
+                let candidates: Vec<String> = found
+                    .iter()
+                    .map(|u| format!("{} ({})", u.username, u.id))
+                    .collect();
+                let err_msg = format!(
+                    "User {} is ambiguous, it matches {}, please use an id instead",
+                    user,
+                    candidates.join(", ")
+                );
+                OutMessage::message_error(err_msg.as_str());
+                Err(Error::new(ErrorKind::InvalidInput, err_msg))
+            }
+        }
+    }
+
+    /// Set ids for every user in the config that has a username or an email.
+    /// Returns ids of users that are used in the config to put them to the lock file
+    pub(crate) fn resolve(&mut self, config: &mut Config) -> Result<BTreeMap<String, u64>> {
+        let mut ids: HashMap<u64, String> = HashMap::new();
+        for u in config.users.iter_mut() {
+            let name = match u.identity() {
+                Some(identity) => {
+                    u.id = self.resolve_user(&identity, u.id)?;
+                    identity.to_string()
+                }
+                None => u.id.to_string(),
+            };
+            if let Some(other) = ids.insert(u.id, name.clone()) {
+                let err_msg = format!(
+                    "Users {} and {} are the same GitLab user {}, please keep only one of them",
+                    other, name, u.id
+                );
+                OutMessage::message_error(err_msg.as_str());
+                return Err(Error::new(ErrorKind::InvalidData, err_msg));
+            }
+        }
+        Ok(self.resolved.clone())
+    }
+
+    fn resolve_user(&mut self, user_ref: &UserRef, id: u64) -> Result<u64> {
+        let identity = user_ref.to_string();
+        let identity = identity.as_str();
+        if let Some(resolved) = self.resolved.get(identity) {
+            return UsersResolver::<T>::check_id(identity, id, *resolved);
+        }
+
+        // The id that was resolved last time, it's used when the username doesn't exist anymore
+        let cached = match self.lock_file.users.get(identity) {
+            Some(cached) => Some(*cached),
+            None if id != 0 => Some(id),
+            None => None,
+        };
+        let resolved = match self.find(user_ref) {
+            Ok(user) => {
+                // Access must not silently move to another person, so it should be confirmed in the config
+                if let Some(cached) = self.lock_file.users.get(identity) {
+                    if id == 0 && *cached != user.id {
+                        let err_msg = format!(
+                            "{} belonged to the user {} before, but now it's the user {}. If it's expected, set `id: {}` for this user in the config",
+                            identity, cached, user.id, user.id
+                        );
+                        OutMessage::message_error(err_msg.as_str());
+                        return Err(Error::new(ErrorKind::InvalidData, err_msg));
+                    }
+                }
+                user.id
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => match cached {
+                Some(cached) => {
+                    let user = self.gitlab_api.users().get_data_by_id(cached)?;
+                    OutMessage::message_warning(
+                        format!(
+                            "User {} ({}) was renamed to {}, please update the config",
+                            identity, cached, user.username
+                        )
+                        .as_str(),
+                    );
+                    cached
+                }
+                None => {
+                    OutMessage::message_error(err.to_string().as_str());
+                    return Err(err);
+                }
+            },
+            Err(err) => return Err(err),
+        };
+        self.resolved.insert(identity.to_string(), resolved);
+        UsersResolver::<T>::check_id(identity, id, resolved)
+    }
+
+    // When both id and username are set in the config, they must point to the same user
+    fn check_id(identity: &str, id: u64, resolved: u64) -> Result<u64> {
+        if id != 0 && id != resolved {
+            let err_msg = format!(
+                "The user {} has id {}, but {} is set in the config",
+                identity, resolved, id
+            );
+            OutMessage::message_error(err_msg.as_str());
+            return Err(Error::new(ErrorKind::InvalidData, err_msg));
+        }
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Error, ErrorKind};
+
+    use super::UsersResolver;
+    use crate::{
+        gitlab::{apis::users::MockGitlabUsersApi, types::users::User, GitlabApiMock},
+        types::v1::{Config, LockFile, UserRef},
+    };
+
+    fn user(id: u64, username: &str) -> User {
+        User {
+            id,
+            username: username.to_string(),
+            name: username.to_string(),
+            web_url: String::new(),
+            email: None,
+            public_email: Some(format!("{}@example.com", username)),
+            two_factor_enabled: None,
+        }
+    }
+
+    // alice is 1, bob was renamed to robert, there are two users with the email of the team
+    fn gitlab_api() -> GitlabApiMock {
+        GitlabApiMock {
+            users: Box::new(|| {
+                let mut users_api = MockGitlabUsersApi::new();
+                users_api.expect_find_by_username().returning(|username| match username {
+                    "alice" => Ok(vec![user(1, "alice")]),
+                    _ => Ok(Vec::new()),
+                });
+                users_api.expect_find_by_email().returning(|email| match email {
+                    "alice@example.com" => Ok(vec![user(1, "alice")]),
+                    "team@example.com" => Ok(vec![user(1, "alice"), user(3, "carol")]),
+                    _ => Ok(Vec::new()),
+                });
+                users_api.expect_get_data_by_id().returning(|id| match id {
+                    2 => Ok(user(2, "robert")),
+                    _ => Err(Error::new(ErrorKind::NotFound, "user can't be found")),
+                });
+                users_api
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn users_config(users: &str) -> Config {
+        serde_yaml::from_str(format!("users:\n{}", users).as_str()).unwrap()
+    }
+
+    #[test]
+    fn find_users() {
+        let gitlab_api = gitlab_api();
+        let resolver = UsersResolver::new(&gitlab_api, LockFile::default());
+        assert_eq!(resolver.find(&UserRef::Username("alice".to_string())).unwrap().id, 1);
+        assert_eq!(resolver.find(&UserRef::Id(2)).unwrap().username, "robert");
+        let err = resolver.find(&UserRef::Email("team@example.com".to_string())).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = resolver.find(&UserRef::Username("nobody".to_string())).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn resolve_users() {
+        let gitlab_api = gitlab_api();
+        let mut lock_file = LockFile::default();
+        lock_file.users.insert("bob".to_string(), 2);
+        let mut config = users_config(
+            r#"
+  - username: alice
+    name: Alice
+  - username: bob
+    name: Bob
+  - id: 4
+    name: Dave
+"#,
+        );
+        let resolved = UsersResolver::new(&gitlab_api, lock_file).resolve(&mut config).unwrap();
+        let ids: Vec<u64> = config.users.iter().map(|u| u.id).collect();
+        assert_eq!(ids, vec![1, 2, 4]);
+        assert_eq!(resolved.len(), 2);
+        assert_eq!(resolved.get("bob"), Some(&2));
+
+        // The same person can't be in the config twice
+        let mut config = users_config("  - username: alice\n    name: Alice\n  - email: alice@example.com\n    name: Alice\n");
+        let err = UsersResolver::new(&gitlab_api, LockFile::default())
+            .resolve(&mut config)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // A username that belongs to another person now must be confirmed with an id
+        let mut lock_file = LockFile::default();
+        lock_file.users.insert("alice".to_string(), 5);
+        let mut config = users_config("  - username: alice\n    name: Alice\n");
+        let err = UsersResolver::new(&gitlab_api, lock_file)
+            .resolve(&mut config)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn match_users_with_lock_file() {
+        let mut lock_file = LockFile::default();
+        lock_file.users.insert("alice".to_string(), 1);
+        lock_file.users.insert("bob@example.com".to_string(), 2);
+        let config = users_config("  - id: 1\n    name: Alice\n  - email: bob@example.com\n    name: Bob\n");
+        let (alice, bob) = (&config.users[0], &config.users[1]);
+
+        assert!(UserRef::Username("Alice".to_string()).matches_locked(alice, &lock_file));
+        assert!(!UserRef::Username("alice".to_string()).matches(alice));
+        assert!(UserRef::Id(2).matches_locked(bob, &lock_file));
+        assert!(UserRef::Email("bob@example.com".to_string()).matches_locked(bob, &lock_file));
+        assert!(!UserRef::Username("bob".to_string()).matches_locked(bob, &lock_file));
+        assert!(!UserRef::Id(2).matches_locked(alice, &lock_file));
+    }
+}
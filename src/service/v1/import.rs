@@ -0,0 +1,307 @@
+use crate::{
+    gitlab::{
+        apis::{groups::GitlabGroupsApi, projects::GitlabProjectsApi},
+        GitlabApiInterface,
+    },
+    output::out_message::OutMessage,
+    types::v1::{AccessLevel, LockFile, UserRef},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{Error, ErrorKind, Result},
+    str::FromStr,
+};
+
+use super::{users::UsersService, UsersResolver};
+
+// Fields that can be imported, a column with the same name is used if it's not mapped
+pub(crate) const FIELDS: [&str; 5] = ["user", "team", "project", "group", "level"];
+
+// Row is a line of the imported file, a user and what should be given to them
+#[derive(Debug, PartialEq)]
+pub(crate) struct Row {
+    line: usize,
+    user: UserRef,
+    team: String,
+    project: String,
+    group: String,
+    level: Option<AccessLevel>,
+}
+
+// ImportService should be used to merge users and their access from other sources into the config.
+// Rows are added the same way as by `users create`, `users add-team` and `users add-project`
+pub(crate) struct ImportService {
+    users: UsersService,
+    file_path: String,
+    // Ids of users that are already imported, so they are found only once
+    ids: HashMap<String, u64>,
+    changes: Vec<String>,
+}
+
+impl ImportService {
+    pub(crate) fn new(file_path: String) -> Result<Self> {
+        Ok(Self {
+            users: UsersService::new(file_path.clone())?,
+            file_path,
+            ids: HashMap::new(),
+            changes: Vec::new(),
+        })
+    }
+
+    /// Read rows of the csv file, `columns` maps fields to headers of the file.
+    /// Quoted fields can have commas, quotes and line breaks, rows are numbered by their first line
+    pub(crate) fn read_csv(path: &str, columns: &BTreeMap<String, String>) -> Result<Vec<Row>> {
+        let data = fs::read_to_string(path)?;
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(data.as_bytes());
+        let headers: Vec<String> = match reader.headers() {
+            Ok(headers) => headers.iter().map(|h| h.to_string()).collect(),
+            Err(err) => return Err(ImportService::invalid(format!("{}: {}", path, err))),
+        };
+        if headers.iter().all(|h| h.is_empty()) {
+            return Ok(Vec::new());
+        }
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        for field in FIELDS {
+            let column = columns.get(field).map_or(field, |c| c.as_str());
+            if let Some(i) = headers.iter().position(|h| h.eq_ignore_ascii_case(column)) {
+                index.insert(field, i);
+            } else if field == "user" || columns.contains_key(field) {
+                return Err(ImportService::invalid(format!(
+                    "Column {} is not found in {}, columns are: {}",
+                    column,
+                    path,
+                    headers.join(", ")
+                )));
+            }
+        }
+
+        let mut rows: Vec<Row> = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|err| ImportService::invalid(format!("{}: {}", path, err)))?;
+            let get = |field: &str| {
+                index
+                    .get(field)
+                    .and_then(|i| record.get(*i))
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            // A position starts at the blank lines before the record, so they are skipped before counting
+            let line = record.position().map_or(0, |p| {
+                let start = data[p.byte() as usize..]
+                    .find(|c| c != '\r' && c != '\n')
+                    .map_or(data.len(), |i| i + p.byte() as usize);
+                data[..start].matches('\n').count() + 1
+            });
+            let user = UserRef::from_str(&get("user"))
+                .map_err(|err| ImportService::invalid(format!("Line {}: {}", line, err)))?;
+            let level = match get("level").to_lowercase().as_str() {
+                "" => None,
+                level => Some(
+                    AccessLevel::from_str(level)
+                        .map_err(|err| ImportService::invalid(format!("Line {}: {}", line, err)))?,
+                ),
+            };
+            let row = Row {
+                line,
+                user,
+                team: get("team"),
+                project: get("project"),
+                group: get("group"),
+                level,
+            };
+            if level.is_none() && (!row.project.is_empty() || !row.group.is_empty()) {
+                return Err(ImportService::invalid(format!(
+                    "Line {}: the level of the access to the project or the group is not set",
+                    line
+                )));
+            }
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    /// Add users of the rows to the config and give them what is set in the rows
+    pub(crate) fn import<T: GitlabApiInterface>(&mut self, gitlab_api: &T, rows: &[Row]) -> Result<&mut Self> {
+        for row in rows.iter() {
+            let id = self.add_user(gitlab_api, &row.user)?;
+            let user_ref = UserRef::Id(id);
+            if !row.team.is_empty() {
+                if self.users.config().team(&row.team).is_none() {
+                    OutMessage::message_warning(
+                        format!(
+                            "Line {}: the team {} doesn't exist, create it with `gum teams create`",
+                            row.line, row.team
+                        )
+                        .as_str(),
+                    );
+                }
+                let result = self.users.add_to_team(&user_ref, row.team.clone()).map(|_| ());
+                self.merge(result, format!("+ user {}: team {}", row.user, row.team))?;
+            }
+            if let Some(level) = row.level {
+                if !row.project.is_empty() {
+                    let pid = match row.project.parse::<u64>() {
+                        Ok(pid) => pid,
+                        Err(_) => gitlab_api.projects().get_data_by_path(&row.project)?.id,
+                    };
+                    let result = self
+                        .users
+                        .add_to_project(gitlab_api, pid, &user_ref, level)
+                        .map(|_| ());
+                    self.merge(result, format!("+ user {}: project {}: {}", row.user, row.project, level))?;
+                }
+                if !row.group.is_empty() {
+                    let gid = match row.group.parse::<u64>() {
+                        Ok(gid) => gid,
+                        Err(_) => gitlab_api.groups().get_data_by_path(&row.group)?.id,
+                    };
+                    let result = self
+                        .users
+                        .add_to_namespace(gitlab_api, gid, &user_ref, level)
+                        .map(|_| ());
+                    self.merge(result, format!("+ user {}: group {}: {}", row.user, row.group, level))?;
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Show what is changed in the config and write it unless it's a dry run
+    pub(crate) fn write_state(&self, dry_run: bool) -> Result<()> {
+        for change in self.changes.iter() {
+            OutMessage::message_empty(change);
+        }
+        if self.changes.is_empty() {
+            OutMessage::message_info_with_alias("Everything is already in the config");
+            return Ok(());
+        }
+        if dry_run {
+            OutMessage::message_info_clean(
+                format!("It's a dry run, {} is not changed", self.file_path).as_str(),
+            );
+            return Ok(());
+        }
+        self.users.write_state()
+    }
+
+    // Users are created once, the id is used for the next rows, because the same user
+    // may be in the config already with another identity
+    fn add_user<T: GitlabApiInterface>(&mut self, gitlab_api: &T, user_ref: &UserRef) -> Result<u64> {
+        let identity = user_ref.to_string().to_lowercase();
+        if let Some(id) = self.ids.get(&identity) {
+            return Ok(*id);
+        }
+        let result = self.users.create(gitlab_api, user_ref).map(|_| ());
+        self.merge(result, format!("+ user {}", user_ref))?;
+        let id = match self.users.find(user_ref) {
+            Some(u) => u.id,
+            None => UsersResolver::new(gitlab_api, LockFile::default()).find(user_ref)?.id,
+        };
+        self.ids.insert(identity, id);
+        Ok(id)
+    }
+
+    // Rows that are already in the config are skipped, so the same file can be imported again
+    fn merge(&mut self, result: Result<()>, change: String) -> Result<()> {
+        match result {
+            Ok(_) => {
+                self.changes.push(change);
+                Ok(())
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                OutMessage::message_info_clean(format!("Skipped: {}", err).as_str());
+                Ok(())
+            }
+            Err(err) => {
+                OutMessage::message_error(err.to_string().as_str());
+                Err(err)
+            }
+        }
+    }
+
+    fn invalid(err_msg: String) -> Error {
+        OutMessage::message_error(err_msg.as_str());
+        Error::new(ErrorKind::InvalidData, err_msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs};
+    use tempfile::tempdir;
+
+    use super::{ImportService, Row};
+    use crate::types::v1::{AccessLevel, UserRef};
+
+    #[test]
+    fn read_csv() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("onboarding.csv");
+        let path = path.to_string_lossy().to_string();
+        fs::write(
+            &path,
+            "Email,Team,Repository,Access\n\
+            jane@example.com,backend,,\n\
+            42,,group/api,Developer\n\
+            \n\
+            jdoe,,10,maintainer\n",
+        )
+        .unwrap();
+        let columns: BTreeMap<String, String> = [("user", "email"), ("project", "Repository"), ("level", "Access")]
+            .iter()
+            .map(|(f, c)| (f.to_string(), c.to_string()))
+            .collect();
+
+        let rows = ImportService::read_csv(&path, &columns).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                Row {
+                    line: 2,
+                    user: UserRef::Email("jane@example.com".to_string()),
+                    team: "backend".to_string(),
+                    project: String::new(),
+                    group: String::new(),
+                    level: None,
+                },
+                Row {
+                    line: 3,
+                    user: UserRef::Id(42),
+                    team: String::new(),
+                    project: "group/api".to_string(),
+                    group: String::new(),
+                    level: Some(AccessLevel::Developer),
+                },
+                Row {
+                    line: 5,
+                    user: UserRef::Username("jdoe".to_string()),
+                    team: String::new(),
+                    project: "10".to_string(),
+                    group: String::new(),
+                    level: Some(AccessLevel::Maintainer),
+                },
+            ]
+        );
+
+        fs::write(
+            &path,
+            "\u{feff}Email,Team,Notes\n\
+            \"jane@example.com\",\"backend, \"\"core\"\"\",\"first\n\
+            second\"\n\
+            jdoe,qa,\n",
+        )
+        .unwrap();
+        let email: BTreeMap<String, String> = [("user".to_string(), "Email".to_string())].into();
+        let rows = ImportService::read_csv(&path, &email).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].line, rows[0].team.as_str()), (2, "backend, \"core\""));
+        assert_eq!((rows[1].line, rows[1].team.as_str()), (4, "qa"));
+
+        fs::write(&path, "Email,Repository\njane@example.com,group/api\n").unwrap();
+        assert!(ImportService::read_csv(&path, &columns).is_err());
+    }
+}
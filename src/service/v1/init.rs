@@ -29,7 +29,7 @@ impl<T: GitlabApiInterface> InitService<T> {
         OutMessage::message_info_with_alias("Scrapping groups");
         for g in groups.iter() {
             let head_group = groups_api.get_data_by_id(*g)?;
-            let sub_groups = groups_api.get_subgroups(head_group.name.clone(), head_group.id, true);
+            let sub_groups = groups_api.get_subgroups(head_group.name.clone(), head_group.id, true)?;
             all_groups.push(head_group);
             all_groups.extend(sub_groups);
         }
@@ -42,7 +42,7 @@ impl<T: GitlabApiInterface> InitService<T> {
         OutMessage::message_info_with_alias("Scrapping projects");
         let mut projects: Vec<Project> = Vec::new();
         for g in groups.iter() {
-            projects.extend(groups_api.get_projects(g.name.clone(), g.id));
+            projects.extend(groups_api.get_projects(g.name.clone(), g.id)?);
         }
         OutMessage::message_info_with_alias(format!("Got {} projects", projects.len()).as_str());
         return Ok(projects);
@@ -60,6 +60,7 @@ impl<T: GitlabApiInterface> InitService<T> {
                                 ns.group_access_level,
                             ),
                             id: g.id,
+                            path: Default::default(),
                             url: g.web_url.clone(),
                         };
                         // TODO: Use a HashMap here to avoid a loop
@@ -75,8 +76,10 @@ impl<T: GitlabApiInterface> InitService<T> {
                             let group_entry = v1::Group {
                                 name: ns.group_name.clone(),
                                 id: ns.group_id,
+                                path: Default::default(),
                                 projects: Default::default(),
                                 namespaces: vec![item],
+                                projects_in: Default::default(),
                             };
                             self.config_file.config_mut().groups.push(group_entry);
                         }
@@ -86,7 +89,7 @@ impl<T: GitlabApiInterface> InitService<T> {
                     OutMessage::message_info_clean("This group is not shared");
                 }
             };
-            let groups_users = groups_api.get_members(g.name.to_string(), g.id);
+            let groups_users = groups_api.get_members(g.name.to_string(), g.id)?;
             for member in groups_users.iter() {
                 // TODO: Use a HashMap here to avoid a loop
                 let mut found = false;
@@ -100,10 +103,13 @@ impl<T: GitlabApiInterface> InitService<T> {
                 if !found {
                     self.config_file.config_mut().users.push(v1::User {
                         id: member.id,
+                        username: Default::default(),
+                        email: Default::default(),
                         name: member.name.clone(),
                         teams: Default::default(),
                         projects: Default::default(),
                         namespaces: vec![g.to_gum_group(member.clone()).unwrap()],
+                        projects_in: Default::default(),
                     });
                 }
             }
@@ -121,6 +127,7 @@ impl<T: GitlabApiInterface> InitService<T> {
                     for ns in group.iter() {
                         let item = v1::Project {
                             id: p.id,
+                            path: Default::default(),
                             name: p.name.clone(),
                             access_level: AccessLevel::from_gitlab_access_level(
                                 ns.group_access_level,
@@ -137,8 +144,10 @@ impl<T: GitlabApiInterface> InitService<T> {
                             let group_entry = v1::Group {
                                 name: ns.group_name.clone(),
                                 id: ns.group_id,
+                                path: Default::default(),
                                 namespaces: Default::default(),
                                 projects: vec![item],
+                                projects_in: Default::default(),
                             };
                             self.config_file.config_mut().groups.push(group_entry);
                         }
@@ -148,7 +157,7 @@ impl<T: GitlabApiInterface> InitService<T> {
                     OutMessage::message_info_clean("This project is not shared");
                 }
             };
-            let projects_users = projects_api.get_members(p.name.to_string(), p.id);
+            let projects_users = projects_api.get_members(p.name.to_string(), p.id)?;
             for member in projects_users.iter() {
                 // TODO: Use a HashMap here to avoid a loop
                 let mut found = false;
@@ -162,10 +171,13 @@ impl<T: GitlabApiInterface> InitService<T> {
                 if !found {
                     self.config_file.config_mut().users.push(v1::User {
                         id: member.id,
+                        username: Default::default(),
+                        email: Default::default(),
                         name: member.name.clone(),
                         projects: vec![p.to_gum_project(member.clone()).unwrap()],
                         teams: Default::default(),
                         namespaces: Default::default(),
+                        projects_in: Default::default(),
                     });
                 }
             }
@@ -1,10 +1,36 @@
+mod describe;
+mod diff;
+mod export;
+mod graph;
+mod identities;
+mod import;
 pub(crate) mod init;
+mod paths;
+mod policy;
+mod refresh;
+mod report;
+mod rules;
+pub(crate) mod state;
 pub(crate) mod sync;
 pub(crate) mod users;
+pub(crate) mod validate;
 mod groups;
 mod teams;
 
+pub(crate) use describe::DescribeService;
+pub(crate) use diff::DiffService;
+pub(crate) use export::{write_output, Matrix};
+pub(crate) use graph::Graph;
+pub(crate) use identities::UsersResolver;
+pub(crate) use import::{ImportService, FIELDS};
 pub(crate) use init::InitService;
+pub(crate) use paths::PathsResolver;
+pub(crate) use policy::{object_violations, report_violations, PolicyChecker};
+pub(crate) use refresh::RefreshService;
+pub(crate) use report::ReportService;
+pub(crate) use state::{StateObject, StateService};
+pub(crate) use rules::RulesExpander;
 pub(crate) use sync::SyncService;
 pub(crate) use groups::GroupsService;
 pub(crate) use teams::TeamsService;
+pub(crate) use validate::ValidateService;
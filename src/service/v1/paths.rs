@@ -0,0 +1,378 @@
+use crate::{
+    gitlab::{
+        apis::{groups::GitlabGroupsApi, projects::GitlabProjectsApi},
+        GitlabApiInterface,
+    },
+    output::out_message::OutMessage,
+    types::v1::{Config, LockFile},
+};
+use std::io::{Error, ErrorKind, Result};
+
+// PathsResolver should be used to get ids of projects and groups that are referenced by path
+pub(crate) struct PathsResolver<'a, T: GitlabApiInterface> {
+    gitlab_api: &'a T,
+    lock_file: LockFile,
+    resolved: LockFile,
+}
+
+impl<'a, T: GitlabApiInterface> PathsResolver<'a, T> {
+    pub(crate) fn new(gitlab_api: &'a T, lock_file: LockFile) -> Self {
+        Self {
+            gitlab_api,
+            lock_file,
+            resolved: LockFile::default(),
+        }
+    }
+
+    /// Set ids for every entry in the config that has a path.
+    /// Returns the lock file that contains only paths which are used in the config
+    pub(crate) fn resolve(&mut self, config: &mut Config) -> Result<LockFile> {
+        for t in config.teams.iter_mut() {
+            for p in t.projects.iter_mut() {
+                p.id = self.resolve_project(&p.path, p.id)?;
+            }
+            for n in t.namespaces.iter_mut() {
+                n.id = self.resolve_group(&n.path, n.id)?;
+            }
+            for r in t.projects_in.iter_mut() {
+                r.group = self.resolve_group(&r.path, r.group)?;
+            }
+        }
+        for u in config.users.iter_mut() {
+            for p in u.projects.iter_mut() {
+                p.id = self.resolve_project(&p.path, p.id)?;
+            }
+            for n in u.namespaces.iter_mut() {
+                n.id = self.resolve_group(&n.path, n.id)?;
+            }
+            for r in u.projects_in.iter_mut() {
+                r.group = self.resolve_group(&r.path, r.group)?;
+            }
+        }
+        for i in config.invites.iter_mut() {
+            for p in i.projects.iter_mut() {
+                p.id = self.resolve_project(&p.path, p.id)?;
+            }
+            for n in i.namespaces.iter_mut() {
+                n.id = self.resolve_group(&n.path, n.id)?;
+            }
+        }
+        for g in config.groups.iter_mut() {
+            g.id = self.resolve_group(&g.path, g.id)?;
+            for p in g.projects.iter_mut() {
+                p.id = self.resolve_project(&p.path, p.id)?;
+            }
+            for n in g.namespaces.iter_mut() {
+                n.id = self.resolve_group(&n.path, n.id)?;
+            }
+            for r in g.projects_in.iter_mut() {
+                r.group = self.resolve_group(&r.path, r.group)?;
+            }
+        }
+        Ok(self.resolved.clone())
+    }
+
+    fn resolve_project(&mut self, path: &str, id: u64) -> Result<u64> {
+        if path.is_empty() {
+            return Ok(id);
+        }
+        if let Some(resolved) = self.resolved.projects.get(path) {
+            return PathsResolver::<T>::check_id("project", path, id, *resolved);
+        }
+
+        let projects_api = self.gitlab_api.projects();
+        let cached = self.lock_file.projects.get(path).copied();
+        let resolved = match projects_api.get_data_by_path(path) {
+            Ok(project) => {
+                if !project.path_with_namespace.is_empty()
+                    && project.path_with_namespace != path
+                {
+                    OutMessage::message_warning(
+                        format!(
+                            "Project {} was moved to {}, please update the config",
+                            path, project.path_with_namespace
+                        )
+                        .as_str(),
+                    );
+                }
+                // Access must not silently move to another project, so it should be confirmed in the config
+                if let Some(cached) = cached {
+                    if id == 0 && cached != project.id {
+                        let err_msg = format!(
+                            "{} was the project {} before, but now it's the project {}. If it's expected, set `id: {}` for this project in the config",
+                            path, cached, project.id, project.id
+                        );
+                        OutMessage::message_error(err_msg.as_str());
+                        return Err(Error::new(ErrorKind::InvalidData, err_msg));
+                    }
+                }
+                project.id
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => match cached {
+                Some(cached) => {
+                    let project = projects_api.get_data_by_id(cached)?;
+                    let err_msg = format!(
+                        "Project {} ({}) was moved to {}, please update the config",
+                        path, cached, project.path_with_namespace
+                    );
+                    OutMessage::message_error(err_msg.as_str());
+                    return Err(Error::new(ErrorKind::NotFound, err_msg));
+                }
+                None => return Err(err),
+            },
+            Err(err) => return Err(err),
+        };
+        self.resolved.projects.insert(path.to_string(), resolved);
+        PathsResolver::<T>::check_id("project", path, id, resolved)
+    }
+
+    fn resolve_group(&mut self, path: &str, id: u64) -> Result<u64> {
+        if path.is_empty() {
+            return Ok(id);
+        }
+        if let Some(resolved) = self.resolved.groups.get(path) {
+            return PathsResolver::<T>::check_id("group", path, id, *resolved);
+        }
+
+        let groups_api = self.gitlab_api.groups();
+        let cached = self.lock_file.groups.get(path).copied();
+        let resolved = match groups_api.get_data_by_path(path) {
+            Ok(group) => {
+                if !group.full_path.is_empty() && group.full_path != path {
+                    OutMessage::message_warning(
+                        format!(
+                            "Group {} was moved to {}, please update the config",
+                            path, group.full_path
+                        )
+                        .as_str(),
+                    );
+                }
+                // Access must not silently move to another group, so it should be confirmed in the config
+                if let Some(cached) = cached {
+                    if id == 0 && cached != group.id {
+                        let err_msg = format!(
+                            "{} was the group {} before, but now it's the group {}. If it's expected, set `id: {}` for this group in the config",
+                            path, cached, group.id, group.id
+                        );
+                        OutMessage::message_error(err_msg.as_str());
+                        return Err(Error::new(ErrorKind::InvalidData, err_msg));
+                    }
+                }
+                group.id
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => match cached {
+                Some(cached) => {
+                    let group = groups_api.get_data_by_id(cached)?;
+                    let err_msg = format!(
+                        "Group {} ({}) was moved to {}, please update the config",
+                        path, cached, group.full_path
+                    );
+                    OutMessage::message_error(err_msg.as_str());
+                    return Err(Error::new(ErrorKind::NotFound, err_msg));
+                }
+                None => return Err(err),
+            },
+            Err(err) => return Err(err),
+        };
+        self.resolved.groups.insert(path.to_string(), resolved);
+        PathsResolver::<T>::check_id("group", path, id, resolved)
+    }
+
+    // When both id and path are set in the config, they must point to the same entity
+    fn check_id(kind: &str, path: &str, id: u64, resolved: u64) -> Result<u64> {
+        if id != 0 && id != resolved {
+            let err_msg = format!(
+                "The {} {} has id {}, but {} is set in the config",
+                kind, path, resolved, id
+            );
+            OutMessage::message_error(err_msg.as_str());
+            return Err(Error::new(ErrorKind::InvalidData, err_msg));
+        }
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Error, ErrorKind},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use super::PathsResolver;
+    use crate::{
+        gitlab::{
+            apis::{groups::MockGitlabGroupsApi, projects::MockGitlabProjectsApi},
+            types::{group::Group, project::Project},
+            GitlabApiMock,
+        },
+        types::v1::{Config, LockFile},
+    };
+
+    // org/api is project 10 that has been moved to org/backend/api, org/old is removed
+    fn gitlab_api(calls: Arc<AtomicUsize>) -> GitlabApiMock {
+        GitlabApiMock {
+            projects: Box::new(move || {
+                let calls = calls.clone();
+                let mut projects_api = MockGitlabProjectsApi::new();
+                projects_api.expect_get_data_by_path().returning(move |path| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    match path {
+                        "org/api" => Ok(project(10, "org/backend/api")),
+                        "org/web" => Ok(project(11, "org/web")),
+                        _ => Err(Error::new(ErrorKind::NotFound, "project can't be found")),
+                    }
+                });
+                projects_api
+                    .expect_get_data_by_id()
+                    .returning(|id| Ok(project(id, "org/archive/old")));
+                projects_api
+            }),
+            groups: Box::new(|| {
+                let mut groups_api = MockGitlabGroupsApi::new();
+                groups_api.expect_get_data_by_path().returning(|path| {
+                    Ok(Group {
+                        id: 5,
+                        name: "org".to_string(),
+                        web_url: String::new(),
+                        full_path: path.to_string(),
+                    })
+                });
+                groups_api
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn project(id: u64, path: &str) -> Project {
+        Project {
+            id,
+            name: String::new(),
+            web_url: String::new(),
+            path_with_namespace: path.to_string(),
+        }
+    }
+
+    fn config(data: &str) -> Config {
+        serde_yaml::from_str(data).unwrap()
+    }
+
+    #[test]
+    fn resolve_paths() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let gitlab_api = gitlab_api(calls.clone());
+        let mut lock_file = LockFile::default();
+        lock_file.projects.insert("org/web".to_string(), 11);
+        lock_file.projects.insert("org/unused".to_string(), 8);
+        let mut config = config(
+            r#"
+teams:
+  - name: backend
+    projects:
+      - path: org/api
+        access_level: Developer
+    namespaces:
+      - path: org
+        access_level: Guest
+users:
+  - id: 1
+    name: alice
+    projects:
+      - path: org/api
+        access_level: Maintainer
+      - path: org/web
+        access_level: Reporter
+      - id: 12
+        access_level: Reporter
+groups: []
+"#,
+        );
+
+        let resolved = PathsResolver::new(&gitlab_api, lock_file).resolve(&mut config).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(config.teams[0].projects[0].id, 10);
+        assert_eq!(config.teams[0].namespaces[0].id, 5);
+        let ids: Vec<u64> = config.users[0].projects.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![10, 11, 12]);
+        assert_eq!(resolved.projects.len(), 2);
+        assert_eq!(resolved.projects.get("org/web"), Some(&11));
+        assert_eq!(resolved.groups.get("org"), Some(&5));
+    }
+
+    #[test]
+    fn resolve_removed_paths() {
+        let data = r#"
+users:
+  - id: 1
+    name: alice
+    projects:
+      - path: org/old
+        access_level: Reporter
+"#;
+        let gitlab_api = gitlab_api(Arc::new(AtomicUsize::new(0)));
+
+        // The project is found by the id from the lock file, but the config must be updated
+        let mut lock_file = LockFile::default();
+        lock_file.projects.insert("org/old".to_string(), 9);
+        let mut moved = config(data);
+        let err = PathsResolver::new(&gitlab_api, lock_file).resolve(&mut moved).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(err.to_string().contains("org/archive/old"));
+
+        let mut removed = config(data);
+        let err = PathsResolver::new(&gitlab_api, LockFile::default())
+            .resolve(&mut removed)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn resolve_conflicting_ids() {
+        let gitlab_api = gitlab_api(Arc::new(AtomicUsize::new(0)));
+        let mut config = config(
+            r#"
+users:
+  - id: 1
+    name: alice
+    projects:
+      - id: 3
+        path: org/api
+        access_level: Reporter
+"#,
+        );
+        let err = PathsResolver::new(&gitlab_api, LockFile::default())
+            .resolve(&mut config)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn resolve_reused_paths() {
+        let gitlab_api = gitlab_api(Arc::new(AtomicUsize::new(0)));
+        let mut lock_file = LockFile::default();
+        lock_file.projects.insert("org/web".to_string(), 7);
+        let data = r#"
+users:
+  - id: 1
+    name: alice
+    projects:
+      - path: org/web
+        access_level: Reporter
+"#;
+
+        // The path points to another project now, it's an error until the new id is set
+        let mut reused = config(data);
+        let err = PathsResolver::new(&gitlab_api, lock_file.clone())
+            .resolve(&mut reused)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("id: 11"));
+
+        let mut pinned = config(&data.replace("- path: org/web", "- id: 11\n        path: org/web"));
+        PathsResolver::new(&gitlab_api, lock_file).resolve(&mut pinned).unwrap();
+        assert_eq!(pinned.users[0].projects[0].id, 11);
+    }
+}
@@ -0,0 +1,499 @@
+use crate::{
+    gitlab::{
+        apis::{groups::GitlabGroupsApi, projects::GitlabProjectsApi, users::GitlabUsersApi},
+        GitlabApiInterface,
+    },
+    output::out_message::OutMessage,
+    types::{
+        policy::{AccessCheck, ObjectGrant, ObjectKind, Policy, UserFacts, UserRule, Violation},
+        v1::{AccessLevel, AccessUnit, Config, EntityType, Namespace, Project, TeamRole},
+    },
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Error, ErrorKind, Result},
+};
+
+use super::{
+    describe::{state_subject, Grant},
+    StateObject,
+};
+
+// PolicyChecker should be used to check the config against policies from the meta
+pub(crate) struct PolicyChecker<'a, T: GitlabApiInterface> {
+    gitlab_api: &'a T,
+    policy: &'a Policy,
+    // Paths of objects that are found in GitLab
+    paths: HashMap<(ObjectKind, u64), String>,
+}
+
+impl<'a, T: GitlabApiInterface> PolicyChecker<'a, T> {
+    pub(crate) fn new(gitlab_api: &'a T, policy: &'a Policy) -> Self {
+        Self {
+            gitlab_api,
+            policy,
+            paths: HashMap::new(),
+        }
+    }
+
+    /// Check every user and invite of the config against the users policy,
+    /// users must be resolved already, because they're found in GitLab by ids
+    pub(crate) fn check_users(&self, config: &Config) -> Result<Vec<Violation>> {
+        let policy = &self.policy.users;
+        if policy.is_empty() {
+            return Ok(Vec::new());
+        }
+        validate(self.policy)?;
+
+        let mut violations: Vec<Violation> = Vec::new();
+        let users_api = self.gitlab_api.users();
+        for u in config.users.iter().filter(|u| u.id != 0) {
+            let account = users_api.get_data_by_id(u.id)?;
+            let email = if !u.email.is_empty() {
+                Some(u.email.as_str())
+            } else {
+                account.email.as_deref().or(account.public_email.as_deref())
+            };
+            let teams = config.user_teams(u);
+            violations.extend(policy.check(&UserFacts {
+                title: format!("{} ({})", u.id, account.username),
+                username: Some(&account.username),
+                email,
+                two_factor_enabled: account.two_factor_enabled,
+                teams: teams.iter().map(|(t, _)| t.name.as_str()).collect(),
+            }));
+        }
+        // Invited people don't have accounts yet, so only emails and teams are checked
+        for i in config.invites.iter() {
+            let user = i.as_user();
+            let teams = config.user_teams(&user);
+            violations.extend(
+                policy
+                    .check(&UserFacts {
+                        title: format!("invited by {}", i.email),
+                        email: Some(&i.email),
+                        teams: teams.iter().map(|(t, _)| t.name.as_str()).collect(),
+                        ..Default::default()
+                    })
+                    .into_iter()
+                    .filter(|v| v.rule != UserRule::Require2fa.to_string()),
+            );
+        }
+        Ok(violations)
+    }
+
+    /// Check grants of the config against rules for objects,
+    /// paths of objects that are referenced by ids are found in GitLab
+    pub(crate) fn check_objects(&mut self, config: &Config) -> Result<Vec<Violation>> {
+        let gitlab_api = self.gitlab_api;
+        let paths = &mut self.paths;
+        object_violations(self.policy, config, &mut |object, id| {
+            path_in_gitlab(gitlab_api, paths, object, id)
+        })
+    }
+
+    /// Check the planned state against access rules
+    pub(crate) fn check_rules(
+        &mut self,
+        config: &Config,
+        state: &HashMap<u64, AccessUnit>,
+    ) -> Result<Vec<Violation>> {
+        let gitlab_api = self.gitlab_api;
+        let paths = &mut self.paths;
+        rule_violations(self.policy, config, state, &mut |object, id| {
+            path_in_gitlab(gitlab_api, paths, object, id)
+        })
+    }
+}
+
+fn path_in_gitlab<T: GitlabApiInterface>(
+    gitlab_api: &T,
+    paths: &mut HashMap<(ObjectKind, u64), String>,
+    object: ObjectKind,
+    id: u64,
+) -> Result<String> {
+    if let Some(path) = paths.get(&(object, id)) {
+        return Ok(path.clone());
+    }
+    let path = match object {
+        ObjectKind::Project => gitlab_api.projects().get_data_by_id(id)?.path_with_namespace,
+        ObjectKind::Group => gitlab_api.groups().get_data_by_id(id)?.full_path,
+    };
+    paths.insert((object, id), path.clone());
+    Ok(path)
+}
+
+// A subject of the state with grants from the config that explain its access
+struct Subject {
+    title: String,
+    entity: EntityType,
+    grants: Vec<Grant>,
+}
+
+/// Evaluate access rules against the planned state,
+/// grants of the config are used to show where the access comes from
+pub(crate) fn rule_violations(
+    policy: &Policy,
+    config: &Config,
+    state: &HashMap<u64, AccessUnit>,
+    path_of: &mut dyn FnMut(ObjectKind, u64) -> Result<String>,
+) -> Result<Vec<Violation>> {
+    if policy.rules.is_empty() {
+        return Ok(Vec::new());
+    }
+    // Sorted, so violations are reported in the same order every time
+    let state: BTreeMap<&u64, &AccessUnit> = state.iter().collect();
+    let subjects: HashMap<u64, Subject> = state
+        .iter()
+        .map(|(id, unit)| (**id, subject(config, **id, unit)))
+        .collect();
+    let mut access: Vec<(u64, ObjectKind, u64, AccessLevel)> = Vec::new();
+    for (id, unit) in state.iter() {
+        let mut projects: Vec<(&u64, &AccessLevel)> = unit.projects.iter().collect();
+        projects.sort_by_key(|(o, _)| **o);
+        let mut namespaces: Vec<(&u64, &AccessLevel)> = unit.namespaces.iter().collect();
+        namespaces.sort_by_key(|(o, _)| **o);
+        access.extend(projects.iter().map(|(o, a)| (**id, ObjectKind::Project, **o, **a)));
+        access.extend(namespaces.iter().map(|(o, a)| (**id, ObjectKind::Group, **o, **a)));
+    }
+
+    let mut violations: Vec<Violation> = Vec::new();
+    for (i, rule) in policy.rules.iter().enumerate() {
+        let mut push = |message: String| {
+            violations.push(Violation {
+                rule: rule.title(i),
+                message,
+                warn: rule.warn,
+            })
+        };
+        match &rule.check {
+            AccessCheck::MaxSubjects { filter, access: min, max } => {
+                let mut holders: BTreeMap<(ObjectKind, u64), Vec<String>> = BTreeMap::new();
+                for (sid, object, oid, a) in access.iter() {
+                    if !min.is_above(*a) && filter.matches(*object, *oid, path_of)? {
+                        let s = &subjects[sid];
+                        let via = sources(&s.grants, *object, *oid, *min);
+                        holders
+                            .entry((*object, *oid))
+                            .or_default()
+                            .push(format!("{} ({})", s.title, via));
+                    }
+                }
+                for ((object, oid), h) in holders {
+                    if h.len() > *max {
+                        push(format!(
+                            "{} has {} subjects with {} or higher, but at most {} are allowed: {}",
+                            object_title(object, oid, path_of)?,
+                            h.len(),
+                            min,
+                            max,
+                            h.join(", ")
+                        ));
+                    }
+                }
+            }
+            AccessCheck::OnlyTeams { filter, access: min, teams } => {
+                for (sid, object, oid, a) in access.iter() {
+                    if min.is_above(*a) || !filter.matches(*object, *oid, path_of)? {
+                        continue;
+                    }
+                    let s = &subjects[sid];
+                    let offending: Vec<&Grant> = granting(&s.grants, *object, *oid, *min)
+                        .filter(|g| !teams.contains(&g.team))
+                        .collect();
+                    if !offending.is_empty() {
+                        let via: Vec<String> = offending.iter().map(|g| format!("{} {}", g.access, g.via)).collect();
+                        push(format!(
+                            "{} has {} on the {} through {}, but only teams {} may grant {} or higher there",
+                            s.title,
+                            a,
+                            object_title(*object, *oid, path_of)?,
+                            via.join(", "),
+                            teams.join(", "),
+                            min
+                        ));
+                    }
+                }
+            }
+            AccessCheck::NoDirect { object: kind } => {
+                for (sid, object, oid, a) in access.iter() {
+                    let s = &subjects[sid];
+                    if s.entity == EntityType::Group || kind.is_some_and(|k| k != *object) {
+                        continue;
+                    }
+                    let direct = s
+                        .grants
+                        .iter()
+                        .any(|g| g.team.is_empty() && as_kind(&g.object) == (*object, *oid));
+                    if direct {
+                        push(format!(
+                            "{} gets {} on the {} directly, but access may be only granted by teams",
+                            s.title,
+                            a,
+                            object_title(*object, *oid, path_of)?
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    Ok(violations)
+}
+
+fn subject(config: &Config, id: u64, unit: &AccessUnit) -> Subject {
+    let (title, grants) = state_subject(config, id, &unit.entity);
+    Subject {
+        title,
+        entity: unit.entity.clone(),
+        grants,
+    }
+}
+
+fn as_kind(object: &StateObject) -> (ObjectKind, u64) {
+    match object {
+        StateObject::Project(id) => (ObjectKind::Project, *id),
+        StateObject::Group(id) => (ObjectKind::Group, *id),
+    }
+}
+
+// Grants of the object that give this access or a higher one
+fn granting(
+    grants: &[Grant],
+    object: ObjectKind,
+    id: u64,
+    min: AccessLevel,
+) -> impl Iterator<Item = &Grant> {
+    grants
+        .iter()
+        .filter(move |g| as_kind(&g.object) == (object, id) && !min.is_above(g.access))
+}
+
+fn sources(grants: &[Grant], object: ObjectKind, id: u64, min: AccessLevel) -> String {
+    let via: Vec<&str> = granting(grants, object, id, min).map(|g| g.via.as_str()).collect();
+    match via.is_empty() {
+        true => "unknown source".to_string(),
+        false => via.join(", "),
+    }
+}
+
+fn object_title(
+    object: ObjectKind,
+    id: u64,
+    path_of: &mut dyn FnMut(ObjectKind, u64) -> Result<String>,
+) -> Result<String> {
+    let path = path_of(object, id)?;
+    match path.is_empty() {
+        true => Ok(format!("{} {}", object, id)),
+        false => Ok(format!("{} {} ({})", object, path, id)),
+    }
+}
+
+/// Check grants of teams, users and invites against rules for objects.
+/// `path_of` is used for objects without paths in the config, grants are skipped if it's empty
+pub(crate) fn object_violations(
+    policy: &Policy,
+    config: &Config,
+    path_of: &mut dyn FnMut(ObjectKind, u64) -> Result<String>,
+) -> Result<Vec<Violation>> {
+    if policy.objects.is_empty() {
+        return Ok(Vec::new());
+    }
+    validate(policy)?;
+
+    let mut grants: Vec<ObjectGrant> = Vec::new();
+    for t in config.teams.iter() {
+        let including = config.including_teams(&t.name);
+        let teams: Vec<&str> = including.iter().map(|t| t.name.as_str()).collect();
+        // Leads of the team and of teams that include it get more than the team grants
+        let has_leads = including
+            .iter()
+            .any(|t| t.members.iter().any(|m| m.role == TeamRole::Lead));
+        for g in object_grants(format!("team {}", t.name), &teams, &t.projects, &t.namespaces) {
+            let lead_access = TeamRole::Lead.access(g.access);
+            if has_leads && lead_access != g.access {
+                grants.push(ObjectGrant {
+                    source: format!("team {} (to its leads)", t.name),
+                    access: lead_access,
+                    ..g.clone()
+                });
+            }
+            grants.push(g);
+        }
+    }
+    for u in config.users.iter() {
+        let teams = config.user_teams(u);
+        let teams: Vec<&str> = teams.iter().map(|(t, _)| t.name.as_str()).collect();
+        let source = format!("user {} ({})", u.id, u.name);
+        grants.extend(object_grants(source, &teams, &u.projects, &u.namespaces));
+    }
+    for i in config.invites.iter() {
+        let teams = config.user_teams(&i.as_user());
+        let teams: Vec<&str> = teams.iter().map(|(t, _)| t.name.as_str()).collect();
+        let source = format!("invite of {}", i.email);
+        grants.extend(object_grants(source, &teams, &i.projects, &i.namespaces));
+    }
+
+    let mut violations: Vec<Violation> = Vec::new();
+    for mut grant in grants {
+        if grant.path.is_empty() && grant.id != 0 {
+            grant.path = path_of(grant.object, grant.id)?;
+        }
+        if grant.path.is_empty() {
+            continue;
+        }
+        for (i, rule) in policy.objects.iter().enumerate() {
+            violations.extend(rule.check(i, &grant));
+        }
+    }
+    Ok(violations)
+}
+
+fn object_grants<'a>(
+    source: String,
+    teams: &[&'a str],
+    projects: &[Project],
+    namespaces: &[Namespace],
+) -> Vec<ObjectGrant<'a>> {
+    let grant = |object: ObjectKind, id: u64, path: &str, access: AccessLevel| ObjectGrant {
+        source: source.clone(),
+        teams: teams.to_vec(),
+        object,
+        id,
+        path: path.to_string(),
+        access,
+    };
+    projects
+        .iter()
+        .map(|p| grant(ObjectKind::Project, p.id, &p.path, p.access_level))
+        .chain(
+            namespaces
+                .iter()
+                .map(|n| grant(ObjectKind::Group, n.id, &n.path, n.access_level)),
+        )
+        .collect()
+}
+
+// The policy is checked before it's applied, so broken rules are not skipped silently
+fn validate(policy: &Policy) -> Result<()> {
+    let problems = policy.validate();
+    for p in problems.iter() {
+        OutMessage::message_error(p);
+    }
+    match problems.first() {
+        Some(err_msg) => Err(Error::new(ErrorKind::InvalidData, err_msg.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Print violations, it fails if at least one of them is not a warning
+pub(crate) fn report_violations(violations: &[Violation]) -> Result<()> {
+    let mut errors = 0;
+    for v in violations.iter() {
+        if v.warn {
+            OutMessage::message_warning(v.to_string().as_str());
+        } else {
+            OutMessage::message_error(v.to_string().as_str());
+            errors += 1;
+        }
+    }
+    if errors > 0 {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!("The config breaks the policy {} times", errors),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs};
+    use tempfile::tempdir;
+
+    use super::rule_violations;
+    use crate::types::{
+        policy::{ObjectKind, Policy},
+        v1::{AccessLevel, AccessUnit, ConfigFile, EntityType},
+    };
+
+    #[test]
+    fn access_rules() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: sre
+      projects:
+        - id: 10
+          access_level: Maintainer
+    - name: backend
+      projects:
+        - id: 10
+          access_level: Maintainer
+  users:
+    - id: 1
+      name: alice
+      teams: [sre]
+    - id: 2
+      name: bob
+      teams: [backend]
+      projects:
+        - id: 11
+          access_level: Developer
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        let config_file = ConfigFile::read(file_path.to_string_lossy().to_string()).unwrap();
+        let policy: Policy = serde_yaml::from_str(
+            r#"
+rules:
+  - name: sre-only
+    rule: only_teams
+    in_group: 42
+    access: Maintainer
+    teams: [sre]
+  - rule: max_subjects
+    access: Developer
+    max: 1
+    warn: true
+  - rule: no_direct
+"#,
+        )
+        .unwrap();
+
+        let unit = |projects: Vec<(u64, AccessLevel)>| AccessUnit {
+            entity: EntityType::User,
+            projects: projects.into_iter().collect(),
+            ..Default::default()
+        };
+        let state = HashMap::from([
+            (1, unit(vec![(10, AccessLevel::Maintainer)])),
+            (2, unit(vec![(10, AccessLevel::Maintainer), (11, AccessLevel::Developer)])),
+        ]);
+        let paths = HashMap::from([
+            ((ObjectKind::Group, 42), "infra"),
+            ((ObjectKind::Project, 10), "infra/terraform"),
+            ((ObjectKind::Project, 11), "backend/api"),
+        ]);
+        let violations = rule_violations(&policy, config_file.config(), &state, &mut |o, id| {
+            Ok(paths.get(&(o, id)).unwrap_or(&"").to_string())
+        })
+        .unwrap();
+        let messages: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "user 2 (bob) has Maintainer on the project infra/terraform (10) through Maintainer team backend, \
+                but only teams sre may grant Maintainer or higher there (policy rule sre-only)",
+                "project infra/terraform (10) has 2 subjects with Developer or higher, but at most 1 are allowed: \
+                user 1 (alice) (team sre), user 2 (bob) (team backend) (policy rule rules[1])",
+                "user 2 (bob) gets Developer on the project backend/api (11) directly, \
+                but access may be only granted by teams (policy rule rules[2])",
+            ]
+        );
+        assert!(violations[1].warn);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    gitlab::{
+        apis::{groups::GitlabGroupsApi, projects::GitlabProjectsApi, users::GitlabUsersApi},
+        GitlabApiInterface,
+    },
+    output::out_message::OutMessage,
+    types::v1::{ConfigFile, Namespace, Project},
+};
+use std::{
+    collections::HashMap,
+    io::{ErrorKind, Result},
+};
+
+// RefreshService should be used to update names that are kept in the config after they're changed in GitLab
+pub(crate) struct RefreshService<T: GitlabApiInterface> {
+    config_file: ConfigFile,
+    file_path: String,
+    gitlab_api: T,
+    // Every entity is requested once, None is kept for entities that can't be found
+    users: HashMap<u64, Option<String>>,
+    projects: HashMap<u64, Option<String>>,
+    groups: HashMap<u64, Option<(String, String)>>,
+    updated: usize,
+    missing: Vec<String>,
+}
+
+impl<T: GitlabApiInterface> RefreshService<T> {
+    pub(crate) fn new(file_path: String, gitlab_api: T) -> Result<Self> {
+        Ok(Self {
+            config_file: ConfigFile::read(file_path.clone())?,
+            file_path,
+            gitlab_api,
+            users: HashMap::new(),
+            projects: HashMap::new(),
+            groups: HashMap::new(),
+            updated: 0,
+            missing: Vec::new(),
+        })
+    }
+
+    /// Get current names of every user, project and group of the config,
+    /// entries that are referenced by path only and are not resolved yet are skipped
+    pub(crate) fn refresh(&mut self) -> Result<&mut Self> {
+        let mut config = self.config_file.config().clone();
+        for u in config.users.iter_mut() {
+            if u.id != 0 {
+                if let Some(name) = self.user(u.id, &u.name)? {
+                    self.update(format!("user {}", u.id), &mut u.name, name);
+                }
+            }
+            self.refresh_projects(&mut u.projects)?;
+            self.refresh_namespaces(&mut u.namespaces)?;
+        }
+        for t in config.teams.iter_mut() {
+            self.refresh_projects(&mut t.projects)?;
+            self.refresh_namespaces(&mut t.namespaces)?;
+        }
+        for g in config.groups.iter_mut() {
+            if g.id != 0 {
+                if let Some((name, _)) = self.group(g.id, &g.name)? {
+                    self.update(format!("group {}", g.id), &mut g.name, name);
+                }
+            }
+            self.refresh_projects(&mut g.projects)?;
+            self.refresh_namespaces(&mut g.namespaces)?;
+        }
+        *self.config_file.config_mut() = config;
+
+        if self.updated == 0 {
+            OutMessage::message_info_with_alias("All names are up to date");
+        } else {
+            OutMessage::message_info_with_alias(
+                format!("{} names and urls are updated", self.updated).as_str(),
+            );
+        }
+        if !self.missing.is_empty() {
+            OutMessage::message_warning(
+                "These entities can't be found in GitLab, they are probably removed:",
+            );
+            for m in self.missing.iter() {
+                OutMessage::message_empty(m);
+            }
+        }
+        Ok(self)
+    }
+
+    pub(crate) fn write_state(&self, dry: bool) -> Result<()> {
+        if dry {
+            return Ok(());
+        }
+        self.config_file.write(self.file_path.clone())
+    }
+
+    fn refresh_projects(&mut self, projects: &mut [Project]) -> Result<()> {
+        for p in projects.iter_mut().filter(|p| p.id != 0) {
+            if let Some(name) = self.project(p.id, &p.name)? {
+                self.update(format!("project {}", p.id), &mut p.name, name);
+            }
+        }
+        Ok(())
+    }
+
+    fn refresh_namespaces(&mut self, namespaces: &mut [Namespace]) -> Result<()> {
+        for n in namespaces.iter_mut().filter(|n| n.id != 0) {
+            if let Some((name, url)) = self.group(n.id, &n.name)? {
+                self.update(format!("group {}", n.id), &mut n.name, name);
+                self.update(format!("url of the group {}", n.id), &mut n.url, url);
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, what: String, field: &mut String, value: String) {
+        if *field != value {
+            OutMessage::message_info_clean(
+                format!("{}: {} -> {}", what, field, value).as_str(),
+            );
+            *field = value;
+            self.updated += 1;
+        }
+    }
+
+    fn user(&mut self, id: u64, name: &str) -> Result<Option<String>> {
+        if let Some(cached) = self.users.get(&id) {
+            return Ok(cached.clone());
+        }
+        let found = match self.gitlab_api.users().get_data_by_id(id) {
+            Ok(user) => Some(user.name),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                self.missing.push(format!("user {} ({})", id, name));
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        self.users.insert(id, found.clone());
+        Ok(found)
+    }
+
+    fn project(&mut self, id: u64, name: &str) -> Result<Option<String>> {
+        if let Some(cached) = self.projects.get(&id) {
+            return Ok(cached.clone());
+        }
+        let found = match self.gitlab_api.projects().get_data_by_id(id) {
+            Ok(project) => Some(project.name),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                self.missing.push(format!("project {} ({})", id, name));
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        self.projects.insert(id, found.clone());
+        Ok(found)
+    }
+
+    fn group(&mut self, id: u64, name: &str) -> Result<Option<(String, String)>> {
+        if let Some(cached) = self.groups.get(&id) {
+            return Ok(cached.clone());
+        }
+        let found = match self.gitlab_api.groups().get_data_by_id(id) {
+            Ok(group) => Some((group.name, group.web_url)),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                self.missing.push(format!("group {} ({})", id, name));
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        self.groups.insert(id, found.clone());
+        Ok(found)
+    }
+}
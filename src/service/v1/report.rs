@@ -0,0 +1,413 @@
+use crate::{
+    output::out_message::OutMessage,
+    store::{history, Revision},
+    types::v1::{AccessLevel, AccessUnit, ConfigFile, EntityType},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Result,
+    path::Path,
+};
+
+use super::{
+    describe::{state_subject, Grant},
+    export::{access_sources, kind, rank},
+    StateObject, StateService,
+};
+
+// A subject of the state with its title and grants from the config
+struct Subject {
+    id: u64,
+    entity: EntityType,
+    title: String,
+    grants: Vec<Grant>,
+    access: Vec<(StateObject, AccessLevel)>,
+}
+
+// ReportService should be used to render the state as a static site that doesn't need anything else to be shown
+pub(crate) struct ReportService {
+    config_file: ConfigFile,
+    state: HashMap<u64, AccessUnit>,
+    last: Option<Revision>,
+}
+
+impl ReportService {
+    pub(crate) fn new(file_path: String) -> Result<Self> {
+        let state_service = StateService::new(file_path.clone())?;
+        let store = state_service.store()?;
+        Ok(Self {
+            config_file: ConfigFile::read(file_path)?,
+            state: state_service.get()?,
+            last: store.history()?.pop(),
+        })
+    }
+
+    /// Write the index page and pages of users, groups and projects to the directory
+    pub(crate) fn html(&self, output: &str) -> Result<()> {
+        let pages = self.pages()?;
+        for (path, page) in pages.iter() {
+            let path = Path::new(output).join(path);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, page)?;
+        }
+        OutMessage::message_info_clean(
+            format!("{} pages are written to {}", pages.len(), output).as_str(),
+        );
+        Ok(())
+    }
+
+    // Get every page by its path relative to the output directory
+    fn pages(&self) -> Result<BTreeMap<String, String>> {
+        let config = self.config_file.config();
+        let mut subjects: Vec<Subject> = self
+            .state
+            .iter()
+            .map(|(id, unit)| {
+                let (title, grants) = state_subject(config, *id, &unit.entity);
+                let mut access: Vec<(StateObject, AccessLevel)> = unit
+                    .projects
+                    .iter()
+                    .map(|(id, a)| (StateObject::Project(*id), *a))
+                    .chain(unit.namespaces.iter().map(|(id, a)| (StateObject::Group(*id), *a)))
+                    .collect();
+                access.sort_by_key(|(object, _)| *object);
+                Subject {
+                    id: *id,
+                    entity: unit.entity.clone(),
+                    title,
+                    grants,
+                    access,
+                }
+            })
+            .collect();
+        subjects.sort_by_key(|s| (rank(&s.entity), s.id));
+
+        let mut titles: BTreeMap<StateObject, String> = BTreeMap::new();
+        let mut access_to: BTreeMap<StateObject, Vec<(&Subject, AccessLevel)>> = BTreeMap::new();
+        for s in subjects.iter() {
+            for g in s.grants.iter() {
+                let t = titles.entry(g.object).or_default();
+                if g.title.len() > t.len() {
+                    *t = g.title.clone();
+                }
+            }
+            for (object, access) in s.access.iter() {
+                access_to.entry(*object).or_default().push((s, *access));
+            }
+        }
+        let object_title = |object: &StateObject| match titles.get(object) {
+            Some(t) if !t.is_empty() => format!("{} {}", kind(object), t),
+            _ => object.to_string(),
+        };
+
+        let mut pages: BTreeMap<String, String> = BTreeMap::new();
+        for s in subjects.iter() {
+            let Some(path) = subject_page(s) else {
+                continue;
+            };
+            let mut body = format!("<h1>{}</h1>\n", escape(&s.title));
+            if s.entity == EntityType::User {
+                if let Some(u) = config.users.iter().find(|u| u.id == s.id) {
+                    let teams: Vec<String> = config
+                        .user_teams(u)
+                        .iter()
+                        .map(|(t, role)| format!("{} ({})", t.name, role))
+                        .collect();
+                    body.push_str(&format!("<p>Teams: {}</p>\n", escape(&teams.join(", "))));
+                }
+            }
+            body.push_str("<h2>Access</h2>\n");
+            let rows: Vec<Vec<String>> = s
+                .access
+                .iter()
+                .map(|(object, access)| {
+                    vec![
+                        link(&object_page(object), &object_title(object), 1),
+                        access.to_string(),
+                        escape(&sources(&s.grants, *object, *access)),
+                    ]
+                })
+                .collect();
+            body.push_str(&table(&["object", "access", "source"], &rows));
+            // A group is an object too, so its page shows who has access to it
+            if s.entity == EntityType::Group {
+                body.push_str(&self.access_section(StateObject::Group(s.id), &access_to));
+            }
+            pages.insert(path, page(&s.title, 1, &body, self.last_sync()));
+        }
+        for object in access_to.keys() {
+            let path = object_page(object);
+            if pages.contains_key(&path) {
+                continue;
+            }
+            let title = object_title(object);
+            let body = format!(
+                "<h1>{}</h1>\n{}",
+                escape(&title),
+                self.access_section(*object, &access_to)
+            );
+            pages.insert(path, page(&title, 1, &body, self.last_sync()));
+        }
+        pages.insert(
+            "index.html".to_string(),
+            self.index(&subjects, &access_to, &object_title),
+        );
+        Ok(pages)
+    }
+
+    fn index(
+        &self,
+        subjects: &[Subject],
+        access_to: &BTreeMap<StateObject, Vec<(&Subject, AccessLevel)>>,
+        object_title: &dyn Fn(&StateObject) -> String,
+    ) -> String {
+        let config = self.config_file.config();
+        let mut body = "<h1>GitLab access</h1>\n".to_string();
+
+        body.push_str("<h2>Teams</h2>\n");
+        let rows: Vec<Vec<String>> = config
+            .teams
+            .iter()
+            .map(|t| {
+                let members: Vec<String> = config
+                    .users
+                    .iter()
+                    .filter(|u| config.user_teams(u).iter().any(|(team, _)| team.name == t.name))
+                    .map(|u| match subjects.iter().find(|s| s.entity == EntityType::User && s.id == u.id) {
+                        Some(s) => link(&subject_page(s).unwrap_or_default(), &s.title, 0),
+                        None => escape(&u.name),
+                    })
+                    .collect();
+                let objects: Vec<String> = t
+                    .projects
+                    .iter()
+                    .map(|p| (StateObject::Project(p.id), p.access_level))
+                    .chain(t.namespaces.iter().map(|n| (StateObject::Group(n.id), n.access_level)))
+                    .map(|(object, access)| {
+                        format!("{}: {}", link(&object_page(&object), &object_title(&object), 0), access)
+                    })
+                    .collect();
+                vec![
+                    escape(&t.name),
+                    escape(&t.includes.join(", ")),
+                    members.join("<br>"),
+                    objects.join("<br>"),
+                ]
+            })
+            .collect();
+        body.push_str(&table(&["team", "includes", "members", "grants"], &rows));
+
+        body.push_str("<h2>Users and groups</h2>\n");
+        let rows: Vec<Vec<String>> = subjects
+            .iter()
+            .map(|s| {
+                let title = match subject_page(s) {
+                    Some(path) => link(&path, &s.title, 0),
+                    None => escape(&s.title),
+                };
+                vec![title, s.access.len().to_string()]
+            })
+            .collect();
+        body.push_str(&table(&["subject", "objects"], &rows));
+
+        body.push_str("<h2>Projects and groups</h2>\n");
+        let rows: Vec<Vec<String>> = access_to
+            .iter()
+            .map(|(object, holders)| {
+                vec![
+                    link(&object_page(object), &object_title(object), 0),
+                    holders.len().to_string(),
+                ]
+            })
+            .collect();
+        body.push_str(&table(&["object", "subjects"], &rows));
+        page("GitLab access", 0, &body, self.last_sync())
+    }
+
+    fn access_section(
+        &self,
+        object: StateObject,
+        access_to: &BTreeMap<StateObject, Vec<(&Subject, AccessLevel)>>,
+    ) -> String {
+        let rows: Vec<Vec<String>> = access_to
+            .get(&object)
+            .map(|holders| {
+                holders
+                    .iter()
+                    .map(|(s, access)| {
+                        let title = match subject_page(s) {
+                            Some(path) => link(&path, &s.title, 1),
+                            None => escape(&s.title),
+                        };
+                        vec![
+                            title,
+                            access.to_string(),
+                            escape(&sources(&s.grants, object, *access)),
+                        ]
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        format!(
+            "<h2>Access to the {}</h2>\n{}",
+            kind(&object),
+            table(&["subject", "access", "source"], &rows)
+        )
+    }
+
+    fn last_sync(&self) -> String {
+        match &self.last {
+            Some(revision) => {
+                let current = serde_json::to_string(self.config_file.config())
+                    .map(|c| history::config_hash(&c))
+                    .unwrap_or_default();
+                let changed = match current == revision.config_hash {
+                    true => "",
+                    false => ", the config has been changed since then",
+                };
+                format!(
+                    "Last sync: revision {} at {}{}",
+                    revision.rev, revision.created, changed
+                )
+            }
+            None => "The config has not been synced yet".to_string(),
+        }
+    }
+}
+
+fn sources(grants: &[Grant], object: StateObject, access: AccessLevel) -> String {
+    let sources = access_sources(grants, object, access);
+    match sources.is_empty() {
+        true => "not in the config, e.g. a projects_in rule".to_string(),
+        false => sources.join(", "),
+    }
+}
+
+// Invites don't have pages, they're only listed
+fn subject_page(s: &Subject) -> Option<String> {
+    match s.entity {
+        EntityType::User => Some(format!("users/{}.html", s.id)),
+        EntityType::Group => Some(format!("groups/{}.html", s.id)),
+        EntityType::Invite => None,
+    }
+}
+
+fn object_page(object: &StateObject) -> String {
+    match object {
+        StateObject::Project(id) => format!("projects/{}.html", id),
+        StateObject::Group(id) => format!("groups/{}.html", id),
+    }
+}
+
+// Links are relative, so the site can be published anywhere
+fn link(path: &str, title: &str, depth: usize) -> String {
+    format!("<a href=\"{}{}\">{}</a>", "../".repeat(depth), path, escape(title))
+}
+
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::from("<table>\n<tr>");
+    for h in header {
+        out.push_str(&format!("<th>{}</th>", h));
+    }
+    out.push_str("</tr>\n");
+    for row in rows {
+        out.push_str("<tr class=\"item\">");
+        for cell in row {
+            out.push_str(&format!("<td>{}</td>", cell));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// Styles and the search are inlined, so pages don't fetch anything when they're opened
+fn page(title: &str, depth: usize, body: &str, last_sync: String) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; color: #222; }}
+table {{ border-collapse: collapse; margin-bottom: 2em; }}
+th, td {{ border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }}
+#search {{ padding: 0.4em; width: 20em; }}
+.meta {{ color: #666; }}
+</style>
+</head>
+<body>
+<p><a href="{root}index.html">Index</a> <input id="search" type="search" placeholder="Search"></p>
+<p class="meta">{last_sync}</p>
+{body}<script>
+document.getElementById("search").addEventListener("input", function (e) {{
+  var q = e.target.value.toLowerCase();
+  document.querySelectorAll(".item").forEach(function (row) {{
+    row.style.display = row.textContent.toLowerCase().indexOf(q) === -1 ? "none" : "";
+  }});
+}});
+</script>
+</body>
+</html>
+"#,
+        title = escape(title),
+        root = "../".repeat(depth),
+        last_sync = escape(&last_sync),
+        body = body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use super::ReportService;
+
+    #[test]
+    fn html_pages() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: backend
+      projects:
+        - id: 10
+          name: api
+          access_level: Developer
+  users:
+    - id: 1
+      name: <alice>
+      teams: [backend]
+state: '{"1":{"entity":"User","projects":{"10":"Developer"},"namespaces":{}}}'
+"#;
+        fs::write(&file_path, data).unwrap();
+        let svc = ReportService::new(file_path.to_string_lossy().to_string()).unwrap();
+        let pages = svc.pages().unwrap();
+        assert_eq!(
+            pages.keys().collect::<Vec<&String>>(),
+            vec!["index.html", "projects/10.html", "users/1.html"]
+        );
+        let user = &pages["users/1.html"];
+        assert!(user.contains("<h1>user 1 (&lt;alice&gt;)</h1>"));
+        assert!(user.contains(
+            "<td><a href=\"../projects/10.html\">project 10 (api)</a></td><td>Developer</td><td>team backend</td>"
+        ));
+        assert!(pages["index.html"].contains("The config has not been synced yet"));
+    }
+}
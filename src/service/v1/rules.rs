@@ -0,0 +1,223 @@
+use crate::{
+    gitlab::{apis::groups::GitlabGroupsApi, GitlabApiInterface},
+    output::out_message::OutMessage,
+    types::v1::{Config, Project, ProjectsIn},
+};
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind, Result},
+};
+
+// RulesExpander should be used to turn `projects_in` rules into the list of projects
+pub(crate) struct RulesExpander<'a, T: GitlabApiInterface> {
+    gitlab_api: &'a T,
+    // Projects that are found in a group, the key is (group id, recursive)
+    found: HashMap<(u64, bool), Vec<(u64, String)>>,
+}
+
+impl<'a, T: GitlabApiInterface> RulesExpander<'a, T> {
+    pub(crate) fn new(gitlab_api: &'a T) -> Self {
+        Self {
+            gitlab_api,
+            found: HashMap::new(),
+        }
+    }
+
+    /// Add projects produced by rules to teams, users and groups
+    pub(crate) fn expand(&mut self, config: &mut Config) -> Result<()> {
+        for t in config.teams.iter_mut() {
+            for r in t.projects_in.iter() {
+                let projects = self.expand_rule(r, format!("team {}", t.name))?;
+                t.projects.extend(projects);
+            }
+        }
+        for u in config.users.iter_mut() {
+            for r in u.projects_in.iter() {
+                let projects = self.expand_rule(r, format!("user {}", u.name))?;
+                u.projects.extend(projects);
+            }
+        }
+        for g in config.groups.iter_mut() {
+            for r in g.projects_in.iter() {
+                let projects = self.expand_rule(r, format!("group {}", g.name))?;
+                g.projects.extend(projects);
+            }
+        }
+        Ok(())
+    }
+
+    fn expand_rule(&mut self, rule: &ProjectsIn, owner: String) -> Result<Vec<Project>> {
+        let pattern = RulesExpander::<T>::regex(&rule.pattern, &owner)?;
+        let mut exclude: Vec<Regex> = Vec::new();
+        for e in rule.exclude.iter() {
+            exclude.push(RulesExpander::<T>::regex(e, &owner)?);
+        }
+
+        let mut projects: Vec<Project> = Vec::new();
+        for (id, name) in self.get_projects(rule.group, rule.recursive)?.iter() {
+            if !pattern.is_match(name) || exclude.iter().any(|e| e.is_match(name)) {
+                continue;
+            }
+            projects.push(Project {
+                name: name.to_string(),
+                id: *id,
+                path: Default::default(),
+                access_level: rule.access,
+            });
+        }
+
+        let names: Vec<String> = projects.iter().map(|p| p.name.clone()).collect();
+        OutMessage::message_info_clean(
+            format!(
+                "Rule projects_in {} for {} gives {} access to {} projects: {}",
+                rule.group,
+                owner,
+                rule.access,
+                names.len(),
+                names.join(", ")
+            )
+            .as_str(),
+        );
+        Ok(projects)
+    }
+
+    fn get_projects(&mut self, group: u64, recursive: bool) -> Result<Vec<(u64, String)>> {
+        if let Some(projects) = self.found.get(&(group, recursive)) {
+            return Ok(projects.clone());
+        }
+        let groups_api = self.gitlab_api.groups();
+        let head_group = groups_api.get_data_by_id(group)?;
+        let mut groups = vec![(head_group.id, head_group.name.clone())];
+        if recursive {
+            groups.extend(
+                groups_api
+                    .get_subgroups(head_group.name.clone(), head_group.id, true)?
+                    .iter()
+                    .map(|g| (g.id, g.name.clone())),
+            );
+        }
+
+        let mut projects: Vec<(u64, String)> = Vec::new();
+        for (id, name) in groups {
+            projects.extend(
+                groups_api
+                    .get_projects(name, id)?
+                    .iter()
+                    .map(|p| (p.id, p.name.clone())),
+            );
+        }
+        self.found.insert((group, recursive), projects.clone());
+        Ok(projects)
+    }
+
+    fn regex(pattern: &str, owner: &str) -> Result<Regex> {
+        Regex::new(pattern).map_err(|err| {
+            let err_msg = format!(
+                "Pattern '{}' in projects_in of the {} is not valid: {}",
+                pattern, owner, err
+            );
+            OutMessage::message_error(err_msg.as_str());
+            Error::new(ErrorKind::InvalidData, err_msg)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RulesExpander;
+    use crate::{
+        gitlab::{
+            apis::groups::MockGitlabGroupsApi,
+            types::group::Group,
+            GitlabApiMock, Project,
+        },
+        types::v1::Config,
+    };
+
+    fn group(id: u64) -> Group {
+        Group {
+            id,
+            name: format!("group{}", id),
+            web_url: String::new(),
+            full_path: String::new(),
+        }
+    }
+
+    fn projects(ids: impl Iterator<Item = u64>) -> Vec<Project> {
+        ids.map(|id| Project {
+            id,
+            name: format!("service-{}", id),
+            web_url: String::new(),
+        })
+        .collect()
+    }
+
+    // Group 1 has 30 projects and the subgroup 2 has 5 more. The mock returns every project at once,
+    // reading every page is tested by `projects_of_every_page` in gitlab/apis/groups.rs
+    fn gitlab_api() -> GitlabApiMock {
+        GitlabApiMock {
+            groups: Box::new(|| {
+                let mut groups_api = MockGitlabGroupsApi::new();
+                groups_api.expect_get_data_by_id().returning(|id| Ok(group(id)));
+                groups_api
+                    .expect_get_subgroups()
+                    .withf(|_, id, recursive| *id == 1 && *recursive)
+                    .returning(|_, _, _| Ok(vec![group(2)]));
+                groups_api.expect_get_projects().returning(|_, id| match id {
+                    1 => Ok(projects(1..=30)),
+                    2 => Ok(projects(31..=35)),
+                    _ => Ok(Vec::new()),
+                });
+                groups_api
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn expand_rules() {
+        let mut config: Config = serde_yaml::from_str(
+            r#"
+teams:
+  - name: backend
+    projects_in:
+      - group: 1
+        recursive: true
+        access: Developer
+  - name: qa
+    projects_in:
+      - group: 1
+        match: "^service-1"
+        exclude: ["-10$"]
+        access: Reporter
+users: []
+groups: []
+"#,
+        )
+        .unwrap();
+        let gitlab_api = gitlab_api();
+        RulesExpander::new(&gitlab_api).expand(&mut config).unwrap();
+
+        let ids = |team: usize| -> Vec<u64> { config.teams[team].projects.iter().map(|p| p.id).collect() };
+        assert_eq!(ids(0), (1..=35).collect::<Vec<u64>>());
+        assert_eq!(ids(1), vec![1, 11, 12, 13, 14, 15, 16, 17, 18, 19]);
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let mut config: Config = serde_yaml::from_str(
+            r#"
+teams:
+  - name: backend
+    projects_in:
+      - group: 1
+        match: "("
+        access: Developer
+"#,
+        )
+        .unwrap();
+        let gitlab_api = gitlab_api();
+        assert!(RulesExpander::new(&gitlab_api).expand(&mut config).is_err());
+    }
+}
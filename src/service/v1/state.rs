@@ -0,0 +1,365 @@
+use crate::{
+    output::out_message::OutMessage,
+    store::{get_store_type, history, LockInfo, Revision, Store},
+    types::v1::{AccessLevel, AccessUnit, ConfigFile, EntityType},
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{Error, ErrorKind, Result},
+};
+
+// StateService should be used to manage the state without syncing
+pub(crate) struct StateService {
+    config_file: ConfigFile,
+    file_path: String,
+}
+
+// Object is what the subject of the state has access to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum StateObject {
+    Project(u64),
+    Group(u64),
+}
+
+impl StateObject {
+    pub(crate) fn from_ids(project: Option<u64>, group: Option<u64>) -> Option<Self> {
+        match (project, group) {
+            (Some(id), _) => Some(StateObject::Project(id)),
+            (_, Some(id)) => Some(StateObject::Group(id)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for StateObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateObject::Project(id) => write!(f, "project {}", id),
+            StateObject::Group(id) => write!(f, "group {}", id),
+        }
+    }
+}
+
+impl StateService {
+    pub(crate) fn new(file_path: String) -> Result<Self> {
+        Ok(Self {
+            config_file: ConfigFile::read(file_path.clone())?,
+            file_path,
+        })
+    }
+
+    /// Get the store that is defined in the config file
+    pub(crate) fn store(&self) -> Result<Box<dyn Store>> {
+        get_store_type(self.config_file.state().to_string(), self.file_path.clone())
+    }
+
+    /// Read the state, an empty state is returned if it doesn't exist yet
+    pub(crate) fn get(&self) -> Result<HashMap<u64, AccessUnit>> {
+        StateService::get_from(self.store()?.as_ref())
+    }
+
+    fn get_from(store: &dyn Store) -> Result<HashMap<u64, AccessUnit>> {
+        match store.get() {
+            Ok(data) => Ok(data),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Move the state and its history to another store, `to` is `config`, `file:<path>` or an http(s) address.
+    /// The state is locked while they're copied and the config is changed, so a sync can't change them meanwhile
+    pub(crate) fn migrate(&mut self, to: String) -> Result<()> {
+        if to != "config" && !to.starts_with("file:") && !to.starts_with("http://") && !to.starts_with("https://") {
+            let err_msg = format!(
+                "Store {} is not supported, use `config`, `file:<path>` or an http(s) address",
+                to
+            );
+            OutMessage::message_error(err_msg.as_str());
+            return Err(Error::new(ErrorKind::InvalidInput, err_msg));
+        }
+        if self.config_file.state() == to {
+            OutMessage::message_info_with_alias("The state is already there, nothing to migrate");
+            return Ok(());
+        }
+        let source = self.store()?;
+        let lock = LockInfo::new("state migrate");
+        if let Err(err) = source.lock(&lock) {
+            OutMessage::message_error(err.to_string().as_str());
+            return Err(err);
+        }
+        let result = self.copy(source.as_ref(), &to);
+        source.unlock(&lock)?;
+        let (entities, revisions) = result?;
+        OutMessage::message_info_with_alias(
+            format!("State of {} entities and {} revisions are moved to {}", entities, revisions, to).as_str(),
+        );
+        Ok(())
+    }
+
+    // The config is read again under the lock, because a sync could change it before the lock is taken
+    fn copy(&mut self, source: &dyn Store, to: &str) -> Result<(usize, usize)> {
+        self.config_file = ConfigFile::read(self.file_path.clone())?;
+        let data = StateService::get_from(source)?;
+        let history = source.history()?;
+        let (state, destination) = match to {
+            "config" => (
+                serde_json::to_string(&data)?,
+                get_store_type(String::new(), self.file_path.clone())?,
+            ),
+            _ => (to.to_string(), get_store_type(to.to_string(), self.file_path.clone())?),
+        };
+        if to != "config" {
+            destination.write(data.clone())?;
+        }
+        if !history.is_empty() {
+            destination.write_history(&history)?;
+            // A server without a history address doesn't keep it
+            if destination.history()?.is_empty() {
+                OutMessage::message_warning(
+                    format!("{} doesn't keep the history, it starts from the next sync", to).as_str(),
+                );
+            }
+        }
+        self.config_file.set_state(state);
+        self.config_file.write(self.file_path.clone())?;
+        Ok((data.len(), history.len()))
+    }
+
+    /// Remove the lock of the state, whoever holds it
+    pub(crate) fn force_unlock(&self) -> Result<()> {
+        self.store()?.force_unlock()?;
+        OutMessage::message_info_with_alias("State is unlocked");
+        Ok(())
+    }
+
+    /// Print kept revisions of the state, the oldest one goes first
+    pub(crate) fn list(&self) -> Result<()> {
+        let history = self.store()?.history()?;
+        if history.is_empty() {
+            OutMessage::message_info_with_alias("There is no history yet, it's written by `gum sync`");
+            return Ok(());
+        }
+        for revision in history.iter() {
+            OutMessage::message_empty(
+                format!(
+                    "{}\t{}\tconfig {}\t{} entities",
+                    revision.rev,
+                    revision.created,
+                    revision.config_hash,
+                    revision.state.len()
+                )
+                .as_str(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Print a revision of the state, or the current state if the revision is not specified,
+    /// when the subject is set, only its access is printed
+    pub(crate) fn show(&self, rev: Option<u64>, subject: Option<u64>) -> Result<()> {
+        let data = match rev {
+            Some(rev) => self.revision(rev)?.state,
+            None => self.get()?,
+        };
+        let out = match subject {
+            Some(subject) => match data.get(&subject) {
+                Some(unit) => serde_json::to_string_pretty(unit)?,
+                None => return Err(StateService::subject_not_found(subject)),
+            },
+            None => serde_json::to_string_pretty(&data)?,
+        };
+        OutMessage::message_empty(out.as_str());
+        Ok(())
+    }
+
+    /// Forget the subject or its access to the object, GitLab is not touched
+    pub(crate) fn remove(&self, subject: u64, object: Option<StateObject>) -> Result<()> {
+        self.edit("state rm", |data| {
+            let object = match object {
+                Some(object) => object,
+                None => {
+                    if data.remove(&subject).is_none() {
+                        return Err(StateService::subject_not_found(subject));
+                    }
+                    return Ok(format!("Subject {} is removed from the state", subject));
+                }
+            };
+            let removed = match (data.get_mut(&subject), object) {
+                (None, _) => return Err(StateService::subject_not_found(subject)),
+                (Some(unit), StateObject::Project(id)) => unit.projects.remove(&id),
+                (Some(unit), StateObject::Group(id)) => unit.namespaces.remove(&id),
+            };
+            if removed.is_none() {
+                let err_msg = format!(
+                    "Subject {} doesn't have access to the {} in the state",
+                    subject, object
+                );
+                OutMessage::message_error(err_msg.as_str());
+                return Err(Error::new(ErrorKind::NotFound, err_msg));
+            }
+            Ok(format!(
+                "Access of {} to the {} is removed from the state",
+                subject, object
+            ))
+        })
+    }
+
+    /// Put the access that already exists in GitLab to the state, so gum starts managing it
+    pub(crate) fn add(
+        &self,
+        subject: u64,
+        entity: EntityType,
+        object: Option<StateObject>,
+        access: AccessLevel,
+    ) -> Result<()> {
+        let object = match object {
+            Some(object) => object,
+            None => {
+                let err_msg = "Either --project or --group must be provided";
+                OutMessage::message_error(err_msg);
+                return Err(Error::new(ErrorKind::InvalidInput, err_msg));
+            }
+        };
+        self.edit("state add", |data| {
+            let unit = data.entry(subject).or_insert_with(|| AccessUnit {
+                entity: entity.clone(),
+                ..Default::default()
+            });
+            if unit.entity != entity {
+                let err_msg = format!(
+                    "{} is a {:?} in the state, not a {:?}, remove it first to change the type",
+                    subject, unit.entity, entity
+                );
+                OutMessage::message_error(err_msg.as_str());
+                return Err(Error::new(ErrorKind::InvalidInput, err_msg));
+            }
+            match object {
+                StateObject::Project(id) => unit.projects.insert(id, access),
+                StateObject::Group(id) => unit.namespaces.insert(id, access),
+            };
+            Ok(format!(
+                "{:?} {} has {} access to the {} in the state",
+                unit.entity, subject, access, object
+            ))
+        })
+    }
+
+    // The state is locked while it's changed, so a sync can't overwrite the change
+    fn edit<F>(&self, operation: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut HashMap<u64, AccessUnit>) -> Result<String>,
+    {
+        let store = self.store()?;
+        let lock = LockInfo::new(operation);
+        if let Err(err) = store.lock(&lock) {
+            OutMessage::message_error(err.to_string().as_str());
+            return Err(err);
+        }
+        let result = StateService::get_from(store.as_ref()).and_then(|mut data| {
+            let msg = f(&mut data)?;
+            store.write(data)?;
+            Ok(msg)
+        });
+        store.unlock(&lock)?;
+        OutMessage::message_info_with_alias(result?.as_str());
+        Ok(())
+    }
+
+    fn subject_not_found(subject: u64) -> Error {
+        let err_msg = format!("Subject {} is not found in the state", subject);
+        OutMessage::message_error(err_msg.as_str());
+        Error::new(ErrorKind::NotFound, err_msg)
+    }
+
+    /// Print changes that turn the revision `a` into the revision `b`
+    pub(crate) fn diff(&self, a: u64, b: u64) -> Result<()> {
+        let changes = history::diff(&self.revision(a)?.state, &self.revision(b)?.state);
+        if changes.is_empty() {
+            OutMessage::message_info_with_alias("Revisions are the same");
+        }
+        for change in changes {
+            OutMessage::message_empty(change.as_str());
+        }
+        Ok(())
+    }
+
+    fn revision(&self, rev: u64) -> Result<Revision> {
+        let history = self.store()?.history()?;
+        match history::find(&history, rev) {
+            Ok(revision) => Ok(revision.clone()),
+            Err(err) => {
+                OutMessage::message_error(err.to_string().as_str());
+                Err(err)
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use super::{StateObject, StateService};
+    use crate::store::{history, FileStore, Store, StringStore};
+    use crate::types::v1::{AccessLevel, ConfigFile, EntityType};
+
+    #[test]
+    fn add_and_remove() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = "meta:\n  version: V2\nconfig:\n  users: []\nstate: file:state.json\n";
+        fs::write(&file_path, data).unwrap();
+        let svc = StateService::new(file_path.to_string_lossy().to_string()).unwrap();
+
+        svc.add(1, EntityType::User, Some(StateObject::Project(10)), AccessLevel::Developer)
+            .unwrap();
+        svc.add(1, EntityType::User, Some(StateObject::Group(20)), AccessLevel::Guest)
+            .unwrap();
+        assert!(svc
+            .add(1, EntityType::Group, Some(StateObject::Group(21)), AccessLevel::Guest)
+            .is_err());
+        svc.remove(1, Some(StateObject::Project(10))).unwrap();
+        assert!(svc.remove(1, Some(StateObject::Project(10))).is_err());
+
+        let store = FileStore::new(dir.path().join("state.json").to_string_lossy().to_string());
+        let state = store.get().unwrap();
+        assert!(state[&1].projects.is_empty());
+        assert_eq!(state[&1].namespaces[&20], AccessLevel::Guest);
+
+        svc.remove(1, None).unwrap();
+        assert!(store.get().unwrap().is_empty());
+        assert!(!dir.path().join("state.json.lock.info").exists());
+    }
+
+    #[test]
+    fn migrate_with_history() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml").to_string_lossy().to_string();
+        let state = r#"{"1":{"entity":"User","projects":{"10":"Developer"},"namespaces":{}}}"#;
+        let data = format!("meta:\n  version: V2\nconfig:\n  users: []\nstate: '{}'\n", state);
+        fs::write(&file_path, data).unwrap();
+        let config_store = StringStore::new(file_path.clone());
+        let mut revisions = Vec::new();
+        history::append(&mut revisions, config_store.get().unwrap(), "hash".to_string(), 10);
+        config_store.write_history(&revisions).unwrap();
+
+        let mut svc = StateService::new(file_path.clone()).unwrap();
+        svc.migrate("file:state.json".to_string()).unwrap();
+        let file_store = FileStore::new(dir.path().join("state.json").to_string_lossy().to_string());
+        assert_eq!(file_store.get().unwrap()[&1].projects[&10], AccessLevel::Developer);
+        assert_eq!(file_store.history().unwrap(), revisions);
+        assert_eq!(ConfigFile::read(file_path.clone()).unwrap().state(), "file:state.json");
+        assert!(!dir.path().join("gum-config.yaml.lock.info").exists());
+
+        // The history goes back with the state
+        fs::remove_file(dir.path().join("gum-config.yaml.history")).unwrap();
+        let mut svc = StateService::new(file_path.clone()).unwrap();
+        svc.migrate("config".to_string()).unwrap();
+        assert_eq!(config_store.get().unwrap(), file_store.get().unwrap());
+        assert_eq!(config_store.history().unwrap(), revisions);
+        assert!(!dir.path().join("state.json.lock.info").exists());
+    }
+}
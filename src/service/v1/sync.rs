@@ -1,33 +1,54 @@
 use crate::{
     gitlab::{
         apis::{
-            groups::GitlabGroupsApi, members::GitlabMembersApi, projects::GitlabProjectsApi,
-            users::GitlabUsersApi,
+            groups::GitlabGroupsApi, invitations::GitlabInvitationsApi, members::GitlabMembersApi,
+            projects::GitlabProjectsApi, users::GitlabUsersApi,
         },
+        types::users::User as GitlabUser,
         GitlabApiInterface,
     },
     output::{out_message::OutMessage, out_spinner::OutSpinner},
-    types::v1::{
-        self, AccessLevel, AccessUnit, Config, ConfigFile, EntityType, Group, Namespace, Project,
-        State, User,
+    store::{get_store_type, history, FileStore, LockInfo, Store},
+    types::{
+        policy::Policy,
+        v1::{
+            self, AccessLevel, AccessUnit, Config, ConfigFile, EntityType, Group, LockFile,
+            Namespace, Project, State, User, UserRef,
+        },
     },
 };
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     io::{Error, ErrorKind, Result},
+    path::Path,
 };
 
+use super::{report_violations, PathsResolver, PolicyChecker, RulesExpander, UsersResolver};
+
 // SyncService should be used to sync config with GitLab
 pub(crate) struct SyncService<T: GitlabApiInterface> {
     config_file: ConfigFile,
     config_path: String,
+    // Config with resolved paths, it's used for planning, but it's not written back
+    config: Config,
+    lock_file: LockFile,
     gitlab_api: T,
     state_source: String,
     state_destination: String,
     write_state: bool,
+    // How many previous states are kept
+    history_size: usize,
+    // Where the state is kept, it's defined by the config file
+    store: Option<Box<dyn Store>>,
+    // The lock that is held while the state is being changed
+    lock: Option<LockInfo>,
     state: v1::State,
     new_state: v1::State,
     actions: Vec<Actions>,
+    // Accepted invites are moved to users, so the config file must be written
+    config_changed: bool,
+    // The policy of the config with rules from its file
+    policy: Policy,
 }
 
 #[derive(Debug, Clone)]
@@ -59,77 +80,385 @@ impl<T: GitlabApiInterface> SyncService<T> {
         state_source: String,
         state_destination: String,
         write_state: bool,
+        history_size: usize,
     ) -> Self {
         let actions: Vec<Actions> = Vec::new();
         Self {
             config_file: ConfigFile::default(),
             config_path,
+            config: Config::default(),
+            lock_file: LockFile::default(),
             gitlab_api,
             state_source,
             state_destination,
             write_state,
+            history_size,
+            store: None,
+            lock: None,
             state: State::default(),
             new_state: State::default(),
             actions,
+            config_changed: false,
+            policy: Policy::default(),
         }
     }
 
     pub(crate) fn read_config(&mut self) -> Result<&mut Self> {
+        self.load_config()?;
+        self.store = Some(get_store_type(
+            self.config_file.state().to_string(),
+            self.config_path.clone(),
+        )?);
+        Ok(self)
+    }
+
+    fn load_config(&mut self) -> Result<()> {
         self.config_file = ConfigFile::read(self.config_path.clone())?;
+        self.config = self.config_file.config().clone();
+        self.policy = self.config_file.meta().policy().load(&self.config_path)?;
+        Ok(())
+    }
+
+    pub(crate) fn resolve_paths(&mut self) -> Result<&mut Self> {
+        let lock_file = LockFile::read(LockFile::file_name_for(&self.config_path))?;
+        let mut config = self.config.clone();
+        let resolved = PathsResolver::new(&self.gitlab_api, lock_file).resolve(&mut config)?;
+        self.lock_file.projects = resolved.projects;
+        self.lock_file.groups = resolved.groups;
+        self.config = config;
         Ok(self)
     }
 
-    pub(crate) fn create_states(&mut self) -> Result<&mut Self> {
-        if !self.state_source.is_empty() {
+    pub(crate) fn resolve_users(&mut self) -> Result<&mut Self> {
+        let lock_file = LockFile::read(LockFile::file_name_for(&self.config_path))?;
+        let mut config = self.config.clone();
+        self.lock_file.users = UsersResolver::new(&self.gitlab_api, lock_file).resolve(&mut config)?;
+        self.config = config;
+        Ok(self)
+    }
+
+    /// Check users against the policy of the config, it must be done after users are resolved
+    pub(crate) fn check_policy(&mut self) -> Result<&mut Self> {
+        let violations = PolicyChecker::new(&self.gitlab_api, &self.policy).check_users(&self.config)?;
+        report_violations(&violations)?;
+        Ok(self)
+    }
+
+    /// Check the planned state against rules for objects and access rules, it must be done before `apply`.
+    /// Rules are checked after projects_in rules are expanded, so expanded grants are checked too
+    pub(crate) fn check_rules(&mut self) -> Result<&mut Self> {
+        let mut checker = PolicyChecker::new(&self.gitlab_api, &self.policy);
+        let mut violations = checker.check_objects(&self.config)?;
+        violations.extend(checker.check_rules(&self.config, self.new_state.data())?);
+        report_violations(&violations)?;
+        Ok(self)
+    }
+
+    /// Check the planned state against every policy and report all violations at once
+    pub(crate) fn evaluate_policy(&mut self) -> Result<()> {
+        let mut checker = PolicyChecker::new(&self.gitlab_api, &self.policy);
+        let mut violations = checker.check_users(&self.config)?;
+        violations.extend(checker.check_objects(&self.config)?);
+        violations.extend(checker.check_rules(&self.config, self.new_state.data())?);
+        report_violations(&violations)?;
+        if violations.is_empty() {
+            OutMessage::message_info_with_alias("Config follows the policy");
+        }
+        Ok(())
+    }
+
+    /// Lock the state, so nobody else can sync the same config until it's unlocked.
+    /// The config is read again, because it could be changed by somebody who held the lock before
+    pub(crate) fn lock_state(&mut self) -> Result<&mut Self> {
+        let lock = LockInfo::new("sync");
+        if let Err(err) = self.store()?.lock(&lock) {
+            OutMessage::message_error(err.to_string().as_str());
+            return Err(err);
+        }
+        self.lock = Some(lock);
+        if let Err(err) = self.load_config() {
+            self.unlock_state();
+            return Err(err);
+        }
+        Ok(self)
+    }
+
+    /// Remove the lock that is taken by `lock_state`. A failure is only reported,
+    /// so it doesn't hide the result of what was done under the lock
+    pub(crate) fn unlock_state(&mut self) {
+        if let Some(lock) = self.lock.take() {
+            if let Err(err) = self.store().and_then(|store| store.unlock(&lock)) {
+                OutMessage::message_error(
+                    format!(
+                        "The state is not unlocked: {}, run `gum state unlock` to remove the lock",
+                        err
+                    )
+                    .as_str(),
+                );
+            }
+        }
+    }
+
+    /// Plan changes that bring GitLab back to a revision from the history
+    pub(crate) fn revert(&mut self, rev: u64) -> Result<&mut Self> {
+        self.read_state()?;
+        let history = self.store()?.history()?;
+        let revision = history::find(&history, rev)?;
+        OutMessage::message_info_with_alias(
+            format!(
+                "I will bring back the revision {} from {}",
+                revision.rev, revision.created
+            )
+            .as_str(),
+        );
+        self.new_state.set_data(revision.state.clone());
+        Ok(self)
+    }
+
+    fn read_state(&mut self) -> Result<()> {
+        let data = if !self.state_source.is_empty() {
             OutMessage::message_info_with_alias(
                 format!("I will try to use this file: {}", self.state_source.clone()).as_str(),
             );
-            self.state
-                .set_data(AccessUnit::read_from_file(self.state_source.clone())?);
+            FileStore::new(self.state_source.clone()).get()?
         } else {
-            if self.config_file.state() == "~" || self.config_file.state().is_empty() {
-                OutMessage::message_info_with_alias(
-                    "State is not found, I will generate a new one",
-                );
-            } else {
-                OutMessage::message_info_with_alias("State is found");
-                let data = match serde_json::from_str(self.config_file.state()) {
-                    Ok(state) => state,
-                    Err(err) => return Err(Error::new(ErrorKind::InvalidData, err)),
-                };
-                self.state.set_data(data);
+            match self.store()?.get() {
+                Ok(data) => {
+                    OutMessage::message_info_with_alias("State is found");
+                    data
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    OutMessage::message_info_with_alias(
+                        "State is not found, I will generate a new one",
+                    );
+                    HashMap::new()
+                }
+                Err(err) => return Err(Error::new(ErrorKind::InvalidData, err)),
             }
-        }
+        };
+        self.state.set_data(data);
+        Ok(())
+    }
 
-        let mut new_state: HashMap<u64, AccessUnit> = HashMap::new();
-        for u in self.config_file.config().users.iter().clone() {
-            new_state.insert(
-                u.id,
-                AccessUnit {
-                    projects: self.configure_projects(u, self.config_file.config().clone()),
-                    namespaces: self.configure_groups(u, self.config_file.config().clone()),
-                    entity: EntityType::User,
-                },
+    pub(crate) fn create_states(&mut self) -> Result<&mut Self> {
+        self.read_state()?;
+        self.accept_invites()?;
+        self.plan_state()
+    }
+
+    /// Turn invites that are not pending anymore into users.
+    /// Their access is moved in the state too, so nothing is changed in GitLab
+    fn accept_invites(&mut self) -> Result<()> {
+        let invites: Vec<(u64, AccessUnit)> = self
+            .state
+            .data()
+            .iter()
+            .filter(|(_, unit)| unit.entity == EntityType::Invite)
+            .map(|(id, unit)| (*id, unit.clone()))
+            .collect();
+        let invitations_api = self.gitlab_api.invitations();
+        for (id, unit) in invites {
+            if unit.projects.is_empty() && unit.namespaces.is_empty() {
+                continue;
+            }
+            let mut pending = false;
+            for pid in unit.projects.keys() {
+                pending |= invitations_api.is_invited_to_project(&unit.email, *pid)?;
+            }
+            for nid in unit.namespaces.keys() {
+                pending |= invitations_api.is_invited_to_group(&unit.email, *nid)?;
+            }
+            if pending {
+                continue;
+            }
+            // The invite is gone, but it may be revoked or expired instead of accepted,
+            // so the access is moved only where the user has become a member
+            let user_ref = UserRef::Email(unit.email.clone());
+            let user = match UsersResolver::new(&self.gitlab_api, LockFile::default()).find(&user_ref) {
+                Ok(user) => Some(user),
+                Err(err) if err.kind() == ErrorKind::NotFound => None,
+                Err(err) => return Err(err),
+            };
+            let mut projects: HashMap<u64, AccessLevel> = HashMap::new();
+            let mut namespaces: HashMap<u64, AccessLevel> = HashMap::new();
+            if let Some(user) = &user {
+                let members_api = self.gitlab_api.members();
+                for (pid, access) in unit.projects.iter() {
+                    if members_api.is_member_of_project(user.id, *pid)? {
+                        projects.insert(*pid, *access);
+                    }
+                }
+                for (nid, access) in unit.namespaces.iter() {
+                    if members_api.is_member_of_group(user.id, *nid)? {
+                        namespaces.insert(*nid, *access);
+                    }
+                }
+            }
+
+            // It's removed from the state, so it's invited again if it's still in the config
+            self.state.data_mut().remove(&id);
+            let user = match user {
+                Some(user) if !projects.is_empty() || !namespaces.is_empty() => user,
+                _ => {
+                    OutMessage::message_warning(
+                        format!(
+                            "The invite of {} is not pending anymore, but nobody has accepted it, it's revoked or expired",
+                            unit.email
+                        )
+                        .as_str(),
+                    );
+                    continue;
+                }
+            };
+            OutMessage::message_info_with_alias(
+                format!(
+                    "{} has accepted the invite, it's {} now",
+                    unit.email, user.username
+                )
+                .as_str(),
             );
+
+            let accepted = self
+                .state
+                .data_mut()
+                .entry(user.id)
+                .or_insert_with(|| AccessUnit::new_simple(EntityType::User));
+            for (pid, access) in projects {
+                let entry = accepted.projects.entry(pid).or_insert(access);
+                *entry = entry.higher(access);
+            }
+            for (nid, access) in namespaces {
+                let entry = accepted.namespaces.entry(nid).or_insert(access);
+                *entry = entry.higher(access);
+            }
+
+            SyncService::<T>::accept_invite(&mut self.config, &unit.email, &user);
+            SyncService::<T>::accept_invite(self.config_file.config_mut(), &unit.email, &user);
+            self.config_changed = true;
         }
-        for u in self.config_file.config().groups.iter().clone() {
-            new_state.insert(
-                u.id,
-                AccessUnit {
-                    projects: self.gr_configure_projects(u),
-                    namespaces: self.gr_configure_groups(u),
-                    entity: EntityType::Group,
-                },
-            );
+        Ok(())
+    }
+
+    // Replace the invite with the user in the config, the user may be there already
+    fn accept_invite(config: &mut Config, email: &str, user: &GitlabUser) {
+        let pos = match config
+            .invites
+            .iter()
+            .position(|i| i.email.eq_ignore_ascii_case(email))
+        {
+            Some(pos) => pos,
+            None => return,
+        };
+        let invite = config.invites.remove(pos);
+        match config.users.iter_mut().find(|u| u.id == user.id) {
+            Some(u) => {
+                for t in invite.teams {
+                    if !u.teams.contains(&t) {
+                        u.teams.push(t);
+                    }
+                }
+                u.projects.extend(invite.projects);
+                u.namespaces.extend(invite.namespaces);
+            }
+            None => config.users.push(User {
+                id: user.id,
+                username: user.username.clone(),
+                name: user.name.clone(),
+                teams: invite.teams,
+                projects: invite.projects,
+                namespaces: invite.namespaces,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Rebuild the state from the access that subjects of the config really have in GitLab,
+    /// it's used when the state is lost, so the state is not read at all
+    pub(crate) fn refresh_state(&mut self) -> Result<&mut Self> {
+        self.plan_state()?;
+        let planned = self.new_state.data().clone();
+        let mut projects: BTreeSet<u64> = BTreeSet::new();
+        let mut namespaces: BTreeSet<u64> = BTreeSet::new();
+        for unit in planned.values() {
+            projects.extend(unit.projects.keys());
+            namespaces.extend(unit.namespaces.keys());
+        }
+
+        let mut state: HashMap<u64, AccessUnit> = planned
+            .iter()
+            .map(|(id, unit)| (*id, AccessUnit::new_simple(unit.entity.clone())))
+            .collect();
+        let is = |state: &HashMap<u64, AccessUnit>, id: u64, entity: EntityType| {
+            state.get(&id).is_some_and(|unit| unit.entity == entity)
+        };
+        // Members without access can't be kept in the state, so they are skipped
+        let access_of = |access_level: gitlab::AccessLevel, member: String| {
+            let access = AccessLevel::from_gitlab_member(access_level);
+            if access.is_none() {
+                OutMessage::message_warning(format!("{} has no access, it's skipped", member).as_str());
+            }
+            access
+        };
+        let projects_api = self.gitlab_api.projects();
+        for pid in projects {
+            for m in projects_api.get_members(format!("project {}", pid), pid)? {
+                if is(&state, m.id, EntityType::User) {
+                    if let Some(access) = access_of(m.access_level, format!("User {} in the project {}", m.id, pid)) {
+                        state.get_mut(&m.id).unwrap().projects.insert(pid, access);
+                    }
+                }
+            }
+            for g in projects_api.get_groups_shared_with(pid)? {
+                if is(&state, g.group_id, EntityType::Group) {
+                    let member = format!("Group {} in the project {}", g.group_id, pid);
+                    if let Some(access) = access_of(g.group_access_level, member) {
+                        state.get_mut(&g.group_id).unwrap().projects.insert(pid, access);
+                    }
+                }
+            }
+        }
+        let groups_api = self.gitlab_api.groups();
+        for nid in namespaces {
+            for m in groups_api.get_members(format!("group {}", nid), nid)? {
+                if is(&state, m.id, EntityType::User) {
+                    if let Some(access) = access_of(m.access_level, format!("User {} in the group {}", m.id, nid)) {
+                        state.get_mut(&m.id).unwrap().namespaces.insert(nid, access);
+                    }
+                }
+            }
+            for g in groups_api.git_groups_shared_with(nid)? {
+                if is(&state, g.group_id, EntityType::Group) {
+                    let member = format!("Group {} in the group {}", g.group_id, nid);
+                    if let Some(access) = access_of(g.group_access_level, member) {
+                        state.get_mut(&g.group_id).unwrap().namespaces.insert(nid, access);
+                    }
+                }
+            }
         }
 
-        self.new_state.set_data(new_state);
+        let changes = history::diff(&state, &planned);
+        if changes.is_empty() {
+            OutMessage::message_info_with_alias("GitLab is the same as the config");
+        } else {
+            OutMessage::message_info_with_alias("GitLab differs from the config, the next sync will do this:");
+            for change in changes {
+                OutMessage::message_empty(change.as_str());
+            }
+        }
+        self.state.set_data(state);
         Ok(self)
     }
 
-    pub(crate) fn update_state(&mut self) -> Result<&mut Self> {
-        self.config_file
-            .set_state(serde_json::to_string(self.state.data())?);
+    /// Get the config with resolved paths and expanded rules, and the state that is planned by it
+    pub(crate) fn planned(&self) -> (&Config, &HashMap<u64, AccessUnit>) {
+        (&self.config, self.new_state.data())
+    }
+
+    /// Build the state that is defined by the config
+    pub(crate) fn plan_state(&mut self) -> Result<&mut Self> {
+        warn_teams(&self.config);
+        RulesExpander::new(&self.gitlab_api).expand(&mut self.config)?;
+        self.new_state.set_data(expand_state(&self.config));
         Ok(self)
     }
 
@@ -139,6 +468,7 @@ impl<T: GitlabApiInterface> SyncService<T> {
             let projects_api = self.gitlab_api.projects();
             let groups_api = self.gitlab_api.groups();
             let members_api = self.gitlab_api.members();
+            let invitations_api = self.gitlab_api.invitations();
 
             let subject_name = match a.subject_entity_type {
                 EntityType::User => match users_api.get_data_by_id(a.subject_entity_id) {
@@ -149,6 +479,14 @@ impl<T: GitlabApiInterface> SyncService<T> {
                     Ok(r) => r.name,
                     Err(err) => return Err(err),
                 },
+                // Invites are known by their emails only
+                EntityType::Invite => self
+                    .new_state
+                    .data()
+                    .get(&a.subject_entity_id)
+                    .or_else(|| self.state.data().get(&a.subject_entity_id))
+                    .map(|unit| unit.email.clone())
+                    .unwrap_or_default(),
             };
 
             match a.object_entity_type {
@@ -184,6 +522,21 @@ impl<T: GitlabApiInterface> SyncService<T> {
                                             }
                                         }
                                     }
+                                    EntityType::Invite => {
+                                        match invitations_api.invite_to_project(
+                                            &subject_name,
+                                            a.object_entity_id,
+                                            a.access,
+                                        ) {
+                                            Err(err) => {
+                                                spinner.spinner_failure(err.to_string());
+                                                return Err(err);
+                                            }
+                                            Ok(msg) => {
+                                                spinner.spinner_success(msg.to_string());
+                                            }
+                                        }
+                                    }
                                     EntityType::Group => {
                                         match members_api.add_group_to_project(
                                             a.subject_entity_id,
@@ -203,15 +556,15 @@ impl<T: GitlabApiInterface> SyncService<T> {
                             } else {
                                 spinner.spinner_close();
                             }
-                            if !self.state.data_mut().contains_key(&a.subject_entity_id) {
-                                self.state.data_mut().insert(
-                                    a.subject_entity_id,
-                                    AccessUnit::new_simple(a.subject_entity_type.clone()),
-                                );
-                            };
-                            if let Some(x) = self.state.data_mut().get_mut(&a.subject_entity_id) {
-                                x.projects.insert(a.object_entity_id, a.access);
+                            let x = self
+                                .state
+                                .data_mut()
+                                .entry(a.subject_entity_id)
+                                .or_insert_with(|| AccessUnit::new_simple(a.subject_entity_type.clone()));
+                            if a.subject_entity_type == EntityType::Invite {
+                                x.email = subject_name.clone();
                             }
+                            x.projects.insert(a.object_entity_id, a.access);
                         }
                         Action::Delete => {
                             let spinner = OutSpinner::spinner_start(
@@ -234,6 +587,20 @@ impl<T: GitlabApiInterface> SyncService<T> {
                                             }
                                         }
                                     }
+                                    EntityType::Invite => {
+                                        match invitations_api.remove_invite_from_project(
+                                            &subject_name,
+                                            a.object_entity_id,
+                                        ) {
+                                            Err(err) => {
+                                                spinner.spinner_failure(err.to_string());
+                                                return Err(err);
+                                            }
+                                            Ok(msg) => {
+                                                spinner.spinner_success(msg.to_string());
+                                            }
+                                        }
+                                    }
                                     EntityType::Group => {
                                         match members_api.remove_group_from_project(
                                             a.subject_entity_id,
@@ -292,6 +659,21 @@ impl<T: GitlabApiInterface> SyncService<T> {
                                             }
                                         }
                                     }
+                                    EntityType::Invite => {
+                                        match invitations_api.edit_invite_in_project(
+                                            &subject_name,
+                                            a.object_entity_id,
+                                            a.access,
+                                        ) {
+                                            Err(err) => {
+                                                spinner.spinner_failure(err.to_string());
+                                                return Err(err);
+                                            }
+                                            Ok(msg) => {
+                                                spinner.spinner_success(msg.to_string());
+                                            }
+                                        }
+                                    }
                                     EntityType::Group => {
                                         match members_api.remove_group_from_project(
                                             a.subject_entity_id,
@@ -359,6 +741,21 @@ impl<T: GitlabApiInterface> SyncService<T> {
                                             }
                                         }
                                     }
+                                    EntityType::Invite => {
+                                        match invitations_api.invite_to_group(
+                                            &subject_name,
+                                            a.object_entity_id,
+                                            a.access,
+                                        ) {
+                                            Err(err) => {
+                                                spinner.spinner_failure(err.to_string());
+                                                return Err(err);
+                                            }
+                                            Ok(msg) => {
+                                                spinner.spinner_success(msg.to_string());
+                                            }
+                                        }
+                                    }
                                     EntityType::Group => {
                                         match members_api.add_group_to_namespace(
                                             a.subject_entity_id,
@@ -378,15 +775,15 @@ impl<T: GitlabApiInterface> SyncService<T> {
                             } else {
                                 spinner.spinner_close();
                             }
-                            if !self.state.data_mut().contains_key(&a.subject_entity_id) {
-                                self.state.data_mut().insert(
-                                    a.subject_entity_id,
-                                    AccessUnit::new_simple(a.subject_entity_type.clone()),
-                                );
-                            };
-                            if let Some(x) = self.state.data_mut().get_mut(&a.subject_entity_id) {
-                                x.namespaces.insert(a.object_entity_id, a.access);
+                            let x = self
+                                .state
+                                .data_mut()
+                                .entry(a.subject_entity_id)
+                                .or_insert_with(|| AccessUnit::new_simple(a.subject_entity_type.clone()));
+                            if a.subject_entity_type == EntityType::Invite {
+                                x.email = subject_name.clone();
                             }
+                            x.namespaces.insert(a.object_entity_id, a.access);
                         }
                         Action::Delete => {
                             let spinner = OutSpinner::spinner_start(
@@ -409,6 +806,20 @@ impl<T: GitlabApiInterface> SyncService<T> {
                                             }
                                         }
                                     }
+                                    EntityType::Invite => {
+                                        match invitations_api.remove_invite_from_group(
+                                            &subject_name,
+                                            a.object_entity_id,
+                                        ) {
+                                            Err(err) => {
+                                                spinner.spinner_failure(err.to_string());
+                                                return Err(err);
+                                            }
+                                            Ok(msg) => {
+                                                spinner.spinner_success(msg.to_string());
+                                            }
+                                        }
+                                    }
                                     EntityType::Group => {
                                         match members_api.remove_group_from_namespace(
                                             a.subject_entity_id,
@@ -467,6 +878,21 @@ impl<T: GitlabApiInterface> SyncService<T> {
                                             }
                                         }
                                     }
+                                    EntityType::Invite => {
+                                        match invitations_api.edit_invite_in_group(
+                                            &subject_name,
+                                            a.object_entity_id,
+                                            a.access,
+                                        ) {
+                                            Err(err) => {
+                                                spinner.spinner_failure(err.to_string());
+                                                return Err(err);
+                                            }
+                                            Ok(msg) => {
+                                                spinner.spinner_success(msg.to_string());
+                                            }
+                                        }
+                                    }
                                     EntityType::Group => {
                                         match members_api.remove_group_from_namespace(
                                             a.subject_entity_id,
@@ -511,10 +937,7 @@ impl<T: GitlabApiInterface> SyncService<T> {
 
     pub(crate) fn write_state(&self, dry: bool) -> Result<()> {
         if self.write_state {
-            match AccessUnit::write_to_file(
-                self.state.data().clone(),
-                self.state_destination.clone(),
-            ) {
+            match FileStore::new(self.state_destination.clone()).write(self.state.data().clone()) {
                 Ok(_) => {
                     let msg = format!(
                         "State is saved, check it out\n $ cat {}",
@@ -526,120 +949,43 @@ impl<T: GitlabApiInterface> SyncService<T> {
             };
         }
         if !dry {
-            match self.config_file.write(self.config_path.clone()) {
-                Ok(_) => Ok(()),
-                Err(err) => Err(err),
-            }
-        } else {
-            Ok(())
-        }
-    }
-
-    fn configure_projects(&self, u: &User, c: Config) -> HashMap<u64, AccessLevel> {
-        let mut projects_map: HashMap<u64, AccessLevel> = HashMap::new();
-        let mut projects: Vec<Project> = u.projects.clone();
-        for t in c.teams.iter() {
-            if u.teams.contains(&t.name.to_string()) || t.name == "default" {
-                projects.extend(t.projects.clone());
-            }
-        }
-
-        let mut keys: HashMap<u64, AccessLevel> = HashMap::new();
-        for p in projects.iter() {
-            if !keys.contains_key(&p.id) {
-                keys.insert(p.id, p.clone().access_level);
-            } else {
-                keys.insert(
-                    p.id,
-                    self.higher_access(p.access_level, *keys.get(&p.id).unwrap()),
-                );
-            }
-        }
-        projects.clear();
-        for (k, v) in keys.iter() {
-            projects_map.insert(*k, *v);
-        }
-        return projects_map;
-    }
-
-    pub(crate) fn configure_groups(&self, u: &User, c: Config) -> HashMap<u64, AccessLevel> {
-        let mut groups_map: HashMap<u64, AccessLevel> = HashMap::new();
-        let mut groups: Vec<Namespace> = u.namespaces.clone();
-        for t in c.teams.iter() {
-            if u.teams.contains(&t.name.to_string()) || t.name == "default" {
-                groups.extend(t.namespaces.clone());
+            if self.config_changed {
+                self.config_file.write(self.config_path.clone())?;
+                OutMessage::message_info_clean("Accepted invites are moved to users in the config");
             }
-        }
-
-        let mut keys: HashMap<u64, AccessLevel> = HashMap::new();
-        for g in groups.iter() {
-            if !keys.contains_key(&g.id) {
-                keys.insert(g.id, g.clone().access_level);
-            } else {
-                keys.insert(
-                    g.id,
-                    self.higher_access(g.access_level, *keys.get(&g.id).unwrap()),
-                );
+            let lock_path = LockFile::file_name_for(&self.config_path);
+            if !self.lock_file.is_empty() || Path::new(&lock_path).exists() {
+                self.lock_file.write(lock_path)?;
             }
-        }
-        groups.clear();
-        for (k, v) in keys.iter() {
-            groups_map.insert(*k, *v);
-        }
-        groups_map
-    }
+            self.store()?.write(self.state.data().clone())?;
 
-    fn gr_configure_groups(&self, u: &Group) -> HashMap<u64, AccessLevel> {
-        let mut groups_map: HashMap<u64, AccessLevel> = HashMap::new();
-        let mut groups: Vec<Namespace> = u.namespaces.clone();
-        let mut keys: HashMap<u64, AccessLevel> = HashMap::new();
-        for g in groups.iter() {
-            if !keys.contains_key(&g.id) {
-                keys.insert(g.id, g.clone().access_level);
-            } else {
-                keys.insert(
-                    g.id,
-                    self.higher_access(g.access_level, *keys.get(&g.id).unwrap()),
-                );
-            }
-        }
-        groups.clear();
-        for (k, v) in keys.iter() {
-            groups_map.insert(*k, *v);
-        }
-        groups_map
-    }
-
-    fn gr_configure_projects(&self, u: &Group) -> HashMap<u64, AccessLevel> {
-        let mut projects_map: HashMap<u64, AccessLevel> = HashMap::new();
-        let mut projects: Vec<Project> = u.projects.clone();
-        let mut keys: HashMap<u64, AccessLevel> = HashMap::new();
-        for p in projects.iter() {
-            if !keys.contains_key(&p.id) {
-                keys.insert(p.id, p.clone().access_level);
-            } else {
-                keys.insert(
-                    p.id,
-                    self.higher_access(p.access_level, *keys.get(&p.id).unwrap()),
-                );
+            let mut history = self.store()?.history()?;
+            let config_hash = history::config_hash(&serde_json::to_string(self.config_file.config())?);
+            match history::append(
+                &mut history,
+                self.state.data().clone(),
+                config_hash,
+                self.history_size,
+            ) {
+                Some(rev) => {
+                    self.store()?.write_history(&history)?;
+                    OutMessage::message_info_clean(format!("State is saved as the revision {}", rev).as_str());
+                }
+                None => OutMessage::message_info_clean("State is not changed, no revision is added to the history"),
             }
+            Ok(())
+        } else {
+            Ok(())
         }
-        projects.clear();
-        for (k, v) in keys.iter() {
-            projects_map.insert(*k, *v);
-        }
-        return projects_map;
     }
 
-    fn higher_access(&self, a1: AccessLevel, a2: AccessLevel) -> AccessLevel {
-        if a1 == AccessLevel::Maintainer || a2 == AccessLevel::Maintainer {
-            AccessLevel::Maintainer
-        } else if a1 == AccessLevel::Developer || a2 == AccessLevel::Developer {
-            AccessLevel::Developer
-        } else if a1 == AccessLevel::Reporter || a2 == AccessLevel::Reporter {
-            AccessLevel::Reporter
-        } else {
-            AccessLevel::Guest
+    fn store(&self) -> Result<&dyn Store> {
+        match &self.store {
+            Some(store) => Ok(store.as_ref()),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                "The config must be read before the state",
+            )),
         }
     }
 
@@ -801,3 +1147,376 @@ impl<T: GitlabApiInterface> SyncService<T> {
         }
     }
 }
+
+/// Teams that don't exist are skipped when the state is planned, only `gum validate` fails on them
+pub(crate) fn warn_teams(config: &Config) {
+    let problems = config.check_teams();
+    for p in problems.iter() {
+        OutMessage::message_warning(p);
+    }
+    if !problems.is_empty() {
+        OutMessage::message_warning("Teams are not valid, run `gum validate` to check the config");
+    }
+}
+
+/// Build the state that is defined by the config, paths and rules must be resolved before
+pub(crate) fn expand_state(config: &Config) -> HashMap<u64, AccessUnit> {
+    let mut new_state: HashMap<u64, AccessUnit> = HashMap::new();
+    for u in config.users.iter().clone() {
+        new_state.insert(
+            u.id,
+            AccessUnit {
+                projects: configure_projects(u, config),
+                namespaces: configure_groups(u, config),
+                entity: EntityType::User,
+                email: String::new(),
+            },
+        );
+    }
+    for i in config.invites.iter() {
+        let u = i.as_user();
+        new_state.insert(
+            i.state_id(),
+            AccessUnit {
+                projects: configure_projects(&u, config),
+                namespaces: configure_groups(&u, config),
+                entity: EntityType::Invite,
+                email: i.email.clone(),
+            },
+        );
+    }
+    for u in config.groups.iter().clone() {
+        new_state.insert(
+            u.id,
+            AccessUnit {
+                projects: gr_configure_projects(u),
+                namespaces: gr_configure_groups(u),
+                entity: EntityType::Group,
+                email: String::new(),
+            },
+        );
+    }
+    new_state
+}
+
+fn configure_projects(u: &User, c: &Config) -> HashMap<u64, AccessLevel> {
+    let mut projects_map: HashMap<u64, AccessLevel> = HashMap::new();
+    let mut projects: Vec<Project> = u.projects.clone();
+    for (t, role) in c.user_teams(u) {
+        projects.extend(t.projects.iter().map(|p| Project {
+            access_level: role.access(p.access_level),
+            ..p.clone()
+        }));
+    }
+
+    let mut keys: HashMap<u64, AccessLevel> = HashMap::new();
+    for p in projects.iter() {
+        if !keys.contains_key(&p.id) {
+            keys.insert(p.id, p.clone().access_level);
+        } else {
+            keys.insert(
+                p.id,
+                p.access_level.higher(*keys.get(&p.id).unwrap()),
+            );
+        }
+    }
+    projects.clear();
+    for (k, v) in keys.iter() {
+        projects_map.insert(*k, *v);
+    }
+    projects_map
+}
+
+fn configure_groups(u: &User, c: &Config) -> HashMap<u64, AccessLevel> {
+    let mut groups_map: HashMap<u64, AccessLevel> = HashMap::new();
+    let mut groups: Vec<Namespace> = u.namespaces.clone();
+    for (t, role) in c.user_teams(u) {
+        groups.extend(t.namespaces.iter().map(|n| Namespace {
+            access_level: role.access(n.access_level),
+            ..n.clone()
+        }));
+    }
+
+    let mut keys: HashMap<u64, AccessLevel> = HashMap::new();
+    for g in groups.iter() {
+        if !keys.contains_key(&g.id) {
+            keys.insert(g.id, g.clone().access_level);
+        } else {
+            keys.insert(
+                g.id,
+                g.access_level.higher(*keys.get(&g.id).unwrap()),
+            );
+        }
+    }
+    groups.clear();
+    for (k, v) in keys.iter() {
+        groups_map.insert(*k, *v);
+    }
+    groups_map
+}
+
+fn gr_configure_groups(u: &Group) -> HashMap<u64, AccessLevel> {
+    let mut groups_map: HashMap<u64, AccessLevel> = HashMap::new();
+    let mut groups: Vec<Namespace> = u.namespaces.clone();
+    let mut keys: HashMap<u64, AccessLevel> = HashMap::new();
+    for g in groups.iter() {
+        if !keys.contains_key(&g.id) {
+            keys.insert(g.id, g.clone().access_level);
+        } else {
+            keys.insert(
+                g.id,
+                g.access_level.higher(*keys.get(&g.id).unwrap()),
+            );
+        }
+    }
+    groups.clear();
+    for (k, v) in keys.iter() {
+        groups_map.insert(*k, *v);
+    }
+    groups_map
+}
+
+fn gr_configure_projects(u: &Group) -> HashMap<u64, AccessLevel> {
+    let mut projects_map: HashMap<u64, AccessLevel> = HashMap::new();
+    let mut projects: Vec<Project> = u.projects.clone();
+    let mut keys: HashMap<u64, AccessLevel> = HashMap::new();
+    for p in projects.iter() {
+        if !keys.contains_key(&p.id) {
+            keys.insert(p.id, p.clone().access_level);
+        } else {
+            keys.insert(
+                p.id,
+                p.access_level.higher(*keys.get(&p.id).unwrap()),
+            );
+        }
+    }
+    projects.clear();
+    for (k, v) in keys.iter() {
+        projects_map.insert(*k, *v);
+    }
+    projects_map
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use super::SyncService;
+    use crate::gitlab::apis::{
+        invitations::MockGitlabInvitationsApi, members::MockGitlabMembersApi, projects::MockGitlabProjectsApi,
+        users::MockGitlabUsersApi,
+    };
+    use crate::gitlab::{types::users::User, CustomMember, GitlabApi, GitlabApiMock};
+    use crate::types::v1::{AccessLevel, ConfigFile, Invite};
+
+    #[test]
+    fn state_is_read_under_lock() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml").to_string_lossy().to_string();
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams: []
+  users:
+    - id: 1
+      name: alice
+      projects:
+        - id: 10
+          access_level: Maintainer
+state: '{"1":{"entity":"User","projects":{"10":"Developer"},"namespaces":{}}}'
+"#;
+        fs::write(&file_path, data).unwrap();
+        let mut svc = SyncService::new(
+            file_path.clone(),
+            GitlabApiMock::default(),
+            String::new(),
+            String::new(),
+            false,
+            10,
+        );
+        svc.read_config().unwrap();
+
+        // Another sync saves its state while this one is waiting for the lock
+        let mut config_file = ConfigFile::read(file_path.clone()).unwrap();
+        config_file.set_state(r#"{"1":{"entity":"User","projects":{"10":"Maintainer"},"namespaces":{}}}"#.to_string());
+        config_file.write(file_path.clone()).unwrap();
+
+        svc.lock_state().unwrap();
+        let result = svc.create_states().and_then(|svc| svc.compare()).map(|_| ());
+        svc.unlock_state();
+        result.unwrap();
+        assert_eq!(svc.state.data()[&1].projects[&10], AccessLevel::Maintainer);
+        assert!(svc.actions.is_empty());
+    }
+
+    #[test]
+    fn invites_are_moved_only_when_accepted() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml").to_string_lossy().to_string();
+        let id = |email: &str| {
+            Invite {
+                email: email.to_string(),
+                ..Default::default()
+            }
+            .state_id()
+        };
+        let data = format!(
+            r#"
+meta:
+  version: V2
+config:
+  teams: []
+  users: []
+  invites:
+    - email: bob@example.com
+      projects:
+        - id: 10
+          access_level: Developer
+        - id: 11
+          access_level: Developer
+    - email: carol@example.com
+      projects:
+        - id: 10
+          access_level: Developer
+state: '{{"{}":{{"entity":"Invite","projects":{{"10":"Developer","11":"Developer"}},"namespaces":{{}},"email":"bob@example.com"}},"{}":{{"entity":"Invite","projects":{{"10":"Developer"}},"namespaces":{{}},"email":"carol@example.com"}}}}'
+"#,
+            id("bob@example.com"),
+            id("carol@example.com")
+        );
+        fs::write(&file_path, data).unwrap();
+        // bob has joined only the project 10, the invite of carol is revoked
+        let gitlab_api = GitlabApiMock {
+            invitations: Box::new(|| {
+                let mut invitations_api = MockGitlabInvitationsApi::new();
+                invitations_api.expect_is_invited_to_project().returning(|_, _| Ok(false));
+                invitations_api
+            }),
+            users: Box::new(|| {
+                let mut users_api = MockGitlabUsersApi::new();
+                users_api.expect_find_by_email().returning(|email| match email {
+                    "bob@example.com" => Ok(vec![User {
+                        id: 2,
+                        username: "bob".to_string(),
+                        name: "bob".to_string(),
+                        web_url: String::new(),
+                        email: None,
+                        public_email: Some(email.to_string()),
+                        two_factor_enabled: None,
+                    }]),
+                    _ => Ok(Vec::new()),
+                });
+                users_api
+            }),
+            members: Box::new(|| {
+                let mut members_api = MockGitlabMembersApi::new();
+                members_api
+                    .expect_is_member_of_project()
+                    .returning(|uid, pid| Ok(uid == 2 && pid == 10));
+                members_api
+            }),
+            ..Default::default()
+        };
+        let mut svc = SyncService::new(file_path, gitlab_api, String::new(), String::new(), false, 10);
+        svc.read_config().unwrap();
+        svc.lock_state().unwrap();
+        let result = svc.create_states().map(|_| ());
+        svc.unlock_state();
+        result.unwrap();
+
+        let data = svc.state.data();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[&2].projects.len(), 1);
+        assert_eq!(data[&2].projects[&10], AccessLevel::Developer);
+        assert_eq!(svc.config.users.len(), 1);
+        assert_eq!(svc.config.invites.len(), 1);
+        assert_eq!(svc.config.invites[0].email, "carol@example.com");
+    }
+
+    #[test]
+    fn members_without_access_are_skipped_in_refresh() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml").to_string_lossy().to_string();
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams: []
+  users:
+    - id: 1
+      name: alice
+      projects:
+        - id: 10
+          access_level: Developer
+    - id: 2
+      name: bob
+      projects:
+        - id: 10
+          access_level: Developer
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        let gitlab_api = GitlabApiMock {
+            projects: Box::new(|| {
+                let mut projects_api = MockGitlabProjectsApi::new();
+                projects_api.expect_get_members().returning(|_, _| {
+                    let member = |id: u64, access_level: gitlab::AccessLevel| CustomMember {
+                        id,
+                        access_level,
+                        username: String::new(),
+                        name: String::new(),
+                        web_url: String::new(),
+                    };
+                    Ok(vec![
+                        member(1, gitlab::AccessLevel::Developer),
+                        member(2, gitlab::AccessLevel::Anonymous),
+                    ])
+                });
+                projects_api.expect_get_groups_shared_with().returning(|_| Ok(Vec::new()));
+                projects_api
+            }),
+            ..Default::default()
+        };
+        let mut svc = SyncService::new(file_path, gitlab_api, String::new(), String::new(), false, 10);
+        svc.read_config().unwrap();
+        svc.refresh_state().unwrap();
+
+        let data = svc.state.data();
+        assert_eq!(data[&1].projects[&10], AccessLevel::Developer);
+        assert!(data[&2].projects.is_empty());
+    }
+
+    #[test]
+    fn state_is_planned_without_gitlab() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml").to_string_lossy().to_string();
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: backend
+      projects:
+        - id: 10
+          access_level: Developer
+  users:
+    - id: 1
+      name: alice
+      teams:
+        - backend
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        // Nothing listens on the port, so any request fails
+        let gitlab_api = || GitlabApi::lazy("127.0.0.1:1", "token");
+        let mut svc = SyncService::new(file_path.clone(), gitlab_api(), String::new(), String::new(), false, 0);
+        svc.read_config().unwrap().resolve_paths().unwrap().resolve_users().unwrap().plan_state().unwrap();
+        assert_eq!(svc.planned().1[&1].projects[&10], AccessLevel::Developer);
+
+        fs::write(&file_path, data.replace("- id: 10", "- path: group/api")).unwrap();
+        let mut svc = SyncService::new(file_path.clone(), gitlab_api(), String::new(), String::new(), false, 0);
+        assert!(svc.read_config().unwrap().resolve_paths().is_err());
+    }
+}
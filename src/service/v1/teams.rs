@@ -4,22 +4,29 @@ use log::info;
 use crate::{
     gitlab::{apis::projects::GitlabProjectsApi, GitlabApiInterface},
     output::{out_extra::OutExtra, out_message::OutMessage},
-    types::v1::{AccessLevel, ConfigFile, Project, Team},
+    types::v1::{AccessLevel, ConfigFile, LockFile, Project, Team, TeamMember, TeamRole, UserRef},
+};
+use std::{
+    io::{Error, ErrorKind, Result},
+    str::FromStr,
 };
-use std::io::{Error, ErrorKind, Result};
 
 pub(crate) struct TeamsService {
     config_file: ConfigFile,
     file_path: String,
+    // Members are matched with ids from the lock file, so they can be found by any identity
+    lock_file: LockFile,
 }
 
 impl TeamsService {
-    pub(crate) fn new(file_path: String) -> Self {
+    /// Read the config and its lock file, the lock file is empty if it doesn't exist yet
+    pub(crate) fn new(file_path: String) -> Result<Self> {
         info!("{}", file_path.clone());
-        Self {
-            config_file: ConfigFile::read(file_path.clone()).unwrap(),
+        Ok(Self {
+            config_file: ConfigFile::read(file_path.clone())?,
+            lock_file: LockFile::read(LockFile::file_name_for(&file_path))?,
             file_path,
-        }
+        })
     }
 
     pub(crate) fn create(&mut self, team_name: String) -> Result<&mut Self> {
@@ -59,6 +66,12 @@ impl TeamsService {
             let mut message = format!("{}", team.name);
             if large_out {
                 message.push_str(format!("\nprojects: {:?}\n", team.projects,).as_str());
+                for m in team.members.iter() {
+                    message.push_str(format!("member: {} ({})\n", m.user, m.role).as_str());
+                }
+                if !team.includes.is_empty() {
+                    message.push_str(format!("includes: {}\n", team.includes.join(", ")).as_str());
+                }
             }
             OutMessage::message_empty(message.as_str());
         }
@@ -84,6 +97,7 @@ impl TeamsService {
                 let p = Project {
                     name: project.name.to_string(),
                     id: project.id,
+                    path: Default::default(),
                     access_level,
                 };
                 if team.projects.iter().any(|i| i.id == p.id) {
@@ -127,6 +141,74 @@ impl TeamsService {
         Err(Error::new(ErrorKind::NotFound, error_message))
     }
 
+    pub(crate) fn add_member(
+        &mut self,
+        team_name: String,
+        user_ref: &UserRef,
+        role: TeamRole,
+    ) -> Result<&mut Self> {
+        let config = self.config_file.config_mut();
+        // The member is written the same way as the user is set in the config
+        let user = match config.users.iter().find(|u| user_ref.matches_locked(u, &self.lock_file)) {
+            Some(u) => u.identity().map_or(u.id.to_string(), |identity| identity.to_string()),
+            None => {
+                let error_message = format!(
+                    "The user {} is not in the config, add it with `gum users create` first",
+                    user_ref
+                );
+                OutMessage::message_error(error_message.as_str());
+                return Err(Error::new(ErrorKind::NotFound, error_message));
+            }
+        };
+
+        for team in config.teams.iter_mut() {
+            if team.name == team_name {
+                match team.members.iter_mut().find(|m| m.user == user) {
+                    Some(member) => member.role = role,
+                    None => team.members.push(TeamMember { user, role }),
+                }
+                OutMessage::message_info_clean(
+                    format!("{} is a {} of the team {} now", user_ref, role, team_name).as_str(),
+                );
+                return Ok(self);
+            }
+        }
+
+        let error_message = format!("The team with this name can't be found: {}", team_name);
+        OutMessage::message_error(error_message.as_str());
+        Err(Error::new(ErrorKind::NotFound, error_message))
+    }
+
+    /// Remove the member from the team, the member can be set by another identity of the same user
+    pub(crate) fn remove_member(&mut self, team_name: String, user_ref: &UserRef) -> Result<&mut Self> {
+        let lock_file = &self.lock_file;
+        let config = self.config_file.config_mut();
+        let user = config
+            .users
+            .iter()
+            .find(|u| user_ref.matches_locked(u, lock_file))
+            .cloned();
+        let is_user = |m: &TeamMember| match (&user, UserRef::from_str(&m.user)) {
+            (Some(u), Ok(member)) => member.matches_locked(u, lock_file),
+            _ => m.user == user_ref.to_string(),
+        };
+        for team in config.teams.iter_mut() {
+            if team.name == team_name {
+                if !team.members.iter().any(is_user) {
+                    let error_message =
+                        format!("{} is not a member of the team {}", user_ref, team_name);
+                    OutMessage::message_error(error_message.as_str());
+                    return Err(Error::new(ErrorKind::NotFound, error_message));
+                }
+                team.members.retain(|m| !is_user(m));
+                return Ok(self);
+            }
+        }
+        let error_message = format!("The team with this name can't be found: {}", team_name);
+        OutMessage::message_error(error_message.as_str());
+        Err(Error::new(ErrorKind::NotFound, error_message))
+    }
+
     pub(crate) fn write_state(&self) -> Result<()> {
         match self.config_file.write(self.file_path.clone()) {
             Ok(_) => Ok(()),
@@ -134,3 +216,110 @@ impl TeamsService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use super::TeamsService;
+    use crate::types::v1::{TeamRole, UserRef};
+
+    #[test]
+    fn remove_member_by_any_identity() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: backend
+      members:
+        - user: "1"
+        - user: bob
+          role: lead
+  users:
+    - id: 1
+      name: alice
+    - username: bob
+      name: Bob
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        fs::write(dir.path().join("gum-config.lock"), "users:\n  alice: 1\n  bob: 2\n").unwrap();
+        let mut svc = TeamsService::new(file_path.to_string_lossy().to_string()).unwrap();
+
+        svc.remove_member("backend".to_string(), &UserRef::Username("alice".to_string()))
+            .unwrap();
+        svc.remove_member("backend".to_string(), &UserRef::Id(2)).unwrap();
+        assert!(svc.config_file.config().teams[0].members.is_empty());
+        assert!(svc
+            .remove_member("backend".to_string(), &UserRef::Id(2))
+            .is_err());
+
+        // A lock file that can't be read isn't taken for an empty one
+        fs::write(dir.path().join("gum-config.lock"), "users: [").unwrap();
+        assert!(TeamsService::new(file_path.to_string_lossy().to_string()).is_err());
+    }
+
+    #[test]
+    fn add_member_keeps_the_layout() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let data = r#"meta:
+  version: V2
+config:
+  teams:
+    # Services
+    - name: backend
+      projects:
+          - id: 10
+            access_level: Developer
+    - name: qa
+  users:
+    - id: 2
+      name: bob
+    - username: carol
+      name: Carol
+state: ''
+"#;
+        fs::write(&file_path, data).unwrap();
+        let mut svc = TeamsService::new(file_path.to_string_lossy().to_string()).unwrap();
+        svc.add_member("backend".to_string(), &UserRef::Id(2), TeamRole::Lead)
+            .unwrap()
+            .add_member("qa".to_string(), &UserRef::Username("carol".to_string()), TeamRole::Member)
+            .unwrap()
+            .write_state()
+            .unwrap();
+
+        // Blocks are indented like `projects` of the same team, ids are written as numbers
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            r#"meta:
+  version: V2
+config:
+  teams:
+    # Services
+    - name: backend
+      projects:
+          - id: 10
+            access_level: Developer
+      members:
+          - user: 2
+            role: lead
+    - name: qa
+      members:
+        - user: carol
+  users:
+    - id: 2
+      name: bob
+    - username: carol
+      name: Carol
+state: ''
+"#
+        );
+        let svc = TeamsService::new(file_path.to_string_lossy().to_string()).unwrap();
+        assert_eq!(svc.config_file.config().teams[0].members[0].user, "2");
+    }
+}
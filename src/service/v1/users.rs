@@ -2,40 +2,57 @@ use console::style;
 
 use crate::{
     gitlab::{
-        apis::{groups::GitlabGroupsApi, projects::GitlabProjectsApi, users::GitlabUsersApi},
+        apis::{groups::GitlabGroupsApi, projects::GitlabProjectsApi},
         GitlabApiInterface,
     },
     output::{out_extra::OutExtra, out_message::OutMessage, out_spinner::OutSpinner},
-    types::v1::{AccessLevel, ConfigFile, Namespace, Project, User},
+    types::v1::{AccessLevel, Config, ConfigFile, LockFile, Namespace, Project, User, UserRef},
 };
+
+use super::{DescribeService, UsersResolver};
 use std::io::{Error, ErrorKind, Result};
 
 pub(crate) struct UsersService {
     config_file: ConfigFile,
     file_path: String,
+    // Users are matched with ids from the lock file, so they can be found by any identity
+    lock_file: LockFile,
 }
 
 impl UsersService {
-    pub(crate) fn new(file_path: String) -> Self {
-        Self {
-            config_file: ConfigFile::read(file_path.clone()).unwrap(),
+    /// Read the config and its lock file, the lock file is empty if it doesn't exist yet
+    pub(crate) fn new(file_path: String) -> Result<Self> {
+        Ok(Self {
+            config_file: ConfigFile::read(file_path.clone())?,
+            lock_file: LockFile::read(LockFile::file_name_for(&file_path))?,
             file_path,
-        }
+        })
+    }
+
+    pub(crate) fn config(&self) -> &Config {
+        self.config_file.config()
+    }
+
+    /// Find the user in the config by any identity
+    pub(crate) fn find(&self, user_ref: &UserRef) -> Option<&User> {
+        self.config_file
+            .config()
+            .users
+            .iter()
+            .find(|u| user_ref.matches_locked(u, &self.lock_file))
     }
 
     pub(crate) fn list(&mut self, large_out: bool) -> Result<()> {
         let total = &self.config_file.config().users.len();
 
-        for u in self.config_file.config().users.clone() {
+        let config = self.config_file.config();
+        for u in config.users.iter() {
             let mut message = format!("{} - {}", u.id, u.name);
             if large_out {
-                message.push_str(
-                    format!(
-                        "\nprojects: {:?}\nteams: {:?}\ngroups: {:?}\n",
-                        u.projects, u.teams, u.namespaces
-                    )
-                    .as_str(),
-                );
+                for line in DescribeService::describe_user(config, u) {
+                    message.push_str(format!("\n  {}", line).as_str());
+                }
+                message.push('\n');
             }
             OutMessage::message_empty(message.as_str());
         }
@@ -48,25 +65,31 @@ impl UsersService {
 
     pub(crate) fn create<T: GitlabApiInterface>(
         &mut self,
-        gitlab_api: T,
-        user_id: u64,
+        gitlab_api: &T,
+        user_ref: &UserRef,
     ) -> Result<&mut Self> {
         OutMessage::message_info_with_alias("I'm getting data about the user from Gitlab");
-        let users_api = gitlab_api.users();
-        let user = users_api.get_data_by_id(user_id)?;
+        let user = UsersResolver::new(gitlab_api, LockFile::default()).find(user_ref)?;
 
-        let new_user = User {
-            id: user_id,
+        // The id is saved even if the user is added by username or email,
+        // so it's possible to find out that the username is not the same user anymore
+        let mut new_user = User {
+            id: user.id,
             name: user.name.to_string(),
             ..Default::default()
         };
+        match user_ref {
+            UserRef::Id(_) => (),
+            UserRef::Username(_) => new_user.username = user.username.to_string(),
+            UserRef::Email(email) => new_user.email = email.to_string(),
+        };
 
         if self
             .config_file
             .config()
             .users
             .iter()
-            .any(|i| i.id == user_id)
+            .any(|i| i.id == user.id || user_ref.matches_locked(i, &self.lock_file))
         {
             return Err(Error::new(
                 ErrorKind::AlreadyExists,
@@ -81,9 +104,9 @@ impl UsersService {
         }
     }
 
-    pub(crate) fn remove(&mut self, user_id: u64) -> Result<&mut Self> {
+    pub(crate) fn remove(&mut self, user_ref: &UserRef) -> Result<&mut Self> {
         for (i, u) in self.config_file.config().users.iter().enumerate() {
-            if u.id == user_id {
+            if user_ref.matches_locked(u, &self.lock_file) {
                 let u = User {
                     id: u.id,
                     name: u.name.to_string(),
@@ -92,7 +115,11 @@ impl UsersService {
                 OutMessage::message_info_clean(
                     format!("removing user {} from config", u.name).as_str(),
                 );
-                self.config_file.config_mut().users.remove(i);
+                let config = self.config_file.config_mut();
+                let removed = config.users.remove(i);
+                for t in config.teams.iter_mut() {
+                    t.members.retain(|m| !m.is(&removed));
+                }
                 break;
             }
         }
@@ -101,9 +128,9 @@ impl UsersService {
 
     pub(crate) fn add_to_namespace<T: GitlabApiInterface>(
         &mut self,
-        gitlab_api: T,
+        gitlab_api: &T,
         gid: u64,
-        uid: u64,
+        user_ref: &UserRef,
         access_level: AccessLevel,
     ) -> Result<&mut Self> {
         OutMessage::message_info_with_alias("I'm getting data about the group from Gitlab");
@@ -111,7 +138,7 @@ impl UsersService {
         let namespace = group_api.get_data_by_id(gid)?;
 
         for u in self.config_file.config_mut().users.iter_mut() {
-            if u.id == uid {
+            if user_ref.matches_locked(u, &self.lock_file) {
                 let spinner = OutSpinner::spinner_start(format!(
                     "Adding {} to {} as owner",
                     u.name, namespace.name
@@ -120,6 +147,7 @@ impl UsersService {
                     name: namespace.name.to_string(),
                     access_level,
                     id: namespace.id,
+                    path: Default::default(),
                     url: namespace.web_url.to_string(),
                 };
                 if u.namespaces.iter().any(|i| i.id == o.id) {
@@ -138,9 +166,9 @@ impl UsersService {
         Ok(self)
     }
 
-    pub(crate) fn add_to_team(&mut self, uid: u64, team_name: String) -> Result<&mut Self> {
+    pub(crate) fn add_to_team(&mut self, user_ref: &UserRef, team_name: String) -> Result<&mut Self> {
         for u in self.config_file.config_mut().users.iter_mut() {
-            if u.id == uid {
+            if user_ref.matches_locked(u, &self.lock_file) {
                 let spinner =
                     OutSpinner::spinner_start(format!("Adding {} to {}", u.name, team_name));
 
@@ -164,9 +192,9 @@ impl UsersService {
 
     pub(crate) fn add_to_project<T: GitlabApiInterface>(
         &mut self,
-        gitlab_api: T,
+        gitlab_api: &T,
         pid: u64,
-        uid: u64,
+        user_ref: &UserRef,
         access_level: AccessLevel,
     ) -> Result<&mut Self> {
         let projects_api = gitlab_api.projects();
@@ -174,7 +202,7 @@ impl UsersService {
         let project = projects_api.get_data_by_id(pid)?;
 
         for u in self.config_file.config_mut().users.iter_mut() {
-            if u.id == uid {
+            if user_ref.matches_locked(u, &self.lock_file) {
                 let spinner = OutSpinner::spinner_start(format!(
                     "Adding {} to {} as {}",
                     u.name, project.name, access_level,
@@ -183,6 +211,7 @@ impl UsersService {
                 let p = Project {
                     access_level,
                     id: project.id,
+                    path: Default::default(),
                     name: project.name,
                 };
                 if u.projects.iter().any(|i| i.id == p.id) {
@@ -204,9 +233,9 @@ impl UsersService {
         Ok(self)
     }
 
-    pub(crate) fn remove_from_namespace(&mut self, uid: u64, gid: u64) -> Result<&mut Self> {
+    pub(crate) fn remove_from_namespace(&mut self, user_ref: &UserRef, gid: u64) -> Result<&mut Self> {
         for u in self.config_file.config_mut().users.iter_mut() {
-            if u.id == uid {
+            if user_ref.matches_locked(u, &self.lock_file) {
                 for (i, o) in u.namespaces.iter().enumerate() {
                     if o.id == gid {
                         OutMessage::message_info_clean(
@@ -223,9 +252,9 @@ impl UsersService {
         Ok(self)
     }
 
-    pub(crate) fn remove_from_project(&mut self, uid: u64, pid: u64) -> Result<&mut Self> {
+    pub(crate) fn remove_from_project(&mut self, user_ref: &UserRef, pid: u64) -> Result<&mut Self> {
         for u in self.config_file.config_mut().users.iter_mut() {
-            if u.id == uid {
+            if user_ref.matches_locked(u, &self.lock_file) {
                 for (i, p) in u.projects.iter().enumerate() {
                     if p.id == pid {
                         OutMessage::message_info_clean(
@@ -241,9 +270,9 @@ impl UsersService {
         Ok(self)
     }
 
-    pub(crate) fn remove_from_team(&mut self, uid: u64, team_name: String) -> Result<&mut Self> {
+    pub(crate) fn remove_from_team(&mut self, user_ref: &UserRef, team_name: String) -> Result<&mut Self> {
         for u in self.config_file.config_mut().users.iter_mut() {
-            if u.id == uid {
+            if user_ref.matches_locked(u, &self.lock_file) {
                 for (i, p) in u.teams.iter().enumerate() {
                     if p == &team_name {
                         OutMessage::message_info_clean(
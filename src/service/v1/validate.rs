@@ -0,0 +1,146 @@
+use crate::{
+    gitlab::GitlabApiInterface,
+    output::out_message::OutMessage,
+    types::{
+        policy::Policy,
+        v1::{ConfigFile, LockFile},
+    },
+};
+use std::io::{Error, ErrorKind, Result};
+
+use super::{object_violations, report_violations, PolicyChecker, UsersResolver};
+
+// ValidateService should be used to find problems in the config,
+// GitLab is only used for checks that can't be done without it
+pub(crate) struct ValidateService {
+    config_file: ConfigFile,
+    file_path: String,
+    policy: Policy,
+}
+
+impl ValidateService {
+    pub(crate) fn new(file_path: String) -> Result<Self> {
+        let config_file = ConfigFile::read(file_path.clone())?;
+        let policy = config_file.meta().policy().load(&file_path)?;
+        Ok(Self {
+            config_file,
+            file_path,
+            policy,
+        })
+    }
+
+    /// Get every problem that is found in the config
+    pub(crate) fn problems(&self) -> Vec<String> {
+        let mut problems = self.config_file.config().check_teams();
+        problems.extend(self.policy.validate());
+        problems
+    }
+
+    /// Validate the config, rules for objects are only checked for objects that have paths in the config
+    pub(crate) fn validate(&self) -> Result<()> {
+        self.check_problems()?;
+        let violations = object_violations(
+            &self.policy,
+            self.config_file.config(),
+            &mut |_, _| Ok(String::new()),
+        )?;
+        report_violations(&violations)?;
+        OutMessage::message_info_with_alias("Config is valid");
+        Ok(())
+    }
+
+    /// Validate the config and check it against the policy with data from GitLab
+    pub(crate) fn validate_online<T: GitlabApiInterface>(&self, gitlab_api: T) -> Result<()> {
+        self.check_problems()?;
+        let lock_file = LockFile::read(LockFile::file_name_for(&self.file_path))?;
+        let mut config = self.config_file.config().clone();
+        UsersResolver::new(&gitlab_api, lock_file).resolve(&mut config)?;
+        let mut checker = PolicyChecker::new(&gitlab_api, &self.policy);
+        let mut violations = checker.check_users(&config)?;
+        violations.extend(checker.check_objects(&config)?);
+        report_violations(&violations)?;
+        OutMessage::message_info_with_alias("Config is valid");
+        Ok(())
+    }
+
+    fn check_problems(&self) -> Result<()> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        for p in problems.iter() {
+            OutMessage::message_error(p);
+        }
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Found {} problems in the config", problems.len()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    use super::ValidateService;
+    use crate::types::v1::{AccessLevel, TeamRole};
+
+    #[test]
+    fn nested_teams() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gum-config.yaml");
+        let mut file = File::create(file_path.clone()).unwrap();
+        let data = r#"
+meta:
+  version: V2
+config:
+  teams:
+    - name: backend
+      includes: [backend-core, backend-payments]
+    - name: backend-core
+      includes: [platform]
+    - name: backend-payments
+      members:
+        - user: "1"
+          role: lead
+    - name: platform
+      includes: [backend, frontend]
+  users:
+    - id: 1
+      name: user
+      teams: [backend, qa]
+state: ''
+"#;
+        writeln!(file, "{}", data).unwrap();
+
+        let svc = ValidateService::new(file_path.to_string_lossy().to_string()).unwrap();
+        let problems = svc.problems();
+        assert_eq!(
+            problems,
+            vec![
+                "Team platform includes the team frontend that doesn't exist",
+                "User user is in the team qa that doesn't exist",
+                "Teams include each other: backend -> backend-core -> platform -> backend",
+            ]
+        );
+
+        let config = svc.config_file.config();
+        let teams: Vec<(&str, TeamRole)> = config
+            .user_teams(&config.users[0])
+            .iter()
+            .map(|(t, r)| (t.name.as_str(), *r))
+            .collect();
+        assert_eq!(
+            teams,
+            vec![
+                ("backend", TeamRole::Member),
+                ("backend-core", TeamRole::Member),
+                ("backend-payments", TeamRole::Lead),
+                ("platform", TeamRole::Member),
+            ]
+        );
+        assert_eq!(TeamRole::Lead.access(AccessLevel::Developer), AccessLevel::Maintainer);
+    }
+}
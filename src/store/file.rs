@@ -4,7 +4,11 @@ use std::{
     io::{Error, ErrorKind, Result},
 };
 
-use super::Store;
+use super::{
+    history::{self, Revision},
+    lock::{FileLock, LockInfo},
+    Store,
+};
 use crate::types::v1::AccessUnit;
 
 pub(crate) struct FileStore {
@@ -15,6 +19,10 @@ impl FileStore {
     pub(crate) fn new(file_path: String) -> Self {
         Self { file_path }
     }
+
+    fn file_lock(&self) -> FileLock {
+        FileLock::new(format!("{}.lock.info", self.file_path).into())
+    }
 }
 
 impl Store for FileStore {
@@ -57,6 +65,26 @@ impl Store for FileStore {
             }
         }
     }
+
+    fn lock(&self, lock: &LockInfo) -> Result<()> {
+        self.file_lock().lock(lock)
+    }
+
+    fn unlock(&self, lock: &LockInfo) -> Result<()> {
+        self.file_lock().unlock(lock)
+    }
+
+    fn force_unlock(&self) -> Result<()> {
+        self.file_lock().force_unlock()
+    }
+
+    fn history(&self) -> Result<Vec<Revision>> {
+        history::read_file(&format!("{}.history", self.file_path))
+    }
+
+    fn write_history(&self, history: &[Revision]) -> Result<()> {
+        history::write_file(&format!("{}.history", self.file_path), history)
+    }
 }
 
 #[cfg(test)]
@@ -91,6 +119,7 @@ mod tests {
             entity: User,
             projects,
             namespaces,
+            email: String::new(),
         };
         data.insert(1, access_unit);
         assert_eq!(data, file_store.get().unwrap());
@@ -0,0 +1,175 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::{self, OpenOptions},
+    io::{Error, ErrorKind, Result},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{
+    common::fnv1a,
+    v1::{AccessLevel, AccessUnit},
+};
+
+// Revision is a state that has been written by gum, a few last revisions are kept in the history
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub(crate) struct Revision {
+    pub(crate) rev: u64,
+    pub(crate) created: String,
+    // Hash of the config that has produced this state
+    pub(crate) config_hash: String,
+    pub(crate) state: HashMap<u64, AccessUnit>,
+}
+
+/// Add a new revision and keep only `size` last ones, nothing is added if the state is not changed
+pub(crate) fn append(
+    history: &mut Vec<Revision>,
+    state: HashMap<u64, AccessUnit>,
+    config_hash: String,
+    size: usize,
+) -> Option<u64> {
+    if history.last().is_some_and(|r| r.state == state) {
+        return None;
+    }
+    let rev = history.last().map_or(1, |r| r.rev + 1);
+    history.push(Revision {
+        rev,
+        created: Utc::now().to_rfc3339(),
+        config_hash,
+        state,
+    });
+    if history.len() > size {
+        history.drain(..history.len() - size);
+    }
+    Some(rev)
+}
+
+pub(crate) fn find(history: &[Revision], rev: u64) -> Result<&Revision> {
+    history.iter().find(|r| r.rev == rev).ok_or_else(|| {
+        let revs: Vec<String> = history.iter().map(|r| r.rev.to_string()).collect();
+        Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "Revision {} is not in the history, available revisions: {}",
+                rev,
+                revs.join(", ")
+            ),
+        )
+    })
+}
+
+/// Get changes that turn the first state into the second one, a line per change
+pub(crate) fn diff(a: &HashMap<u64, AccessUnit>, b: &HashMap<u64, AccessUnit>) -> Vec<String> {
+    let mut changes: Vec<String> = Vec::new();
+    let subjects: BTreeSet<u64> = a.keys().chain(b.keys()).copied().collect();
+    for s in subjects {
+        let empty = HashMap::new();
+        let (a_unit, b_unit) = (a.get(&s), b.get(&s));
+        let entity = match b_unit.or(a_unit) {
+            Some(unit) => format!("{:?} {}", unit.entity, s),
+            None => continue,
+        };
+        for (kind, a_access, b_access) in [
+            (
+                "project",
+                a_unit.map_or(&empty, |u| &u.projects),
+                b_unit.map_or(&empty, |u| &u.projects),
+            ),
+            (
+                "group",
+                a_unit.map_or(&empty, |u| &u.namespaces),
+                b_unit.map_or(&empty, |u| &u.namespaces),
+            ),
+        ] {
+            changes.extend(diff_access(&entity, kind, a_access, b_access));
+        }
+    }
+    changes
+}
+
+fn diff_access(
+    entity: &str,
+    kind: &str,
+    a: &HashMap<u64, AccessLevel>,
+    b: &HashMap<u64, AccessLevel>,
+) -> Vec<String> {
+    let ids: BTreeSet<u64> = a.keys().chain(b.keys()).copied().collect();
+    ids.into_iter()
+        .filter_map(|id| match (a.get(&id), b.get(&id)) {
+            (None, Some(access)) => Some(format!("+ {} {} {}: {}", entity, kind, id, access)),
+            (Some(access), None) => Some(format!("- {} {} {}: {}", entity, kind, id, access)),
+            (Some(old), Some(new)) if old != new => {
+                Some(format!("~ {} {} {}: {} -> {}", entity, kind, id, old, new))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Get a hash of the config that doesn't change between versions of gum
+pub(crate) fn config_hash(data: &str) -> String {
+    format!("{:016x}", fnv1a(data.as_bytes()))
+}
+
+// The history of stores that are kept in files is kept in a file next to them
+pub(crate) fn read_file(path: &str) -> Result<Vec<Revision>> {
+    match fs::read_to_string(path) {
+        Ok(data) => {
+            serde_json::from_str(&data).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+pub(crate) fn write_file(path: &str, history: &[Revision]) -> Result<()> {
+    let f = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+    serde_json::to_writer(&f, history).map_err(Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{append, diff, find};
+    use crate::types::v1::{AccessLevel, AccessUnit, EntityType};
+
+    fn state(project_access: AccessLevel) -> HashMap<u64, AccessUnit> {
+        let mut projects = HashMap::new();
+        projects.insert(10, project_access);
+        let mut state = HashMap::new();
+        state.insert(
+            1,
+            AccessUnit {
+                entity: EntityType::User,
+                projects,
+                namespaces: HashMap::new(),
+                email: String::new(),
+            },
+        );
+        state
+    }
+
+    #[test]
+    fn keep_last_revisions() {
+        let mut history = Vec::new();
+        for access in [AccessLevel::Guest, AccessLevel::Reporter, AccessLevel::Developer, AccessLevel::Maintainer] {
+            append(&mut history, state(access), String::new(), 3);
+        }
+        let revs: Vec<u64> = history.iter().map(|r| r.rev).collect();
+        assert_eq!(revs, vec![2, 3, 4]);
+        assert_eq!(append(&mut history, state(AccessLevel::Maintainer), String::new(), 3), None);
+        assert_eq!(history.len(), 3);
+        assert!(find(&history, 1).is_err());
+
+        let changes = diff(&state(AccessLevel::Developer), &state(AccessLevel::Maintainer));
+        assert_eq!(changes, vec!["~ User 1 project 10: Developer -> Maintainer"]);
+        let changes = diff(&HashMap::new(), &state(AccessLevel::Guest));
+        assert_eq!(changes, vec!["+ User 1 project 10: Guest"]);
+    }
+}
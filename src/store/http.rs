@@ -0,0 +1,326 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    env,
+    io::{Error, ErrorKind, Result},
+};
+
+use reqwest::{
+    blocking::{Client, RequestBuilder},
+    Method, StatusCode,
+};
+
+use serde::Serialize;
+
+use super::{lock::LockInfo, Revision, Store};
+use crate::types::v1::AccessUnit;
+
+// Credentials and addresses are taken from the environment, the same way terraform does it with TF_HTTP_*
+static ENV_USERNAME: &str = "GUM_HTTP_USERNAME";
+static ENV_PASSWORD: &str = "GUM_HTTP_PASSWORD";
+static ENV_TOKEN: &str = "GUM_HTTP_TOKEN";
+static ENV_LOCK_ADDRESS: &str = "GUM_HTTP_LOCK_ADDRESS";
+static ENV_UNLOCK_ADDRESS: &str = "GUM_HTTP_UNLOCK_ADDRESS";
+static ENV_HISTORY_ADDRESS: &str = "GUM_HTTP_HISTORY_ADDRESS";
+
+// HttpStore keeps the state on a server that implements the terraform http backend protocol:
+// GET and POST the state, LOCK and UNLOCK it
+pub(crate) struct HttpStore {
+    address: String,
+    lock_address: String,
+    unlock_address: String,
+    // The history is kept only when its address is set, it's written under the lock of the state
+    history_address: Option<String>,
+    auth: Auth,
+    client: Client,
+    // Id of the lock that is held by this process, it's sent with the state
+    held: RefCell<Option<String>>,
+}
+
+enum Auth {
+    None,
+    Basic { username: String, password: String },
+    Token(String),
+}
+
+impl HttpStore {
+    pub(crate) fn new(address: String) -> Self {
+        let auth = match (env::var(ENV_TOKEN), env::var(ENV_USERNAME)) {
+            (Ok(token), _) => Auth::Token(token),
+            (_, Ok(username)) => Auth::Basic {
+                username,
+                password: env::var(ENV_PASSWORD).unwrap_or_default(),
+            },
+            _ => Auth::None,
+        };
+        Self {
+            lock_address: env::var(ENV_LOCK_ADDRESS).unwrap_or_else(|_| address.clone()),
+            unlock_address: env::var(ENV_UNLOCK_ADDRESS).unwrap_or_else(|_| address.clone()),
+            history_address: env::var(ENV_HISTORY_ADDRESS).ok(),
+            address,
+            auth,
+            client: Client::new(),
+            held: RefCell::new(None),
+        }
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.auth {
+            Auth::None => request,
+            Auth::Basic { username, password } => request.basic_auth(username, Some(password)),
+            Auth::Token(token) => request.bearer_auth(token),
+        }
+    }
+
+    // When the state is locked by somebody else, the error tells who it is
+    fn send_lock(&self, lock: &LockInfo) -> Result<()> {
+        let method = Method::from_bytes(b"LOCK").unwrap();
+        let response = self
+            .request(method, &self.lock_address)
+            .json(lock)
+            .send()
+            .map_err(HttpStore::error)?;
+        match response.status() {
+            s if s.is_success() => Ok(()),
+            StatusCode::CONFLICT | StatusCode::LOCKED => {
+                Err(match response.json::<LockInfo>() {
+                    Ok(other) => other.locked_error(),
+                    Err(_) => Error::new(ErrorKind::WouldBlock, "State is locked by somebody else"),
+                })
+            }
+            s => Err(Error::other(format!("Can't lock the state at {}: {}", self.lock_address, s))),
+        }
+    }
+
+    // Without lock information, the server removes any lock
+    fn send_unlock(&self, lock: Option<&LockInfo>) -> Result<()> {
+        let method = Method::from_bytes(b"UNLOCK").unwrap();
+        let mut request = self.request(method, &self.unlock_address);
+        if let Some(lock) = lock {
+            request = request.json(lock);
+        }
+        let response = request
+            .send()
+            .map_err(HttpStore::error)?;
+        match response.status() {
+            s if s.is_success() => Ok(()),
+            s => Err(Error::other(format!("Can't unlock the state at {}: {}", self.unlock_address, s))),
+        }
+    }
+
+    fn post<T: Serialize + ?Sized>(&self, address: &str, data: &T, lock_id: &str) -> Result<()> {
+        let response = self
+            .request(Method::POST, address)
+            .query(&[("ID", lock_id)])
+            .json(data)
+            .send()
+            .map_err(HttpStore::error)?;
+        match response.status() {
+            s if s.is_success() => Ok(()),
+            s => Err(Error::other(format!("Can't save to {}: {}", address, s))),
+        }
+    }
+
+    // When the state is not locked yet, it's locked only while the data is posted
+    fn post_locked<T: Serialize + ?Sized>(&self, address: &str, data: &T) -> Result<()> {
+        if let Some(id) = self.held.borrow().as_ref() {
+            return self.post(address, data, id);
+        }
+        let lock = LockInfo::new("write");
+        self.send_lock(&lock)?;
+        let result = self.post(address, data, &lock.id);
+        self.send_unlock(Some(&lock))?;
+        result
+    }
+
+    fn error(err: reqwest::Error) -> Error {
+        Error::other(err.to_string())
+    }
+}
+
+impl Store for HttpStore {
+    fn get(&self) -> Result<HashMap<u64, AccessUnit>> {
+        let response = self
+            .request(Method::GET, &self.address)
+            .send()
+            .map_err(HttpStore::error)?;
+        match response.status() {
+            StatusCode::NOT_FOUND | StatusCode::NO_CONTENT => {
+                Err(Error::new(ErrorKind::NotFound, "state is not found"))
+            }
+            s if s.is_success() => {
+                let body = response.text().map_err(HttpStore::error)?;
+                if body.trim().is_empty() {
+                    return Err(Error::new(ErrorKind::NotFound, "state is empty"));
+                }
+                serde_json::from_str(&body).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+            }
+            s => Err(Error::other(format!("Can't get the state from {}: {}", self.address, s))),
+        }
+    }
+
+    fn write(&self, data: HashMap<u64, AccessUnit>) -> Result<()> {
+        self.post_locked(&self.address, &data)
+    }
+
+    fn lock(&self, lock: &LockInfo) -> Result<()> {
+        self.send_lock(lock)?;
+        self.held.replace(Some(lock.id.clone()));
+        Ok(())
+    }
+
+    fn unlock(&self, lock: &LockInfo) -> Result<()> {
+        self.send_unlock(Some(lock))?;
+        self.held.replace(None);
+        Ok(())
+    }
+
+    fn force_unlock(&self) -> Result<()> {
+        self.send_unlock(None)
+    }
+
+    fn history(&self) -> Result<Vec<Revision>> {
+        let address = match &self.history_address {
+            Some(address) => address,
+            None => return Ok(Vec::new()),
+        };
+        let response = self
+            .request(Method::GET, address)
+            .send()
+            .map_err(HttpStore::error)?;
+        match response.status() {
+            StatusCode::NOT_FOUND | StatusCode::NO_CONTENT => Ok(Vec::new()),
+            s if s.is_success() => {
+                let body = response.text().map_err(HttpStore::error)?;
+                if body.trim().is_empty() {
+                    return Ok(Vec::new());
+                }
+                serde_json::from_str(&body).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+            }
+            s => Err(Error::other(format!("Can't get the history from {}: {}", address, s))),
+        }
+    }
+
+    fn write_history(&self, history: &[Revision]) -> Result<()> {
+        match &self.history_address {
+            Some(address) => self.post_locked(address, history),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::HttpStore;
+    use crate::store::{LockInfo, Store};
+    use crate::types::v1::{AccessLevel, AccessUnit, EntityType};
+
+    // Serve requests one by one, the state is kept in memory, all requests are recorded
+    fn serve(listener: TcpListener, requests: usize) -> thread::JoinHandle<Vec<String>> {
+        thread::spawn(move || {
+            let mut state = String::new();
+            let mut log: Vec<String> = Vec::new();
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let request_line = request_line.trim().to_string();
+                let response = if request_line.starts_with("GET") {
+                    state.clone()
+                } else if request_line.starts_with("POST") {
+                    state = String::from_utf8(body).unwrap();
+                    String::new()
+                } else {
+                    String::new()
+                };
+                log.push(request_line);
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .unwrap();
+            }
+            log
+        })
+    }
+
+    #[test]
+    fn write_and_read() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}/state/gum", listener.local_addr().unwrap());
+        let server = serve(listener, 4);
+
+        let mut projects: HashMap<u64, AccessLevel> = HashMap::new();
+        projects.insert(10, AccessLevel::Developer);
+        let mut data: HashMap<u64, AccessUnit> = HashMap::new();
+        data.insert(
+            1,
+            AccessUnit {
+                entity: EntityType::User,
+                projects,
+                namespaces: HashMap::new(),
+                email: String::new(),
+            },
+        );
+
+        let store = HttpStore::new(address);
+        store.write(data.clone()).unwrap();
+        assert_eq!(store.get().unwrap(), data);
+
+        let log = server.join().unwrap();
+        assert!(log[0].starts_with("LOCK /state/gum"));
+        assert!(log[1].starts_with("POST /state/gum?ID="));
+        assert!(log[2].starts_with("UNLOCK /state/gum"));
+        assert!(log[3].starts_with("GET /state/gum"));
+    }
+
+    #[test]
+    fn history_under_lock() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}/state/gum", listener.local_addr().unwrap());
+        let server = serve(listener, 4);
+
+        let store = HttpStore::new(address.clone());
+        assert!(store.history().unwrap().is_empty());
+        store.write_history(&[]).unwrap();
+
+        let store = HttpStore {
+            history_address: Some(format!("{}-history", address)),
+            ..HttpStore::new(address)
+        };
+        let lock = LockInfo::new("sync");
+        store.lock(&lock).unwrap();
+        store.write_history(&[]).unwrap();
+        store.unlock(&lock).unwrap();
+        assert!(store.history().unwrap().is_empty());
+
+        let log = server.join().unwrap();
+        assert!(log[0].starts_with("LOCK /state/gum "));
+        assert!(log[1].starts_with(format!("POST /state/gum-history?ID={} ", lock.id).as_str()));
+        assert!(log[2].starts_with("UNLOCK /state/gum "));
+        assert!(log[3].starts_with("GET /state/gum-history "));
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{Error, ErrorKind, Result, Write},
+    path::PathBuf,
+};
+
+use chrono::Utc;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Lock information in the format of terraform, so http backends can understand it,
+// owner, pid and host are added to make it clear who has forgotten to unlock the state
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct LockInfo {
+    #[serde(rename = "ID")]
+    pub(crate) id: String,
+    #[serde(default)]
+    pub(crate) operation: String,
+    #[serde(default)]
+    pub(crate) info: String,
+    #[serde(default)]
+    pub(crate) who: String,
+    #[serde(default)]
+    pub(crate) version: String,
+    #[serde(default)]
+    pub(crate) created: String,
+    #[serde(default)]
+    pub(crate) path: String,
+    #[serde(default)]
+    pub(crate) owner: String,
+    #[serde(default)]
+    pub(crate) pid: u32,
+    #[serde(default)]
+    pub(crate) host: String,
+}
+
+impl LockInfo {
+    pub(crate) fn new(operation: &str) -> Self {
+        let id: u128 = rand::thread_rng().gen();
+        let owner = env::var("USER").unwrap_or_else(|_| "gum".to_string());
+        let host = env::var("HOSTNAME")
+            .ok()
+            .or_else(|| fs::read_to_string("/etc/hostname").ok())
+            .map(|h| h.trim().to_string())
+            .unwrap_or_default();
+        let pid = std::process::id();
+        Self {
+            id: format!("{:032x}", id),
+            operation: operation.to_string(),
+            info: format!("pid {}", pid),
+            who: format!("{}@{}", owner, host),
+            version: VERSION.to_string(),
+            created: Utc::now().to_rfc3339(),
+            path: String::new(),
+            owner,
+            pid,
+            host,
+        }
+    }
+
+    /// Get the error that tells who is holding the lock
+    pub(crate) fn locked_error(&self) -> Error {
+        Error::new(
+            ErrorKind::WouldBlock,
+            format!(
+                "State is locked by {} since {} ({}, lock id {}), if it's stale, run `gum state unlock --force`",
+                self.who, self.created, self.info, self.id
+            ),
+        )
+    }
+}
+
+// FileLock is a lock that is kept in a file next to the state, the file is created only if it doesn't exist
+pub(crate) struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub(crate) fn lock(&self, lock: &LockInfo) -> Result<()> {
+        let mut f = match OpenOptions::new().write(true).create_new(true).open(&self.path) {
+            Ok(f) => f,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                return Err(match self.read() {
+                    Ok(other) => other.locked_error(),
+                    Err(_) => Error::new(
+                        ErrorKind::WouldBlock,
+                        format!("State is locked, the lock file is {}", self.path.display()),
+                    ),
+                });
+            }
+            Err(err) => return Err(err),
+        };
+        f.write_all(serde_json::to_string(lock)?.as_bytes())
+    }
+
+    /// Remove the lock, only the one who has locked the state can do it
+    pub(crate) fn unlock(&self, lock: &LockInfo) -> Result<()> {
+        match self.read() {
+            Ok(current) if current.id != lock.id => Err(current.locked_error()),
+            Ok(_) => fs::remove_file(&self.path),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub(crate) fn force_unlock(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    fn read(&self) -> Result<LockInfo> {
+        let data = fs::read_to_string(&self.path)?;
+        serde_json::from_str(&data).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+    use tempfile::tempdir;
+
+    use super::{FileLock, LockInfo};
+
+    #[test]
+    fn lock_and_unlock() {
+        let dir = tempdir().unwrap();
+        let file_lock = FileLock::new(dir.path().join("state.json.lock.info"));
+        let first = LockInfo::new("sync");
+        let second = LockInfo::new("sync");
+
+        file_lock.lock(&first).unwrap();
+        let err = file_lock.lock(&second).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+        assert!(err.to_string().contains(&first.id));
+        assert!(file_lock.unlock(&second).is_err());
+
+        file_lock.unlock(&first).unwrap();
+        file_lock.lock(&second).unwrap();
+        file_lock.force_unlock().unwrap();
+        file_lock.lock(&first).unwrap();
+    }
+}
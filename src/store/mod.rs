@@ -1,30 +1,61 @@
 mod file;
+pub(crate) mod history;
+mod http;
+mod lock;
 mod string;
 use crate::output::out_message::OutMessage;
-use crate::types::v1::AccessUnit;
+use crate::types::{fragments::Sources, v1::AccessUnit};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
 use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+pub(crate) use self::file::FileStore;
+pub(crate) use self::history::Revision;
+pub(crate) use self::http::HttpStore;
+pub(crate) use self::lock::LockInfo;
+pub(crate) use self::string::StringStore;
 
 pub(crate) trait Store {
     fn get(&self) -> Result<HashMap<u64, AccessUnit>>;
     fn write(&self, data: HashMap<u64, AccessUnit>) -> Result<()>;
+    /// Lock the state, so nobody else can change it until it's unlocked
+    fn lock(&self, lock: &LockInfo) -> Result<()>;
+    fn unlock(&self, lock: &LockInfo) -> Result<()>;
+    /// Remove a lock that is left by somebody else
+    fn force_unlock(&self) -> Result<()>;
+    /// Get previous states, the oldest one is the first
+    fn history(&self) -> Result<Vec<Revision>>;
+    fn write_history(&self, history: &[Revision]) -> Result<()>;
 }
 
-pub(crate) fn get_store_type(source: String) -> Result<Box<dyn Store>> {
+/// Get a store by the `state` value of the config file: the state itself or a reference to it.
+/// Relative paths are resolved from the directory of the config file
+pub(crate) fn get_store_type(source: String, config_path: String) -> Result<Box<dyn Store>> {
+    lazy_static! {
+        static ref FILE: Regex = Regex::new(r#"^file:(.+)$"#).unwrap();
+    };
     lazy_static! {
-        static ref FILE: Regex = Regex::new(r#"^(\.?/.*)$"#).unwrap();
+        static ref HTTP: Regex = Regex::new(r#"^https?://.+$"#).unwrap();
     };
     lazy_static! {
-        static ref STRING: Regex = Regex::new(r#"^\{.*\}$"#).unwrap();
+        static ref STRING: Regex = Regex::new(r#"^(\{.*\}|~|)$"#).unwrap();
     };
-    if FILE.is_match(&source) {
+    if let Some(captures) = FILE.captures(&source) {
+        let path = Path::new(&captures[1]);
+        let path = match Sources::root_file(&config_path).parent() {
+            Some(dir) if path.is_relative() => dir.join(path),
+            _ => path.to_path_buf(),
+        };
         OutMessage::message_info_with_alias("Will try to get a state from the file");
-        Ok(Box::new(file::FileStore::new(source)))
+        Ok(Box::new(FileStore::new(path.to_string_lossy().to_string())))
+    } else if HTTP.is_match(&source) {
+        OutMessage::message_info_with_alias("Will try to get a state from the server");
+        Ok(Box::new(HttpStore::new(source)))
     } else if STRING.is_match(&source) {
         OutMessage::message_info_with_alias("Will try to get a state from the config");
-        Ok(Box::new(string::StringStore::new(source)))
+        Ok(Box::new(StringStore::new(config_path)))
     } else {
         OutMessage::message_error("Dude, I don't know where to get a state from");
         Err(Error::new(ErrorKind::InvalidData, "unknown store source"))
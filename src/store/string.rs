@@ -1,29 +1,72 @@
+use crate::store::history::{self, Revision};
+use crate::store::lock::{FileLock, LockInfo};
 use crate::store::AccessUnit;
 use crate::store::Store;
+use crate::types::{fragments::Sources, v1::ConfigFile};
 
 use std::collections::HashMap;
 use std::io::{Error, ErrorKind, Result};
 
 pub(crate) struct StringStore {
-    data: String,
+    // The config file that keeps the state
+    config_path: String,
 }
 
 impl StringStore {
-    pub(crate) fn new(data: String) -> Self {
-        Self { data }
+    pub(crate) fn new(config_path: String) -> Self {
+        Self { config_path }
+    }
+
+    // The lock and the history are kept next to the config file
+    fn file_path(&self, suffix: &str) -> String {
+        format!("{}{}", Sources::root_file(&self.config_path).display(), suffix)
+    }
+
+    fn file_lock(&self) -> FileLock {
+        FileLock::new(self.file_path(".lock.info").into())
     }
 }
 
 impl Store for StringStore {
     // Get store from the string which is taken from the gum configuration file.
+    // The file is read every time, so the state that is changed while waiting for the lock is not missed
     fn get(&self) -> Result<HashMap<u64, AccessUnit>> {
-        let d: std::result::Result<HashMap<u64, AccessUnit>, _> = serde_json::from_str(&self.data);
+        let config_file = ConfigFile::read(self.config_path.clone())?;
+        let data = config_file.state();
+        if data.is_empty() || data == "~" {
+            return Err(Error::new(ErrorKind::NotFound, "state is empty"));
+        }
+        let d: std::result::Result<HashMap<u64, AccessUnit>, _> = serde_json::from_str(data);
         match d {
             Ok(r) => Ok(r),
             Err(err) => Err(Error::new(ErrorKind::Other, err.to_string())),
         }
     }
-    fn write(&self, _: std::collections::HashMap<u64, AccessUnit>) -> Result<()> {
-        todo!()
+
+    // Put the state back to the gum configuration file, the rest of the file is not changed
+    fn write(&self, data: HashMap<u64, AccessUnit>) -> Result<()> {
+        let mut config_file = ConfigFile::read(self.config_path.clone())?;
+        config_file.set_state(serde_json::to_string(&data)?);
+        config_file.write(self.config_path.clone())
+    }
+
+    fn lock(&self, lock: &LockInfo) -> Result<()> {
+        self.file_lock().lock(lock)
+    }
+
+    fn unlock(&self, lock: &LockInfo) -> Result<()> {
+        self.file_lock().unlock(lock)
+    }
+
+    fn force_unlock(&self) -> Result<()> {
+        self.file_lock().force_unlock()
+    }
+
+    fn history(&self) -> Result<Vec<Revision>> {
+        history::read_file(&self.file_path(".history"))
+    }
+
+    fn write_history(&self, history: &[Revision]) -> Result<()> {
+        history::write_file(&self.file_path(".history"), history)
     }
 }
@@ -1,19 +1,22 @@
 pub(crate) use std::{
+    fmt,
     io::{Error, ErrorKind, Result},
     str::FromStr,
 };
 
 use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Serialize, Deserialize)]
 pub(crate) enum Versions {
     V1,
+    V2,
 }
 
 impl FromStr for Versions {
     fn from_str(input: &str) -> Result<Versions> {
         match input {
-            "V1" => Ok(Versions::V1),
+            "V1" | "v1" => Ok(Versions::V1),
+            "V2" | "v2" => Ok(Versions::V2),
             _ => Err(Error::new(
                 ErrorKind::NotFound,
                 format!("Version {} doesn't exists", input),
@@ -23,6 +26,22 @@ impl FromStr for Versions {
     type Err = Error;
 }
 
+impl fmt::Display for Versions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 pub(crate) trait Version {
     fn get_version(&self) -> Result<Versions>;
 }
+
+/// Hash the data with FNV-1a, it's stable between versions of gum, so it can be kept in files
+pub(crate) fn fnv1a(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in data {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
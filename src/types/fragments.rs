@@ -0,0 +1,436 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Error, ErrorKind, Result},
+    path::{Path, PathBuf},
+};
+
+use super::v1::{Config, Group, Team, User};
+
+// The main file of a config directory, it keeps meta and the state
+static ROOT_FILE: &str = "gum-config.yaml";
+
+// Directories of a config directory that fragments are read from, unless `include` is set
+static FRAGMENT_DIRS: [&str; 3] = ["teams", "users", "groups"];
+
+// Fragment is a part of the config that is kept in a separate file,
+// other keys are not allowed, so yaml files that are not fragments are not read silently
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Fragment {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) teams: Vec<Team>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) users: Vec<User>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) groups: Vec<Group>,
+}
+
+// Sources knows which fragment every entity of the config is coming from,
+// so changes can be written back to the same file
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct Sources {
+    fragments: Vec<PathBuf>,
+    // Keys of entities that are defined in fragments, the value is an index of the fragment
+    owners: HashMap<String, usize>,
+}
+
+// Where an entity is defined, it's used to report duplicates. The line is not known
+// when the entity is not found in the text
+struct Location {
+    path: PathBuf,
+    line: Option<usize>,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}", self.path.display(), line),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
+impl Sources {
+    /// Get the path of the main config file, a directory is turned into its gum-config.yaml
+    pub(crate) fn root_file(file_name: &str) -> PathBuf {
+        let path = Path::new(file_name);
+        if path.is_dir() {
+            path.join(ROOT_FILE)
+        } else {
+            path.to_path_buf()
+        }
+    }
+
+    /// Read fragments that are included by the main file and merge them into its config.
+    /// When the config is a directory without `include`, fragments are yaml files in teams/, users/ and groups/
+    pub(crate) fn read(
+        file_name: &str,
+        include: &[String],
+        root_text: &str,
+        config: &mut Config,
+    ) -> Result<Self> {
+        let root = Sources::root_file(file_name);
+        let patterns: Vec<String> = if Path::new(file_name).is_dir() && include.is_empty() {
+            FRAGMENT_DIRS
+                .iter()
+                .flat_map(|d| [format!("{}/**/*.yaml", d), format!("{}/**/*.yml", d)])
+                .collect()
+        } else {
+            include.to_vec()
+        };
+
+        let mut sources = Sources::default();
+        let mut seen: HashMap<String, Location> = HashMap::new();
+        let root_fragment = Fragment {
+            teams: config.teams.clone(),
+            users: config.users.clone(),
+            groups: config.groups.clone(),
+        };
+        Sources::register(&mut seen, &root_fragment, &root, root_text)?;
+        if patterns.is_empty() {
+            return Ok(sources);
+        }
+
+        for path in Sources::find(&root, &patterns)? {
+            let text = fs::read_to_string(&path)?;
+            let fragment: Fragment = serde_yaml::from_str(&text).map_err(|err| {
+                Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), err))
+            })?;
+            Sources::register(&mut seen, &fragment, &path, &text)?;
+
+            let index = sources.fragments.len();
+            for t in fragment.teams {
+                sources.owners.insert(Sources::team_key(&t), index);
+                config.teams.push(t);
+            }
+            for u in fragment.users {
+                sources.owners.insert(Sources::user_key(&u), index);
+                config.users.push(u);
+            }
+            for g in fragment.groups {
+                sources.owners.insert(Sources::group_key(&g), index);
+                config.groups.push(g);
+            }
+            sources.fragments.push(path);
+        }
+        Ok(sources)
+    }
+
+    /// Split the config into the part that stays in the main file and fragments
+    pub(crate) fn split(&self, config: &Config) -> (Config, Vec<(PathBuf, Fragment)>) {
+        let mut root = Config {
+            teams: Vec::new(),
+            users: Vec::new(),
+            groups: Vec::new(),
+            invites: config.invites.clone(),
+        };
+        let mut fragments: Vec<Fragment> = vec![Fragment::default(); self.fragments.len()];
+        for t in config.teams.iter() {
+            match self.owners.get(&Sources::team_key(t)) {
+                Some(i) => fragments[*i].teams.push(t.clone()),
+                None => root.teams.push(t.clone()),
+            }
+        }
+        for u in config.users.iter() {
+            match self.owners.get(&Sources::user_key(u)) {
+                Some(i) => fragments[*i].users.push(u.clone()),
+                None => root.users.push(u.clone()),
+            }
+        }
+        for g in config.groups.iter() {
+            match self.owners.get(&Sources::group_key(g)) {
+                Some(i) => fragments[*i].groups.push(g.clone()),
+                None => root.groups.push(g.clone()),
+            }
+        }
+        (root, self.fragments.iter().cloned().zip(fragments).collect())
+    }
+
+    fn register(
+        seen: &mut HashMap<String, Location>,
+        fragment: &Fragment,
+        path: &Path,
+        text: &str,
+    ) -> Result<()> {
+        let mut entries: Vec<(String, Option<usize>)> = Vec::new();
+        for t in fragment.teams.iter() {
+            let line = Sources::line_of(text, "teams", &["name"], &t.name);
+            entries.push((Sources::team_key(t), line));
+        }
+        for u in fragment.users.iter() {
+            let value = match u.identity() {
+                Some(identity) => identity.to_string(),
+                None => u.id.to_string(),
+            };
+            let line = Sources::line_of(text, "users", &["id", "username", "email"], &value);
+            entries.push((Sources::user_key(u), line));
+        }
+        for g in fragment.groups.iter() {
+            let key = Sources::group_key(g);
+            let value = key.trim_start_matches("group ").to_string();
+            let line = Sources::line_of(text, "groups", &["path", "id", "name"], &value);
+            entries.push((key, line));
+        }
+
+        for (key, line) in entries {
+            let location = Location {
+                path: path.to_path_buf(),
+                line,
+            };
+            if let Some(other) = seen.get(&key) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The {} is defined twice: {} and {}", key, other, location),
+                ));
+            }
+            seen.insert(key, location);
+        }
+        Ok(())
+    }
+
+    fn team_key(t: &Team) -> String {
+        format!("team {}", t.name)
+    }
+
+    fn user_key(u: &User) -> String {
+        match u.identity() {
+            Some(identity) => format!("user {}", identity),
+            None => format!("user {}", u.id),
+        }
+    }
+
+    fn group_key(g: &Group) -> String {
+        if !g.path.is_empty() {
+            format!("group {}", g.path)
+        } else if g.id != 0 {
+            format!("group {}", g.id)
+        } else {
+            format!("group {}", g.name)
+        }
+    }
+
+    // Get the number of the first line in the section where an entry sets one of the fields to the value.
+    // serde doesn't keep positions, so the text is searched instead, only fields of entries
+    // themselves are checked, so ids of nested projects are not matched
+    fn line_of(text: &str, section: &str, fields: &[&str], value: &str) -> Option<usize> {
+        let field = Regex::new(&format!(
+            r#"^(\s*)(- )?\s*({}):\s*["']?{}["']?\s*$"#,
+            fields.join("|"),
+            regex::escape(value)
+        ))
+        .unwrap();
+        let header = Regex::new(r"^\s*(teams|users|groups):").unwrap();
+        let item = Regex::new(r"^(\s*)- ").unwrap();
+        let mut in_section = false;
+        // Column where keys of entries of the section start
+        let mut column: Option<usize> = None;
+        for (i, line) in text.lines().enumerate() {
+            if let Some(captures) = header.captures(line) {
+                in_section = &captures[1] == section;
+                column = None;
+                continue;
+            } else if !line.starts_with(' ') && !line.starts_with('-') && line.trim() != "config:" {
+                in_section = false;
+            }
+            if !in_section {
+                continue;
+            }
+            if column.is_none() {
+                column = item.captures(line).map(|c| c[1].len() + 2);
+            }
+            if let (Some(column), Some(captures)) = (column, field.captures(line)) {
+                let key_column = captures[1].len() + captures.get(2).map_or(0, |m| m.len());
+                if key_column == column {
+                    return Some(i + 1);
+                }
+            }
+        }
+        None
+    }
+
+    // Find files that are matched by glob patterns relatively to the main file
+    fn find(root: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+        let base = root.parent().unwrap_or(Path::new(""));
+        let base = if base.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            base
+        };
+        let mut regexes: Vec<Regex> = Vec::new();
+        for p in patterns {
+            regexes.push(Sources::glob_regex(p)?);
+        }
+
+        let mut files: Vec<PathBuf> = Vec::new();
+        Sources::walk(base, &mut files)?;
+        files.sort();
+        Ok(files
+            .into_iter()
+            .filter(|f| f.file_name() != root.file_name() || f.parent() != Some(base))
+            .filter(|f| {
+                let relative = f
+                    .strip_prefix(base)
+                    .unwrap_or(f)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                regexes.iter().any(|r| r.is_match(&relative))
+            })
+            .collect())
+    }
+
+    // Hidden files and directories, such as .git and .gitlab-ci.yml, are never fragments
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')) {
+                continue;
+            }
+            if path.is_dir() {
+                Sources::walk(&path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    fn glob_regex(pattern: &str) -> Result<Regex> {
+        let mut regex = String::from("^");
+        let mut chars = pattern.trim_start_matches("./").chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        regex.push_str("(.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                _ => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+        Regex::new(&regex).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Pattern '{}' in include is not valid: {}", pattern, err),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use super::Sources;
+    use crate::types::v1::ConfigFile;
+
+    #[test]
+    fn split_config() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("teams")).unwrap();
+        fs::write(
+            dir.path().join("gum-config.yaml"),
+            r#"
+meta:
+  version: V1
+include:
+  - teams/*.yaml
+config:
+  teams:
+    - name: default
+  users: []
+  groups: []
+state: ''
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("teams/backend.yaml"),
+            "teams:\n  - name: backend\n  - name: backend-core\n",
+        )
+        .unwrap();
+        let file_name = dir.path().join("gum-config.yaml").to_string_lossy().to_string();
+
+        let mut config_file = ConfigFile::read(file_name.clone()).unwrap();
+        assert_eq!(config_file.config().teams.len(), 3);
+        config_file.config_mut().teams[1].includes = vec!["backend-core".to_string()];
+        config_file.write(file_name.clone()).unwrap();
+
+        let fragment = fs::read_to_string(dir.path().join("teams/backend.yaml")).unwrap();
+        assert!(fragment.contains("backend-core"));
+        assert!(fragment.contains("includes"));
+        let root = fs::read_to_string(&file_name).unwrap();
+        assert!(!root.contains("backend"));
+
+        // The same config can be read from the directory
+        let dir_name = dir.path().to_string_lossy().to_string();
+        assert_eq!(ConfigFile::read(dir_name).unwrap().config().teams.len(), 3);
+
+        fs::write(
+            dir.path().join("teams/duplicate.yaml"),
+            "teams:\n  - name: platform\n  - name: backend\n",
+        )
+        .unwrap();
+        let err = ConfigFile::read(file_name).unwrap_err();
+        assert!(err.to_string().contains("backend.yaml:2"));
+        assert!(err.to_string().contains("duplicate.yaml:3"));
+    }
+
+    #[test]
+    fn line_of_entries() {
+        let text = r#"
+users:
+  - id: 2
+    name: bob
+    projects:
+      - id: 1
+        access_level: Developer
+  - username: alice
+    id: 1
+groups:
+  - id: 1
+"#;
+        let fields = ["id", "username", "email"];
+        assert_eq!(Sources::line_of(text, "users", &fields, "1"), Some(9));
+        assert_eq!(Sources::line_of(text, "users", &fields, "alice"), Some(8));
+        assert_eq!(Sources::line_of(text, "groups", &["id"], "1"), Some(11));
+        assert_eq!(Sources::line_of(text, "users", &fields, "3"), None);
+    }
+
+    #[test]
+    fn unrelated_files_in_directory() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("gum-config.yaml"),
+            "meta:\n  version: V1\nconfig:\n  teams: []\nstate: ''\n",
+        )
+        .unwrap();
+        fs::create_dir_all(dir.path().join(".git/hooks")).unwrap();
+        fs::create_dir(dir.path().join("teams")).unwrap();
+        fs::write(dir.path().join(".gitlab-ci.yml"), "stages:\n  - sync\n").unwrap();
+        fs::write(dir.path().join(".git/hooks/config.yaml"), "hooks: []\n").unwrap();
+        fs::write(dir.path().join("policy.yaml"), "max_subjects: 2\n").unwrap();
+        fs::write(dir.path().join("teams/backend.yaml"), "teams:\n  - name: backend\n").unwrap();
+
+        // Only fragment directories are read
+        let dir_name = dir.path().to_string_lossy().to_string();
+        let config_file = ConfigFile::read(dir_name.clone()).unwrap();
+        assert_eq!(config_file.config().teams.len(), 1);
+
+        // Unknown keys are still errors in fragments
+        fs::write(dir.path().join("teams/ci.yaml"), "stages:\n  - sync\n").unwrap();
+        let err = ConfigFile::read(dir_name).unwrap_err();
+        assert!(err.to_string().contains("ci.yaml"));
+        assert!(err.to_string().contains("stages"));
+    }
+}
@@ -1,2 +1,5 @@
 pub(crate) mod common;
+pub(crate) mod fragments;
+pub(crate) mod policy;
 pub(crate) mod v1;
+pub(crate) mod yaml_patch;
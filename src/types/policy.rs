@@ -0,0 +1,551 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+
+use super::fragments::Sources;
+use super::v1::AccessLevel;
+
+// Policies are kept in the meta, they describe what the config is allowed to contain
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct Policy {
+    #[serde(default, skip_serializing_if = "UsersPolicy::is_empty")]
+    pub(crate) users: UsersPolicy,
+    // Rules for projects and groups that teams and users may get
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) objects: Vec<ObjectRule>,
+    // Rules that are checked against the state that is planned by `sync`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) rules: Vec<AccessRule>,
+    // A policy can be kept in a separate file, relative to the config, its rules are added to these ones
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) file: String,
+}
+
+impl Policy {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.objects.is_empty() && self.rules.is_empty() && self.file.is_empty()
+    }
+
+    /// Get the policy with rules from its file
+    pub(crate) fn load(&self, config_path: &str) -> Result<Policy> {
+        if self.file.is_empty() {
+            return Ok(self.clone());
+        }
+        let root = Sources::root_file(config_path);
+        let path = root.parent().unwrap_or(&root).join(&self.file);
+        let text = fs::read_to_string(&path).map_err(|err| {
+            Error::new(
+                err.kind(),
+                format!("Policy file {} can't be read: {}", path.display(), err),
+            )
+        })?;
+        let other: Policy = serde_yaml::from_str(&text).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Policy file {} is not valid: {}", path.display(), err),
+            )
+        })?;
+        let mut policy = self.clone();
+        if policy.users.is_empty() {
+            policy.users = other.users;
+        }
+        policy.objects.extend(other.objects);
+        policy.rules.extend(other.rules);
+        Ok(policy)
+    }
+
+    /// Get problems of the policy itself, so it's not applied half way
+    pub(crate) fn validate(&self) -> Vec<String> {
+        let mut problems = self.users.validate();
+        for (i, rule) in self.objects.iter().enumerate() {
+            problems.extend(rule.validate(i));
+        }
+        problems
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum UserRule {
+    EmailDomains,
+    UsernamePattern,
+    DeniedUsernames,
+    #[serde(rename = "require_2fa")]
+    Require2fa,
+    InternalTeams,
+}
+
+impl fmt::Display for UserRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rule = match self {
+            UserRule::EmailDomains => "email_domains",
+            UserRule::UsernamePattern => "username_pattern",
+            UserRule::DeniedUsernames => "denied_usernames",
+            UserRule::Require2fa => "require_2fa",
+            UserRule::InternalTeams => "internal_teams",
+        };
+        write!(f, "{}", rule)
+    }
+}
+
+// Rules for users, they are checked against GitLab accounts, because the config may have ids only
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct UsersPolicy {
+    // Emails of users must belong to one of these domains
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) email_domains: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) username_pattern: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) denied_usernames: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) require_2fa: bool,
+    // Only users with emails of internal domains can be in internal teams
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) internal_teams: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) internal_domains: Vec<String>,
+    // Rules that only produce warnings, every other rule fails
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) warn: Vec<UserRule>,
+}
+
+// What is known about the user when the policy is checked
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UserFacts<'a> {
+    pub(crate) title: String,
+    pub(crate) username: Option<&'a str>,
+    pub(crate) email: Option<&'a str>,
+    pub(crate) two_factor_enabled: Option<bool>,
+    // Every team of the user, including teams that are included by other teams
+    pub(crate) teams: Vec<&'a str>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Violation {
+    pub(crate) rule: String,
+    pub(crate) message: String,
+    pub(crate) warn: bool,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (policy rule {})", self.message, self.rule)
+    }
+}
+
+impl UsersPolicy {
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == UsersPolicy::default()
+    }
+
+    /// Check the username pattern, it's done before talking to GitLab, so the config is not read in vain
+    fn validate(&self) -> Vec<String> {
+        match Regex::new(&self.username_pattern) {
+            Ok(_) => Vec::new(),
+            Err(err) => vec![format!("The username pattern of the users policy is not valid: {}", err)],
+        }
+    }
+
+    /// Get every rule that the user breaks, things that can't be checked are reported as warnings
+    pub(crate) fn check(&self, user: &UserFacts) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = Vec::new();
+        let mut push = |rule: UserRule, message: String, warn: bool| {
+            violations.push(Violation {
+                rule: rule.to_string(),
+                message,
+                warn: warn || self.warn.contains(&rule),
+            })
+        };
+
+        if let Some(username) = user.username {
+            if !self.username_pattern.is_empty() {
+                if let Ok(re) = Regex::new(&self.username_pattern) {
+                    if !re.is_match(username) {
+                        push(
+                            UserRule::UsernamePattern,
+                            format!(
+                                "User {} has the username {} that doesn't match {}",
+                                user.title, username, self.username_pattern
+                            ),
+                            false,
+                        );
+                    }
+                }
+            }
+            if self
+                .denied_usernames
+                .iter()
+                .any(|d| d.eq_ignore_ascii_case(username))
+            {
+                push(
+                    UserRule::DeniedUsernames,
+                    format!("User {} has the denied username {}", user.title, username),
+                    false,
+                );
+            }
+        }
+
+        let internal_teams: Vec<&str> = user
+            .teams
+            .iter()
+            .filter(|t| self.internal_teams.iter().any(|i| i == *t))
+            .copied()
+            .collect();
+        let needs_email = !self.email_domains.is_empty()
+            || (!internal_teams.is_empty() && !self.internal_domains.is_empty());
+        match user.email {
+            Some(email) => {
+                if !self.email_domains.is_empty() && !in_domains(email, &self.email_domains) {
+                    push(
+                        UserRule::EmailDomains,
+                        format!(
+                            "User {} has the email {} that is not in allowed domains: {}",
+                            user.title,
+                            email,
+                            self.email_domains.join(", ")
+                        ),
+                        false,
+                    );
+                }
+                if !self.internal_domains.is_empty()
+                    && !internal_teams.is_empty()
+                    && !in_domains(email, &self.internal_domains)
+                {
+                    push(
+                        UserRule::InternalTeams,
+                        format!(
+                            "User {} with the external email {} is in internal teams: {}",
+                            user.title,
+                            email,
+                            internal_teams.join(", ")
+                        ),
+                        false,
+                    );
+                }
+            }
+            None if needs_email => push(
+                UserRule::EmailDomains,
+                format!(
+                    "Email of the user {} is unknown, it needs a public email or an admin token to be checked",
+                    user.title
+                ),
+                true,
+            ),
+            None => {}
+        }
+
+        if self.require_2fa {
+            match user.two_factor_enabled {
+                Some(true) => {}
+                Some(false) => push(
+                    UserRule::Require2fa,
+                    format!("User {} doesn't have two-factor authentication", user.title),
+                    false,
+                ),
+                None => push(
+                    UserRule::Require2fa,
+                    format!(
+                        "Two-factor authentication of the user {} can be only checked with an admin token",
+                        user.title
+                    ),
+                    true,
+                ),
+            }
+        }
+        violations
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ObjectKind {
+    #[default]
+    Project,
+    Group,
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectKind::Project => write!(f, "project"),
+            ObjectKind::Group => write!(f, "group"),
+        }
+    }
+}
+
+// A rule restricts grants on projects or groups by their paths
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct ObjectRule {
+    // It's shown in messages, the position of the rule is used otherwise
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) object: ObjectKind,
+    // The rule is applied to grants that members of these teams get, or to every grant if it's empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) teams: Vec<String>,
+    // The rule is applied to objects with paths that match it, or to every object if it's empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) path: String,
+    // Only objects with paths that match it can be granted
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) only: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_access: Option<AccessLevel>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) warn: bool,
+}
+
+// A grant of a project or a group that is defined in the config
+#[derive(Debug, Clone)]
+pub(crate) struct ObjectGrant<'a> {
+    // Where the grant is defined, e.g. "team backend" or "user 1 (alice)"
+    pub(crate) source: String,
+    // Teams which members get the grant
+    pub(crate) teams: Vec<&'a str>,
+    pub(crate) object: ObjectKind,
+    pub(crate) id: u64,
+    pub(crate) path: String,
+    pub(crate) access: AccessLevel,
+}
+
+impl ObjectRule {
+    pub(crate) fn title(&self, index: usize) -> String {
+        match self.name.is_empty() {
+            true => format!("objects[{}]", index),
+            false => self.name.clone(),
+        }
+    }
+
+    fn validate(&self, index: usize) -> Vec<String> {
+        let mut problems: Vec<String> = Vec::new();
+        for pattern in [&self.path, &self.only] {
+            if let Err(err) = Regex::new(pattern) {
+                problems.push(format!("The pattern of the policy rule {} is not valid: {}", self.title(index), err));
+            }
+        }
+        if self.only.is_empty() && self.max_access.is_none() {
+            problems.push(format!(
+                "The policy rule {} restricts nothing, it needs `only` or `max_access`",
+                self.title(index)
+            ));
+        }
+        problems
+    }
+
+    /// Check the grant against the rule, patterns must be validated before
+    pub(crate) fn check(&self, index: usize, grant: &ObjectGrant) -> Option<Violation> {
+        if grant.object != self.object {
+            return None;
+        }
+        let teams: Vec<&str> = grant
+            .teams
+            .iter()
+            .filter(|t| self.teams.iter().any(|r| r == *t))
+            .copied()
+            .collect();
+        if !self.teams.is_empty() && teams.is_empty() {
+            return None;
+        }
+        let matches = |pattern: &str| Regex::new(pattern).is_ok_and(|re| re.is_match(&grant.path));
+        if !self.path.is_empty() && !matches(&self.path) {
+            return None;
+        }
+        let granted = format!(
+            "{} grants {} on the {} {} ({})",
+            grant.source, grant.access, grant.object, grant.path, grant.id
+        );
+        let message = if !self.only.is_empty() && !matches(&self.only) {
+            let who = match teams.is_empty() {
+                true => "everybody".to_string(),
+                false => format!("members of {}", teams.join(", ")),
+            };
+            format!("{}, but {} may only get {}s matching {}", granted, who, self.object, self.only)
+        } else {
+            match self.max_access {
+                Some(max) if grant.access.is_above(max) => {
+                    format!("{}, but {} is the highest access allowed there", granted, max)
+                }
+                _ => return None,
+            }
+        };
+        Some(Violation {
+            rule: self.title(index),
+            message,
+            warn: self.warn,
+        })
+    }
+}
+
+// An access rule, it's evaluated against the access of every subject, not against single grants
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub(crate) struct AccessRule {
+    // It's shown in messages, the position of the rule is used otherwise
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) name: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) warn: bool,
+    #[serde(flatten)]
+    pub(crate) check: AccessCheck,
+}
+
+// Objects that a rule is applied to, every object of the kind is matched by default
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct ObjectFilter {
+    #[serde(default)]
+    pub(crate) object: ObjectKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) id: Option<u64>,
+    // Objects in this group and its subgroups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) in_group: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub(crate) enum AccessCheck {
+    // No more than `max` subjects may have this access or a higher one on an object
+    MaxSubjects {
+        #[serde(flatten)]
+        filter: ObjectFilter,
+        access: AccessLevel,
+        max: usize,
+    },
+    // This access or a higher one may only be granted by these teams
+    OnlyTeams {
+        #[serde(flatten)]
+        filter: ObjectFilter,
+        access: AccessLevel,
+        teams: Vec<String>,
+    },
+    // Users may get objects only through teams
+    NoDirect {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        object: Option<ObjectKind>,
+    },
+}
+
+impl AccessRule {
+    pub(crate) fn title(&self, index: usize) -> String {
+        match self.name.is_empty() {
+            true => format!("rules[{}]", index),
+            false => self.name.clone(),
+        }
+    }
+}
+
+impl ObjectFilter {
+    /// Check whether the object is matched, `path_of` is only called for `in_group` filters
+    pub(crate) fn matches(
+        &self,
+        object: ObjectKind,
+        id: u64,
+        path_of: &mut dyn FnMut(ObjectKind, u64) -> Result<String>,
+    ) -> Result<bool> {
+        if object != self.object || self.id.is_some_and(|i| i != id) {
+            return Ok(false);
+        }
+        match self.in_group {
+            Some(group) if object == ObjectKind::Group && group == id => Ok(true),
+            Some(group) => {
+                let group_path = path_of(ObjectKind::Group, group)?;
+                let path = path_of(object, id)?;
+                Ok(!group_path.is_empty() && path.starts_with(&format!("{}/", group_path)))
+            }
+            None => Ok(true),
+        }
+    }
+}
+
+fn in_domains(email: &str, domains: &[String]) -> bool {
+    match email.rsplit_once('@') {
+        Some((_, domain)) => domains.iter().any(|d| d.eq_ignore_ascii_case(domain)),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ObjectGrant, ObjectKind, ObjectRule, UserFacts, UserRule, UsersPolicy};
+    use crate::types::v1::AccessLevel;
+
+    #[test]
+    fn check_user() {
+        let policy = UsersPolicy {
+            email_domains: vec!["example.com".to_string(), "contractors.example.com".to_string()],
+            username_pattern: "^[a-z][a-z0-9.-]+$".to_string(),
+            denied_usernames: vec!["root".to_string()],
+            require_2fa: true,
+            internal_teams: vec!["backend".to_string()],
+            internal_domains: vec!["example.com".to_string()],
+            warn: vec![UserRule::Require2fa],
+        };
+        let internal = UserFacts {
+            title: "1 (alice)".to_string(),
+            username: Some("alice"),
+            email: Some("alice@example.com"),
+            two_factor_enabled: Some(true),
+            teams: vec!["backend"],
+        };
+        assert!(policy.check(&internal).is_empty());
+
+        let contractor = UserFacts {
+            title: "2 (Bob)".to_string(),
+            username: Some("Bob"),
+            email: Some("bob@contractors.example.com"),
+            two_factor_enabled: Some(false),
+            teams: vec!["frontend", "backend"],
+        };
+        let violations: Vec<(String, bool)> = policy
+            .check(&contractor)
+            .into_iter()
+            .map(|v| (v.rule, v.warn))
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                ("username_pattern".to_string(), false),
+                ("internal_teams".to_string(), false),
+                ("require_2fa".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_object() {
+        let external = ObjectRule {
+            teams: vec!["contractors".to_string()],
+            only: "^external/".to_string(),
+            ..Default::default()
+        };
+        let prod = ObjectRule {
+            name: "prod".to_string(),
+            path: "-prod$".to_string(),
+            max_access: Some(AccessLevel::Reporter),
+            ..Default::default()
+        };
+        let mut grant = ObjectGrant {
+            source: "team shared".to_string(),
+            teams: vec!["shared", "contractors"],
+            object: ObjectKind::Project,
+            id: 12,
+            path: "infra/billing-prod".to_string(),
+            access: AccessLevel::Developer,
+        };
+        assert_eq!(
+            external.check(0, &grant).unwrap().message,
+            "team shared grants Developer on the project infra/billing-prod (12), \
+            but members of contractors may only get projects matching ^external/"
+        );
+        let violation = prod.check(1, &grant).unwrap();
+        assert_eq!(violation.rule, "prod");
+        assert!(violation.message.ends_with("but Reporter is the highest access allowed there"));
+
+        grant.path = "external/docs".to_string();
+        assert!(external.check(0, &grant).is_none());
+        assert!(prod.check(1, &grant).is_none());
+    }
+}
@@ -1,18 +1,27 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::io::{ErrorKind, Result};
+use std::path::Path;
 use std::{io::Error, str::FromStr};
 
-use super::common::{Version, Versions};
+use super::common::{fnv1a, Version, Versions};
+use super::fragments::Sources;
+use super::policy::Policy;
+use super::yaml_patch;
 
 // The first version of a config file
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 pub struct ConfigFile {
     meta: Meta,
+    // Glob patterns of files with teams, users and groups, relative to this file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    include: Vec<String>,
     config: Config,
     state: String,
+    #[serde(skip)]
+    sources: Sources,
 }
 
 impl ConfigFile {
@@ -22,6 +31,12 @@ impl ConfigFile {
         &self.meta
     }
 
+    /// Get a mutable reference to the config file's meta.
+    #[must_use]
+    pub(crate) fn meta_mut(&mut self) -> &mut Meta {
+        &mut self.meta
+    }
+
     /// Get a reference to the config file's config.
     #[must_use]
     pub(crate) fn config(&self) -> &Config {
@@ -46,37 +61,65 @@ impl ConfigFile {
     }
 
     pub(crate) fn read(file_name: String) -> Result<Self> {
-        let f = OpenOptions::new().write(true).read(true).open(file_name);
+        let root = Sources::root_file(&file_name);
+        let text = fs::read_to_string(&root)?;
+        let d: std::result::Result<ConfigFile, _> = serde_yaml::from_str(&text);
+        match d {
+            Ok(mut r) => {
+                r.sources = Sources::read(&file_name, &r.include, &text, &mut r.config)?;
+                if r.meta.version == Versions::V2 {
+                    let aliases = r.meta.aliases.clone();
+                    aliases.apply(&mut r.config);
+                }
+                Ok(r)
+            }
+            Err(err) => Err(Error::other(format!("{}: {}", root.display(), err))),
+        }
+    }
 
-        let f = match f {
-            Ok(file) => file,
-            Err(err) => {
-                return Err(err);
+    /// Write the config back, entities that are coming from fragments are written to the same files
+    pub(crate) fn write(&self, file_name: String) -> Result<()> {
+        let mut config_file = self.clone();
+        match config_file.meta.version {
+            // V1 keeps names in every entry, so aliases are not written at all
+            Versions::V1 => config_file.meta.aliases = Aliases::default(),
+            Versions::V2 => {
+                config_file.meta.aliases =
+                    Aliases::from_config(&config_file.config, &self.meta.aliases);
+                Aliases::strip(&mut config_file.config);
             }
         };
-        // TODO: Handle serde error
-        let d: std::result::Result<ConfigFile, _> = serde_yaml::from_reader(&f);
-        match d {
-            Ok(r) => Ok(r),
-            Err(err) => Err(Error::new(ErrorKind::Other, err.to_string())),
+
+        let (config, fragments) = self.sources.split(&config_file.config);
+        config_file.config = config;
+        ConfigFile::write_yaml(&Sources::root_file(&file_name), &config_file)?;
+        for (path, fragment) in fragments.iter() {
+            ConfigFile::write_yaml(path, fragment)?;
         }
+        Ok(())
     }
-    pub(crate) fn write(&self, file_name: String) -> Result<()> {
+
+    // Only changed parts of an existing file are rewritten, so comments and the layout are kept
+    fn write_yaml<S: Serialize>(path: &Path, data: &S) -> Result<()> {
+        if let Ok(original) = fs::read_to_string(path) {
+            let value = serde_yaml::to_value(data)
+                .map_err(|err| Error::other(err.to_string()))?;
+            if let Some(text) = yaml_patch::patch(&original, &value) {
+                if text != original {
+                    fs::write(path, text)?;
+                }
+                return Ok(());
+            }
+        }
+
         let f = OpenOptions::new()
             .create(true)
             .write(true)
             .read(true)
             .truncate(true)
-            .open(file_name);
-
-        let f = match f {
-            Ok(file) => file,
-            Err(err) => {
-                return Err(err);
-            }
-        };
+            .open(path)?;
 
-        match serde_yaml::to_writer(&f, &self) {
+        match serde_yaml::to_writer(&f, data) {
             Ok(()) => Ok(()),
             Err(err) => Err(Error::new(ErrorKind::Other, err.to_string())),
         }
@@ -93,6 +136,10 @@ impl Version for ConfigFile {
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub(crate) struct Meta {
     version: Versions,
+    #[serde(default, skip_serializing_if = "Aliases::is_empty")]
+    aliases: Aliases,
+    #[serde(default, skip_serializing_if = "Policy::is_empty")]
+    policy: Policy,
 }
 
 impl Meta {
@@ -101,12 +148,187 @@ impl Meta {
     pub(crate) fn version(&self) -> Versions {
         self.version
     }
+
+    /// Set the meta's version.
+    pub(crate) fn set_version(&mut self, version: Versions) {
+        self.version = version;
+    }
+
+    /// Get a reference to the meta's aliases.
+    #[must_use]
+    pub(crate) fn aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+
+    /// Get a reference to the meta's policy.
+    #[must_use]
+    pub(crate) fn policy(&self) -> &Policy {
+        &self.policy
+    }
 }
 
 impl Default for Meta {
     fn default() -> Self {
         Self {
             version: Versions::V1,
+            aliases: Default::default(),
+            policy: Default::default(),
+        }
+    }
+}
+
+// Aliases are used since V2 config files, projects and groups are named only
+// once here instead of being named in every entry that refers to them
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct Aliases {
+    #[serde(default)]
+    pub(crate) projects: BTreeMap<u64, String>,
+    #[serde(default)]
+    pub(crate) groups: BTreeMap<u64, String>,
+}
+
+impl Aliases {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.projects.is_empty() && self.groups.is_empty()
+    }
+
+    /// Collect names of all projects and groups that are referenced in the config.
+    /// If an entry has no name, the alias is taken from the previous aliases.
+    pub(crate) fn from_config(config: &Config, previous: &Aliases) -> Self {
+        let mut aliases = Aliases::default();
+        let mut projects: Vec<&Project> = Vec::new();
+        let mut groups: Vec<(u64, &String)> = Vec::new();
+        for t in config.teams.iter() {
+            projects.extend(t.projects.iter());
+            groups.extend(t.namespaces.iter().map(|n| (n.id, &n.name)));
+        }
+        for u in config.users.iter() {
+            projects.extend(u.projects.iter());
+            groups.extend(u.namespaces.iter().map(|n| (n.id, &n.name)));
+        }
+        for g in config.groups.iter() {
+            groups.push((g.id, &g.name));
+            projects.extend(g.projects.iter());
+            groups.extend(g.namespaces.iter().map(|n| (n.id, &n.name)));
+        }
+
+        for p in projects {
+            Aliases::insert(&mut aliases.projects, &previous.projects, p.id, &p.name);
+        }
+        for (id, name) in groups {
+            Aliases::insert(&mut aliases.groups, &previous.groups, id, name);
+        }
+        aliases
+    }
+
+    fn insert(
+        aliases: &mut BTreeMap<u64, String>,
+        previous: &BTreeMap<u64, String>,
+        id: u64,
+        name: &str,
+    ) {
+        if id == 0 {
+            return;
+        }
+        if !name.is_empty() {
+            aliases.insert(id, name.to_string());
+        } else if let Some(alias) = previous.get(&id) {
+            aliases.entry(id).or_insert_with(|| alias.clone());
+        }
+    }
+
+    /// Set names of projects and groups in the config from aliases
+    pub(crate) fn apply(&self, config: &mut Config) {
+        let project_name = |p: &mut Project| {
+            if let Some(alias) = self.projects.get(&p.id) {
+                p.name = alias.clone();
+            }
+        };
+        let group_name = |id: u64, name: &mut String| {
+            if let Some(alias) = self.groups.get(&id) {
+                *name = alias.clone();
+            }
+        };
+        for t in config.teams.iter_mut() {
+            t.projects.iter_mut().for_each(project_name);
+            t.namespaces.iter_mut().for_each(|n| group_name(n.id, &mut n.name));
+        }
+        for u in config.users.iter_mut() {
+            u.projects.iter_mut().for_each(project_name);
+            u.namespaces.iter_mut().for_each(|n| group_name(n.id, &mut n.name));
+        }
+        for g in config.groups.iter_mut() {
+            group_name(g.id, &mut g.name);
+            g.projects.iter_mut().for_each(project_name);
+            g.namespaces.iter_mut().for_each(|n| group_name(n.id, &mut n.name));
+        }
+    }
+
+    /// Remove names of projects and groups from the config, they are kept in aliases
+    pub(crate) fn strip(config: &mut Config) {
+        for t in config.teams.iter_mut() {
+            t.projects.iter_mut().for_each(|p| p.name.clear());
+            t.namespaces.iter_mut().for_each(|n| n.name.clear());
+        }
+        for u in config.users.iter_mut() {
+            u.projects.iter_mut().for_each(|p| p.name.clear());
+            u.namespaces.iter_mut().for_each(|n| n.name.clear());
+        }
+        for g in config.groups.iter_mut() {
+            g.name.clear();
+            g.projects.iter_mut().for_each(|p| p.name.clear());
+            g.namespaces.iter_mut().for_each(|n| n.name.clear());
+        }
+    }
+}
+
+// Lock file keeps ids of projects, groups and users that are referenced by path, username
+// or email in the config, so when one of them starts pointing to another entity, it's visible as a change
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct LockFile {
+    #[serde(default)]
+    pub(crate) projects: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub(crate) groups: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub(crate) users: BTreeMap<String, u64>,
+}
+
+impl LockFile {
+    /// Get a name of the lock file that belongs to the config file
+    pub(crate) fn file_name_for(config_path: &str) -> String {
+        Sources::root_file(config_path)
+            .with_extension("lock")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.projects.is_empty() && self.groups.is_empty() && self.users.is_empty()
+    }
+
+    pub(crate) fn read(file_name: String) -> Result<Self> {
+        let f = match OpenOptions::new().read(true).open(file_name) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(LockFile::default()),
+            Err(err) => return Err(err),
+        };
+        let d: std::result::Result<LockFile, _> = serde_yaml::from_reader(&f);
+        match d {
+            Ok(r) => Ok(r),
+            Err(err) => Err(Error::other(err.to_string())),
+        }
+    }
+
+    pub(crate) fn write(&self, file_name: String) -> Result<()> {
+        let f = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(file_name)?;
+        match serde_yaml::to_writer(&f, &self) {
+            Ok(()) => Ok(()),
+            Err(err) => Err(Error::other(err.to_string())),
         }
     }
 }
@@ -120,9 +342,141 @@ pub struct Config {
     pub(crate) users: Vec<User>,
     #[serde(default)]
     pub(crate) groups: Vec<Group>,
+    // People who don't have a GitLab account yet, they're moved to users when they accept the invitation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) invites: Vec<Invite>,
 }
 
-impl Config {}
+impl Config {
+    /// Find a team by its name
+    pub(crate) fn team(&self, name: &str) -> Option<&Team> {
+        self.teams.iter().find(|t| t.name == name)
+    }
+
+    /// Get teams of the user with the user's role in them: the default team, teams that list the user
+    /// as a member or are listed by the user, and every team that is included by them.
+    /// A role is inherited by included teams, the highest one wins
+    pub(crate) fn user_teams(&self, u: &User) -> Vec<(&Team, TeamRole)> {
+        let mut queue: Vec<(String, TeamRole)> = vec![("default".to_string(), TeamRole::Member)];
+        queue.extend(u.teams.iter().map(|t| (t.clone(), TeamRole::Member)));
+        for t in self.teams.iter() {
+            for m in t.members.iter().filter(|m| m.is(u)) {
+                queue.push((t.name.clone(), m.role));
+            }
+        }
+
+        let mut visited: HashMap<String, TeamRole> = HashMap::new();
+        while let Some((name, role)) = queue.pop() {
+            if visited.get(&name).is_some_and(|r| *r >= role) {
+                continue;
+            }
+            if let Some(team) = self.team(&name) {
+                queue.extend(team.includes.iter().map(|i| (i.clone(), role)));
+                visited.insert(name, role);
+            }
+        }
+        self.teams
+            .iter()
+            .filter_map(|t| visited.get(&t.name).map(|r| (t, *r)))
+            .collect()
+    }
+
+    /// Get the team and every team that includes it, members of all of them get grants of the team
+    pub(crate) fn including_teams(&self, name: &str) -> Vec<&Team> {
+        let mut queue: Vec<&str> = vec![name];
+        let mut visited: HashSet<&str> = HashSet::new();
+        while let Some(name) = queue.pop() {
+            if !visited.insert(name) {
+                continue;
+            }
+            for t in self.teams.iter().filter(|t| t.includes.iter().any(|i| i == name)) {
+                queue.push(&t.name);
+            }
+        }
+        self.teams
+            .iter()
+            .filter(|t| visited.contains(t.name.as_str()))
+            .collect()
+    }
+
+    /// Check whether projects are given by `projects_in` rules anywhere in the config
+    pub(crate) fn has_rules(&self) -> bool {
+        self.teams.iter().any(|t| !t.projects_in.is_empty())
+            || self.users.iter().any(|u| !u.projects_in.is_empty())
+            || self.groups.iter().any(|g| !g.projects_in.is_empty())
+    }
+
+    /// Get problems with team references: teams that don't exist and cycles of includes
+    pub(crate) fn check_teams(&self) -> Vec<String> {
+        let mut problems: Vec<String> = Vec::new();
+        for t in self.teams.iter() {
+            for i in t.includes.iter() {
+                if self.team(i).is_none() {
+                    problems.push(format!("Team {} includes the team {} that doesn't exist", t.name, i));
+                }
+            }
+        }
+        for u in self.users.iter() {
+            for t in u.teams.iter() {
+                if self.team(t).is_none() {
+                    problems.push(format!("User {} is in the team {} that doesn't exist", u.name, t));
+                }
+            }
+        }
+        for i in self.invites.iter() {
+            for t in i.teams.iter() {
+                if self.team(t).is_none() {
+                    problems.push(format!("Invite of {} is in the team {} that doesn't exist", i.email, t));
+                }
+            }
+        }
+        for t in self.teams.iter() {
+            for m in t.members.iter() {
+                if UserRef::from_str(&m.user).is_err() {
+                    problems.push(format!("Team {} has a member without a user", t.name));
+                } else if !self.users.iter().any(|u| m.is(u)) {
+                    problems.push(format!(
+                        "Team {} has the member {} that is not defined in users",
+                        t.name, m.user
+                    ));
+                }
+            }
+        }
+
+        let mut done: HashSet<String> = HashSet::new();
+        for t in self.teams.iter() {
+            let mut path: Vec<String> = Vec::new();
+            self.find_cycles(&t.name, &mut path, &mut done, &mut problems);
+        }
+        problems
+    }
+
+    fn find_cycles(
+        &self,
+        name: &str,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        problems: &mut Vec<String>,
+    ) {
+        if let Some(start) = path.iter().position(|n| n == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name.to_string());
+            problems.push(format!("Teams include each other: {}", cycle.join(" -> ")));
+            return;
+        }
+        if done.contains(name) {
+            return;
+        }
+        if let Some(team) = self.team(name) {
+            path.push(name.to_string());
+            for i in team.includes.iter() {
+                self.find_cycles(i, path, done, problems);
+            }
+            path.pop();
+        }
+        done.insert(name.to_string());
+    }
+}
 
 impl Default for Config {
     fn default() -> Self {
@@ -133,39 +487,83 @@ impl Default for Config {
             }],
             users: Default::default(),
             groups: Default::default(),
+            invites: Default::default(),
         }
     }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 pub struct Group {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub(crate) name: String,
+    #[serde(default, skip_serializing_if = "is_unresolved")]
     pub(crate) id: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) path: String,
     pub(crate) projects: Vec<Project>,
     pub(crate) namespaces: Vec<Namespace>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) projects_in: Vec<ProjectsIn>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 pub struct Namespace {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub(crate) name: String,
     pub(crate) access_level: AccessLevel,
+    #[serde(default, skip_serializing_if = "is_unresolved")]
     pub(crate) id: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) path: String,
+    #[serde(default)]
     pub(crate) url: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub(crate) struct Project {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub(crate) name: String,
+    #[serde(default, skip_serializing_if = "is_unresolved")]
     pub(crate) id: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) path: String,
     pub(crate) access_level: AccessLevel,
 }
 
+// Entries that are referenced by path only don't have an id until the path is resolved
+fn is_unresolved(id: &u64) -> bool {
+    *id == 0
+}
+
+// Projects are the same when their ids are equal, unresolved ones are compared by path
 impl PartialEq for Project {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        match (self.id, other.id) {
+            (0, 0) => self.path == other.path,
+            (id, other_id) => id == other_id,
+        }
     }
 }
 
+// Rule that grants access to every project in a group, projects are found on each sync
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct ProjectsIn {
+    #[serde(default, skip_serializing_if = "is_unresolved")]
+    pub(crate) group: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) path: String,
+    // Look for projects in subgroups too
+    #[serde(default)]
+    pub(crate) recursive: bool,
+    // Regex that project names should match
+    #[serde(default, rename = "match", skip_serializing_if = "String::is_empty")]
+    pub(crate) pattern: String,
+    // Regexes of project names that should be skipped
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) exclude: Vec<String>,
+    pub(crate) access: AccessLevel,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 pub struct Team {
     pub(crate) name: String,
@@ -173,17 +571,222 @@ pub struct Team {
     pub(crate) projects: Vec<Project>,
     #[serde(default)]
     pub(crate) namespaces: Vec<Namespace>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) projects_in: Vec<ProjectsIn>,
+    // Names of teams which grants are inherited by this team
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) includes: Vec<String>,
+    // Members can be defined here as well as in the `teams` field of a user
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) members: Vec<TeamMember>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct TeamMember {
+    // An id, a username or an email, the same way users are referenced
+    #[serde(serialize_with = "serialize_member")]
+    pub(crate) user: String,
+    #[serde(default, skip_serializing_if = "TeamRole::is_member")]
+    pub(crate) role: TeamRole,
+}
+
+// Ids of members are written as numbers, like ids of users, so they aren't quoted in the file
+fn serialize_member<S: Serializer>(user: &str, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    match user.parse::<u64>() {
+        Ok(id) => serializer.serialize_u64(id),
+        Err(_) => serializer.serialize_str(user),
+    }
+}
+
+impl TeamMember {
+    /// Check whether the member is the user from the config
+    pub(crate) fn is(&self, u: &User) -> bool {
+        match UserRef::from_str(&self.user) {
+            Ok(user_ref) => user_ref.matches(u),
+            Err(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Copy, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TeamRole {
+    #[default]
+    Member,
+    Lead,
+}
+
+impl TeamRole {
+    fn is_member(&self) -> bool {
+        *self == TeamRole::Member
+    }
+
+    /// Get the access level that a member with this role gets from a team grant
+    pub(crate) fn access(&self, access_level: AccessLevel) -> AccessLevel {
+        match (self, access_level) {
+            (TeamRole::Lead, AccessLevel::Developer) => AccessLevel::Maintainer,
+            _ => access_level,
+        }
+    }
+}
+
+impl FromStr for TeamRole {
+    fn from_str(input: &str) -> Result<TeamRole> {
+        match input {
+            "member" => Ok(TeamRole::Member),
+            "lead" => Ok(TeamRole::Lead),
+            _ => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("team role {} can not be found", input),
+            )),
+        }
+    }
+    type Err = Error;
+}
+
+impl fmt::Display for TeamRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TeamRole::Member => write!(f, "member"),
+            TeamRole::Lead => write!(f, "lead"),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 pub struct User {
+    #[serde(default, skip_serializing_if = "is_unresolved")]
     pub(crate) id: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) username: String,
+    // Users can be found by email only with an admin token
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) email: String,
+    #[serde(default)]
     pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) teams: Vec<String>,
+    #[serde(default)]
+    pub(crate) projects: Vec<Project>,
+    #[serde(default)]
+    pub(crate) namespaces: Vec<Namespace>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) projects_in: Vec<ProjectsIn>,
+}
+
+impl User {
+    /// Get a username or an email that is used to find the user in GitLab
+    pub(crate) fn identity(&self) -> Option<UserRef> {
+        if !self.username.is_empty() {
+            Some(UserRef::Username(self.username.clone()))
+        } else if !self.email.is_empty() {
+            Some(UserRef::Email(self.email.clone()))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct Invite {
+    pub(crate) email: String,
+    #[serde(default)]
     pub(crate) teams: Vec<String>,
+    #[serde(default)]
     pub(crate) projects: Vec<Project>,
+    #[serde(default)]
     pub(crate) namespaces: Vec<Namespace>,
 }
 
+impl Invite {
+    /// Get the user that the invite grants access to, so it's planned the same way as users
+    pub(crate) fn as_user(&self) -> User {
+        User {
+            email: self.email.clone(),
+            teams: self.teams.clone(),
+            projects: self.projects.clone(),
+            namespaces: self.namespaces.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Get the key of the invite in the state, the highest bit is set, so it can't be a GitLab id
+    pub(crate) fn state_id(&self) -> u64 {
+        fnv1a(self.email.to_lowercase().as_bytes()) | 1 << 63
+    }
+}
+
+// UserRef is what can be used to point to a user: id, username or email
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum UserRef {
+    Id(u64),
+    Username(String),
+    Email(String),
+}
+
+impl UserRef {
+    /// Check whether the config entry is the user that is referenced
+    pub(crate) fn matches(&self, user: &User) -> bool {
+        match self {
+            UserRef::Id(id) => user.id == *id,
+            UserRef::Username(username) => user.username.eq_ignore_ascii_case(username),
+            UserRef::Email(email) => user.email.eq_ignore_ascii_case(email),
+        }
+    }
+
+    /// Check whether the config entry is the user, ids from the lock file are used,
+    /// so a user that is set by id in the config can be found by username or email too
+    pub(crate) fn matches_locked(&self, user: &User, lock_file: &LockFile) -> bool {
+        if self.matches(user) {
+            return true;
+        }
+        let locked = |identity: &UserRef| {
+            let identity = identity.to_string();
+            lock_file
+                .users
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(&identity))
+                .map(|(_, id)| *id)
+        };
+        let user_id = match (user.id, user.identity()) {
+            (0, Some(identity)) => locked(&identity),
+            (0, None) => None,
+            (id, _) => Some(id),
+        };
+        let id = match self {
+            UserRef::Id(id) => Some(*id),
+            user_ref => locked(user_ref),
+        };
+        user_id.is_some() && user_id == id
+    }
+}
+
+impl FromStr for UserRef {
+    fn from_str(input: &str) -> Result<UserRef> {
+        if input.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "user can't be empty"));
+        }
+        if let Ok(id) = input.parse::<u64>() {
+            Ok(UserRef::Id(id))
+        } else if input.contains('@') {
+            Ok(UserRef::Email(input.to_string()))
+        } else {
+            Ok(UserRef::Username(input.to_string()))
+        }
+    }
+    type Err = Error;
+}
+
+impl fmt::Display for UserRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UserRef::Id(id) => write!(f, "{}", id),
+            UserRef::Username(username) => write!(f, "{}", username),
+            UserRef::Email(email) => write!(f, "{}", email),
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Serialize, Deserialize, Copy, Clone)]
 pub(crate) enum AccessLevel {
     Guest,
@@ -213,6 +816,23 @@ impl FromStr for AccessLevel {
 }
 
 impl AccessLevel {
+    pub(crate) fn is_above(self, other: AccessLevel) -> bool {
+        self.to_gitlab_access_level().as_u64() > other.to_gitlab_access_level().as_u64()
+    }
+
+    /// Pick the higher access when the same object is granted several times, it's never higher than Maintainer
+    pub(crate) fn higher(self, other: AccessLevel) -> AccessLevel {
+        if self == AccessLevel::Maintainer || other == AccessLevel::Maintainer {
+            AccessLevel::Maintainer
+        } else if self == AccessLevel::Developer || other == AccessLevel::Developer {
+            AccessLevel::Developer
+        } else if self == AccessLevel::Reporter || other == AccessLevel::Reporter {
+            AccessLevel::Reporter
+        } else {
+            AccessLevel::Guest
+        }
+    }
+
     pub(crate) fn to_gitlab_access_level(self) -> gitlab::api::common::AccessLevel {
         match self {
             AccessLevel::Guest => gitlab::api::common::AccessLevel::Guest,
@@ -234,6 +854,14 @@ impl AccessLevel {
             gitlab::AccessLevel::Anonymous => todo!(),
         }
     }
+
+    /// Get the access level of a GitLab member, there is none for members without access
+    pub(crate) fn from_gitlab_member(access_level: gitlab::AccessLevel) -> Option<Self> {
+        match access_level {
+            gitlab::AccessLevel::Anonymous => None,
+            access_level => Some(AccessLevel::from_gitlab_access_level(access_level)),
+        }
+    }
 }
 
 impl fmt::Display for AccessLevel {
@@ -252,6 +880,8 @@ impl Default for AccessLevel {
 pub(crate) enum EntityType {
     User,
     Group,
+    // A pending invitation, it's kept by a hash of the email, because there is no user id yet
+    Invite,
 }
 
 impl Default for EntityType {
@@ -260,11 +890,28 @@ impl Default for EntityType {
     }
 }
 
+impl FromStr for EntityType {
+    fn from_str(input: &str) -> Result<EntityType> {
+        match input {
+            "user" => Ok(EntityType::User),
+            "group" => Ok(EntityType::Group),
+            _ => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("entity type {} can not be found", input),
+            )),
+        }
+    }
+    type Err = Error;
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub(crate) struct AccessUnit {
     pub(crate) entity: EntityType,
     pub(crate) projects: HashMap<u64, AccessLevel>,
     pub(crate) namespaces: HashMap<u64, AccessLevel>,
+    // Only invites have it
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) email: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -301,49 +948,7 @@ impl AccessUnit {
             entity: entity_type,
             projects: Default::default(),
             namespaces: Default::default(),
-        }
-    }
-    pub(crate) fn write_to_file(state: HashMap<u64, AccessUnit>, file_name: String) -> Result<()> {
-        let f = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .read(true)
-            .truncate(true)
-            .open(file_name);
-        let f = match f {
-            Ok(file) => file,
-            Err(err) => {
-                return Err(err);
-            }
-        };
-        match serde_json::to_writer(&f, &state) {
-            Ok(()) => return Ok(()),
-            Err(err) => {
-                return Err(Error::new(ErrorKind::Other, err.to_string()));
-            }
-        }
-    }
-    pub(crate) fn from_string(data: String) -> Result<HashMap<u64, AccessUnit>> {
-        let d: std::result::Result<HashMap<u64, AccessUnit>, _> = serde_json::from_str(&data);
-        match d {
-            Ok(r) => Ok(r),
-            Err(err) => Err(Error::new(ErrorKind::Other, err.to_string())),
-        }
-    }
-    pub(crate) fn read_from_file(file_name: String) -> Result<HashMap<u64, AccessUnit>> {
-        let f = OpenOptions::new().write(true).read(true).open(file_name);
-
-        let f = match f {
-            Ok(file) => file,
-            Err(err) => {
-                return Err(err);
-            }
-        };
-        // TODO: Handle serde error
-        let d: std::result::Result<HashMap<u64, AccessUnit>, _> = serde_json::from_reader(&f);
-        match d {
-            Ok(r) => Ok(r),
-            Err(err) => Err(Error::new(ErrorKind::Other, err.to_string())),
+            email: Default::default(),
         }
     }
 }
@@ -0,0 +1,665 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_yaml::{Mapping, Sequence, Value};
+
+// Config files are edited by people, so comments, blank lines and the order of keys should survive
+// when gum changes them. The original text is parsed into blocks with line spans, then only blocks
+// that have different values are replaced.
+
+lazy_static! {
+    static ref KEY: Regex =
+        Regex::new(r#"^("(?:[^"\\]|\\.)*"|'[^']*'|[^\s#'"\-\[\]{}&*!|>%@`][^:#]*?)\s*:(\s|$)"#)
+            .unwrap();
+}
+
+// A line of the document without indentation, lines after `- ` are virtual: they start after the dash
+#[derive(Clone)]
+struct Line {
+    no: usize,
+    indent: usize,
+    text: String,
+}
+
+enum Block {
+    Map { indent: usize, entries: Vec<Entry> },
+    Seq { indent: usize, items: Vec<Item> },
+    Inline,
+}
+
+struct Entry {
+    key: String,
+    start: usize,
+    end: usize,
+    // The part of the line before the key when the key is on the same line with a dash
+    dash: Option<String>,
+    value: Block,
+}
+
+struct Item {
+    start: usize,
+    end: usize,
+    value: Block,
+}
+
+struct Edit {
+    start: usize,
+    end: usize,
+    lines: Vec<String>,
+}
+
+// How far blocks are indented under their keys, new blocks are written the same way as their neighbours
+#[derive(Clone, Copy)]
+struct Style {
+    seq: usize,
+    map: usize,
+}
+
+impl Style {
+    // Sibling keys are looked at first, then the first nested blocks of the document
+    fn of(indent: usize, entries: &[Entry], fallback: Style) -> Style {
+        let mut style = fallback;
+        for e in entries.iter().rev() {
+            match e.value {
+                Block::Seq { indent: nested, .. } => style.seq = nested - indent,
+                Block::Map { indent: nested, .. } => style.map = nested - indent,
+                Block::Inline => {}
+            }
+        }
+        style
+    }
+
+    fn detect(block: &Block, style: &mut (Option<usize>, Option<usize>)) {
+        match block {
+            Block::Map { indent, entries } => {
+                for e in entries.iter() {
+                    match e.value {
+                        Block::Seq { indent: nested, .. } => {
+                            style.0.get_or_insert(nested - indent);
+                        }
+                        Block::Map { indent: nested, .. } => {
+                            style.1.get_or_insert(nested - indent);
+                        }
+                        Block::Inline => {}
+                    }
+                    Style::detect(&e.value, style);
+                }
+            }
+            Block::Seq { items, .. } => {
+                for item in items.iter() {
+                    Style::detect(&item.value, style);
+                }
+            }
+            Block::Inline => {}
+        }
+    }
+}
+
+/// Get the new text of the document that keeps everything from the original text that's not changed.
+/// None is returned when the original text can't be patched, then it should be written from scratch
+pub(crate) fn patch(original: &str, new: &Value) -> Option<String> {
+    let old: Value = serde_yaml::from_str(original).ok()?;
+    let old = normalize(&old);
+    let new = normalize(new);
+    let raw: Vec<&str> = original.lines().collect();
+
+    let mut lines: Vec<Line> = Vec::new();
+    for (no, line) in raw.iter().enumerate() {
+        let trimmed = line.trim_start_matches(' ');
+        if trimmed.trim().is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if trimmed.starts_with('\t') || trimmed.starts_with("---") || trimmed.starts_with("...") {
+            return None;
+        }
+        lines.push(Line {
+            no,
+            indent: line.len() - trimmed.len(),
+            text: trimmed.to_string(),
+        });
+    }
+    if lines.is_empty() {
+        return None;
+    }
+
+    let root = parse_block(&lines, &raw)?;
+    // Without nested blocks in the document, they're written the way serde_yaml does it
+    let mut detected = (None, None);
+    Style::detect(&root, &mut detected);
+    let style = Style {
+        seq: detected.0.unwrap_or(0),
+        map: detected.1.unwrap_or(2),
+    };
+    let mut edits: Vec<Edit> = Vec::new();
+    if !patch_value(&root, &old, &new, style, &mut edits) {
+        return None;
+    }
+
+    let mut result: Vec<String> = raw.iter().map(|l| l.to_string()).collect();
+    // Insertions go before removals that start at the same line, so they are applied after them
+    edits.sort_by_key(|e| (e.start, e.end));
+    for e in edits.into_iter().rev() {
+        result.splice(e.start..e.end, e.lines);
+    }
+    let mut text = result.join("\n");
+    text.push('\n');
+
+    // The patched text must mean exactly the same as the new value
+    let patched: Value = serde_yaml::from_str(&text).ok()?;
+    if normalize(&patched) != new {
+        return None;
+    }
+    Some(text)
+}
+
+// Empty values are not written by gum, but they can be in the original text, so they are ignored
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Mapping(m) => {
+            let mut result = Mapping::new();
+            for (k, v) in m.iter() {
+                let v = normalize(v);
+                if !is_empty(&v) {
+                    result.insert(k.clone(), v);
+                }
+            }
+            Value::Mapping(result)
+        }
+        Value::Sequence(s) => Value::Sequence(s.iter().map(normalize).collect()),
+        _ => value.clone(),
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Mapping(m) => m.is_empty(),
+        Value::Sequence(s) => s.is_empty(),
+        _ => false,
+    }
+}
+
+fn key_string(key: &Value) -> Option<String> {
+    match key {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_block(lines: &[Line], raw: &[&str]) -> Option<Block> {
+    let first = lines.first()?;
+    if first.text == "-" || first.text.starts_with("- ") {
+        parse_seq(lines, raw)
+    } else if KEY.is_match(&first.text) {
+        parse_map(lines, raw)
+    } else {
+        Some(Block::Inline)
+    }
+}
+
+fn parse_seq(lines: &[Line], raw: &[&str]) -> Option<Block> {
+    let indent = lines[0].indent;
+    let mut items: Vec<Item> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+        if line.indent != indent || !(line.text == "-" || line.text.starts_with("- ")) {
+            return None;
+        }
+        let mut j = i + 1;
+        while j < lines.len() && lines[j].indent > indent {
+            j += 1;
+        }
+
+        let rest = line.text[1..].trim_start_matches(' ');
+        let mut item_lines: Vec<Line> = Vec::new();
+        if !rest.is_empty() {
+            item_lines.push(Line {
+                no: line.no,
+                indent: indent + line.text.len() - rest.len(),
+                text: rest.to_string(),
+            });
+        }
+        item_lines.extend(lines[i + 1..j].iter().cloned());
+        let value = match item_lines.is_empty() {
+            true => Block::Inline,
+            false => parse_block(&item_lines, raw)?,
+        };
+        items.push(Item {
+            start: line.no,
+            end: lines[j - 1].no + 1,
+            value,
+        });
+        i = j;
+    }
+    Some(Block::Seq { indent, items })
+}
+
+fn parse_map(lines: &[Line], raw: &[&str]) -> Option<Block> {
+    let indent = lines[0].indent;
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+        if line.indent != indent {
+            return None;
+        }
+        let captures = KEY.captures(&line.text)?;
+        let key = unquote(&captures[1]);
+        let value_text = strip_comment(&line.text[captures[0].len()..]);
+        if value_text.starts_with('&') || value_text.starts_with('*') || value_text.starts_with('!') {
+            return None;
+        }
+
+        // Items of a sequence can have the same indentation as the key
+        let mut j = i + 1;
+        while j < lines.len()
+            && (lines[j].indent > indent
+                || (lines[j].indent == indent
+                    && (lines[j].text == "-" || lines[j].text.starts_with("- "))))
+        {
+            j += 1;
+        }
+        let value = if !value_text.is_empty() || j == i + 1 {
+            Block::Inline
+        } else {
+            parse_block(&lines[i + 1..j], raw)?
+        };
+
+        let raw_line = raw[line.no];
+        let dash = match raw_line.len() - raw_line.trim_start_matches(' ').len() == indent {
+            true => None,
+            false => Some(raw_line[..indent].to_string()),
+        };
+        entries.push(Entry {
+            key,
+            start: line.no,
+            end: lines[j - 1].no + 1,
+            dash,
+            value,
+        });
+        i = j;
+    }
+    Some(Block::Map { indent, entries })
+}
+
+fn unquote(key: &str) -> String {
+    let key = key.trim();
+    if key.len() >= 2
+        && ((key.starts_with('"') && key.ends_with('"'))
+            || (key.starts_with('\'') && key.ends_with('\'')))
+    {
+        return serde_yaml::from_str::<String>(key).unwrap_or_else(|_| key.to_string());
+    }
+    key.to_string()
+}
+
+fn strip_comment(text: &str) -> String {
+    let text = text.trim();
+    if text.starts_with('#') {
+        return String::new();
+    }
+    match text.find(" #") {
+        Some(i) if !text.starts_with('"') && !text.starts_with('\'') => text[..i].trim().to_string(),
+        _ => text.to_string(),
+    }
+}
+
+// Changes inside the block are added to edits, false means that the whole block must be replaced
+fn patch_value(block: &Block, old: &Value, new: &Value, style: Style, edits: &mut Vec<Edit>) -> bool {
+    if old == new {
+        return true;
+    }
+    let mut local: Vec<Edit> = Vec::new();
+    let patched = match (block, old, new) {
+        (Block::Map { indent, entries }, Value::Mapping(o), Value::Mapping(n)) => {
+            patch_map(*indent, entries, o, n, Style::of(*indent, entries, style), &mut local)
+        }
+        (Block::Seq { indent, items }, Value::Sequence(o), Value::Sequence(n)) => {
+            patch_seq(*indent, items, o, n, style, &mut local)
+        }
+        _ => false,
+    };
+    if patched {
+        edits.extend(local);
+    }
+    patched
+}
+
+fn patch_map(
+    indent: usize,
+    entries: &[Entry],
+    old: &Mapping,
+    new: &Mapping,
+    style: Style,
+    edits: &mut Vec<Edit>,
+) -> bool {
+    let find = |m: &Mapping, key: &str| -> Option<(Value, Value)> {
+        m.iter()
+            .find(|(k, _)| key_string(k).as_deref() == Some(key))
+            .map(|(k, v)| (k.clone(), v.clone()))
+    };
+
+    for e in entries.iter() {
+        let o = find(old, &e.key).map(|(_, v)| v);
+        match (o, find(new, &e.key)) {
+            (None, None) => {}
+            (Some(_), None) => {
+                if e.dash.is_some() {
+                    return false;
+                }
+                edits.push(Edit {
+                    start: e.start,
+                    end: e.end,
+                    lines: Vec::new(),
+                });
+            }
+            (o, Some((k, n))) => {
+                if o.as_ref() == Some(&n) {
+                    continue;
+                }
+                if let Some(o) = o {
+                    if patch_value(&e.value, &o, &n, style, edits) {
+                        continue;
+                    }
+                }
+                let mut lines = render_entry(indent, &k, &n, style);
+                if let Some(dash) = &e.dash {
+                    lines[0] = format!("{}{}", dash, &lines[0][indent..]);
+                }
+                edits.push(Edit {
+                    start: e.start,
+                    end: e.end,
+                    lines,
+                });
+            }
+        }
+    }
+
+    let end = match entries.last() {
+        Some(e) => e.end,
+        None => return false,
+    };
+    for (k, v) in new.iter() {
+        let key = match key_string(k) {
+            Some(key) => key,
+            None => return false,
+        };
+        if !entries.iter().any(|e| e.key == key) {
+            edits.push(Edit {
+                start: end,
+                end,
+                lines: render_entry(indent, k, v, style),
+            });
+        }
+    }
+    true
+}
+
+fn patch_seq(
+    indent: usize,
+    items: &[Item],
+    old: &Sequence,
+    new: &Sequence,
+    style: Style,
+    edits: &mut Vec<Edit>,
+) -> bool {
+    if items.len() != old.len() {
+        return false;
+    }
+
+    // Items that are not changed are found with the longest common subsequence
+    let (n_old, n_new) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; n_new + 1]; n_old + 1];
+    for i in (0..n_old).rev() {
+        for j in (0..n_new).rev() {
+            lcs[i][j] = match old[i] == new[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n_old && j < n_new {
+        if old[i] == new[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs.push((n_old, n_new));
+
+    // Between unchanged items, old items are changed into new ones, the rest is removed or added
+    let (mut i, mut j) = (0, 0);
+    for (pi, pj) in pairs {
+        let changed = (pi - i).min(pj - j);
+        for k in 0..changed {
+            let item = &items[i + k];
+            if !patch_value(&item.value, &old[i + k], &new[j + k], style, edits) {
+                edits.push(Edit {
+                    start: item.start,
+                    end: item.end,
+                    lines: render_item(indent, &new[j + k], style),
+                });
+            }
+        }
+        for item in items[i + changed..pi].iter() {
+            edits.push(Edit {
+                start: item.start,
+                end: item.end,
+                lines: Vec::new(),
+            });
+        }
+        let at = match i + changed {
+            0 => items.first().map_or(0, |item| item.start),
+            k => items[k - 1].end,
+        };
+        for v in new[j + changed..pj].iter() {
+            edits.push(Edit {
+                start: at,
+                end: at,
+                lines: render_item(indent, v, style),
+            });
+        }
+        i = pi + 1;
+        j = pj + 1;
+    }
+    true
+}
+
+// Nested blocks are written by the style, scalars and empty blocks are written by serde_yaml
+fn render_entry(indent: usize, key: &Value, value: &Value, style: Style) -> Vec<String> {
+    let nested = match value {
+        Value::Mapping(m) if !m.is_empty() => render_map(indent + style.map, m, style),
+        Value::Sequence(s) if !s.is_empty() => {
+            s.iter().flat_map(|v| render_item(indent + style.seq, v, style)).collect()
+        }
+        _ => {
+            let mut m = Mapping::new();
+            m.insert(key.clone(), value.clone());
+            return render(indent, &Value::Mapping(m));
+        }
+    };
+    let key = serde_yaml::to_string(key).unwrap_or_default();
+    let mut lines = vec![format!("{}{}:", " ".repeat(indent), key.trim_end())];
+    lines.extend(nested);
+    lines
+}
+
+fn render_map(indent: usize, value: &Mapping, style: Style) -> Vec<String> {
+    value.iter().flat_map(|(k, v)| render_entry(indent, k, v, style)).collect()
+}
+
+// Blocks in items start after the dash, so they're indented by two more spaces
+fn render_item(indent: usize, value: &Value, style: Style) -> Vec<String> {
+    let mut lines = match value {
+        Value::Mapping(m) if !m.is_empty() => render_map(indent + 2, m, style),
+        Value::Sequence(s) if !s.is_empty() => {
+            s.iter().flat_map(|v| render_item(indent + 2, v, style)).collect()
+        }
+        _ => return render(indent, &Value::Sequence(vec![value.clone()])),
+    };
+    lines[0] = format!("{}- {}", " ".repeat(indent), &lines[0][indent + 2..]);
+    lines
+}
+
+fn render(indent: usize, value: &Value) -> Vec<String> {
+    let prefix = " ".repeat(indent);
+    serde_yaml::to_string(value)
+        .unwrap_or_default()
+        .lines()
+        .map(|l| match l.is_empty() {
+            true => String::new(),
+            false => format!("{}{}", prefix, l),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_yaml::Value;
+
+    use super::patch;
+
+    #[test]
+    fn keep_comments() {
+        let original = r#"# Access of the backend
+meta:
+  version: V1
+config:
+  teams:
+    # Everybody is here
+    - name: default
+      projects: []
+      namespaces: []
+
+    - name: backend # the main team
+      projects:
+        - name: api
+          id: 10
+          access_level: Developer
+  users: []
+state: ''
+"#;
+        let mut new: Value = serde_yaml::from_str(original).unwrap();
+        new["config"]["teams"][1]["projects"][0]["access_level"] =
+            Value::String("Maintainer".to_string());
+        new["config"]["teams"]
+            .as_sequence_mut()
+            .unwrap()
+            .push(serde_yaml::from_str("name: frontend").unwrap());
+
+        let patched = patch(original, &new).unwrap();
+        // New items are added with the indentation of the sequence
+        let expected = original
+            .replace("access_level: Developer", "access_level: Maintainer")
+            .replace("  users: []", "    - name: frontend\n  users: []");
+        assert_eq!(patched, expected);
+    }
+
+    fn patched(original: &str, edit: impl Fn(&mut Value)) -> Option<String> {
+        let mut new: Value = serde_yaml::from_str(original).unwrap();
+        edit(&mut new);
+        patch(original, &new)
+    }
+
+    #[test]
+    fn remove_between_comments() {
+        let original = r#"users:
+  # Developers
+  - name: alice # on call
+    id: 1
+  # Contractors
+  - name: bob
+    id: 2
+  - name: carol
+    id: 3 # until May
+"#;
+        let patched = patched(original, |new| {
+            new["users"].as_sequence_mut().unwrap().remove(1);
+        })
+        .unwrap();
+        assert_eq!(patched, original.replace("  - name: bob\n    id: 2\n", ""));
+    }
+
+    #[test]
+    fn flow_sequences() {
+        let original = "teams:\n  - name: backend\n    includes: [core, db] # shared\n    projects: []\n";
+        // The flow sequence is kept while its neighbours change
+        let patched_text = patched(original, |new| {
+            new["teams"][0]["name"] = Value::String("api".to_string());
+        })
+        .unwrap();
+        assert_eq!(patched_text, original.replace("name: backend", "name: api"));
+
+        // It's written as a block sequence when it's changed, indented the same way as `teams`
+        let patched_text = patched(original, |new| {
+            new["teams"][0]["includes"]
+                .as_sequence_mut()
+                .unwrap()
+                .push(Value::String("cache".to_string()));
+        })
+        .unwrap();
+        assert_eq!(
+            patched_text,
+            "teams:\n  - name: backend\n    includes:\n      - core\n      - db\n      - cache\n    projects: []\n"
+        );
+    }
+
+    #[test]
+    fn quoted_keys() {
+        let original = "\"meta\":\n  'version': V1 # the old one\nstate: ''\n";
+        let patched = patched(original, |new| {
+            new["meta"]["version"] = Value::String("V2".to_string());
+        })
+        .unwrap();
+        assert_eq!(patched, "\"meta\":\n  version: V2\nstate: ''\n");
+    }
+
+    #[test]
+    fn block_scalars() {
+        let original = r#"groups:
+  - name: ops
+    description: |
+      People who are paged at night.
+
+      They need to see everything.
+    id: 5
+"#;
+        let patched_text = patched(original, |new| {
+            new["groups"][0]["id"] = Value::Number(6.into());
+        })
+        .unwrap();
+        assert_eq!(patched_text, original.replace("id: 5", "id: 6"));
+
+        let patched_text = patched(original, |new| {
+            new["groups"][0]["description"] = Value::String("On call".to_string());
+        })
+        .unwrap();
+        assert_eq!(
+            patched_text,
+            "groups:\n  - name: ops\n    description: On call\n    id: 5\n"
+        );
+    }
+
+    #[test]
+    fn rewrite_what_cant_be_patched() {
+        let change = |new: &mut Value| new["b"] = Value::Number(3.into());
+        // Anchors and aliases
+        assert_eq!(patched("a: &x 1\nb: *x\n", change), None);
+        // Several documents
+        assert_eq!(patched("---\na: 1\nb: 2\n", change), None);
+        // Not a yaml at all, e.g. tabs in indentation
+        let new: Value = serde_yaml::from_str("b: 3").unwrap();
+        assert_eq!(patch("a:\n\t- 1\nb: 2\n", &new), None);
+        assert_eq!(patch("a: [1", &new), None);
+        // Everything else is patched
+        assert_eq!(patched("a: 1\nb: 2\n", change).unwrap(), "a: 1\nb: 3\n");
+    }
+}